@@ -19,6 +19,7 @@ final class PlatformCoalescedKeyboard:
     Sendable
 {
     nonisolated(unsafe) let monitor: Any?
+    nonisolated(unsafe) let focusObservers: [NSObjectProtocol]
     nonisolated(unsafe) let context: UnsafeMutableRawPointer
     
     init(context: UnsafeMutableRawPointer) {
@@ -36,7 +37,16 @@ final class PlatformCoalescedKeyboard:
             }
             switch event.type {
             case .keyDown:
-                raw_input_key_notify_func(context,  eventWindow, event.keyCode, true)
+                if event.isARepeat {
+                    raw_input_key_repeat(context, eventWindow, event.keyCode)
+                }
+                else {
+                    raw_input_key_notify_func(context,  eventWindow, event.keyCode, true)
+                }
+                //command shortcuts are not text
+                if !event.modifierFlags.contains(.command), let characters = event.characters, !characters.isEmpty {
+                    characters.withCString { raw_input_key_text(context, eventWindow, $0) }
+                }
             case .keyUp:
                 raw_input_key_notify_func(context, eventWindow, event.keyCode, false)
             case .flagsChanged:
@@ -83,12 +93,33 @@ final class PlatformCoalescedKeyboard:
             
             return event
         }
+        //pass the context as an integer so the closures stay Sendable
+        let contextBits = Int(bitPattern: context)
+        func observeFocus(_ name: Notification.Name, focused: Bool) -> NSObjectProtocol {
+            NotificationCenter.default.addObserver(forName: name, object: nil, queue: .main) { notification in
+                let window: UnsafeMutableRawPointer?
+                if let w = notification.object as? NSWindow {
+                    window = Unmanaged.passUnretained(w).toOpaque()
+                }
+                else {
+                    window = nil
+                }
+                raw_input_key_focus(UnsafeRawPointer(bitPattern: contextBits), window, focused)
+            }
+        }
+        self.focusObservers = [
+            observeFocus(NSWindow.didBecomeKeyNotification, focused: true),
+            observeFocus(NSWindow.didResignKeyNotification, focused: false),
+        ]
 
     }
     deinit {
         if let monitor {
             NSEvent.removeMonitor(monitor)
         }
+        for observer in focusObservers {
+            NotificationCenter.default.removeObserver(observer)
+        }
         raw_input_finish_mouse_event_context(self.context)
         
     }
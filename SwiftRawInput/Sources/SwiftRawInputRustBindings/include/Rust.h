@@ -11,6 +11,9 @@
 extern void raw_input_finish_key_event_context(const void* context);
 extern void raw_input_finish_mouse_event_context(const void* context);
 extern void raw_input_key_notify_func(const void *context, void *window, uint16_t keyCode, bool pressed);
+extern void raw_input_key_repeat(const void *context, void *window, uint16_t keyCode);
+extern void raw_input_key_text(const void *context, void *window, const char *utf8);
+extern void raw_input_key_focus(const void *context, void *window, bool focused);
 extern void raw_input_mouse_move(const void *context, void *window, double windowPosX, double windowPosY, double windowWidth, double windowHeight);
 extern void raw_input_mouse_button(const void *context, void *window, uint8_t button, bool down);
 extern void raw_input_mouse_scroll(const void *context, void *window, double deltaX, double deltaY);
@@ -0,0 +1,674 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Named actions bound to keys and mouse buttons.
+//!
+//! Rather than asking whether `Space` is down, games usually want to ask whether the player is
+//! *jumping*, and let the player choose the key.  An [`ActionMap`](crate::action::ActionMap) maps
+//! named actions to one or more [`Binding`](crate::action::Binding)s, grouped into contexts such as
+//! `menu` and `gameplay`, and answers queries against a [`FrameInput`](crate::frame::FrameInput).
+//!
+//! Because the map only reads [`FrameInput`](crate::frame::FrameInput), it behaves identically on
+//! every platform.
+//!
+//! # Profiles
+//!
+//! Bindings can be saved to and loaded from a plain-text profile, so controls can be retuned
+//! without recompiling:
+//!
+//! ```text
+//! # lines starting with # are comments
+//! [gameplay]
+//! Jump = Space, MouseRight
+//! MoveX = D, A * -1, RightArrow, LeftArrow * -1
+//!
+//! [menu]
+//! Confirm = Return
+//! ```
+//!
+//! Each section is a context.  Each line binds an action to a comma-separated list of inputs.
+//! Context and action names may contain spaces, but not `=`, `[`, `]`, `#`, `,` or newlines.
+//! Keys are written as their [`KeyboardKey`](crate::keyboard::key::KeyboardKey) names; mouse
+//! buttons as `MouseLeft`, `MouseRight`, `MouseMiddle` or `Mouse<n>`.  An input may be followed by
+//! `* <scale>` to change its contribution to
+//! [`ActionMap::action_value`](crate::action::ActionMap::action_value).
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::action::{ActionMap, Binding};
+//! use app_input::frame::FrameInput;
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//! use app_input::mouse::Mouse;
+//!
+//! let mut actions = ActionMap::new();
+//! actions.bind("gameplay", "Jump", KeyboardKey::Space).unwrap();
+//! actions.bind("gameplay", "MoveX", KeyboardKey::D).unwrap();
+//! actions.bind("gameplay", "MoveX", Binding::from(KeyboardKey::A).with_scale(-1.0)).unwrap();
+//! actions.push_context("gameplay").unwrap();
+//!
+//! let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+//! // Once per frame:
+//! input.begin_frame();
+//! if actions.action_just_pressed(&input, "Jump") {
+//!     // jump
+//! }
+//! let speed = actions.action_value(&input, "MoveX");
+//! ```
+
+use crate::frame::FrameInput;
+use crate::keyboard::key::KeyboardKey;
+use crate::mouse::{MOUSE_BUTTON_LEFT, MOUSE_BUTTON_MIDDLE, MOUSE_BUTTON_RIGHT};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A key or mouse button that can be bound to an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Input {
+    /// A keyboard key.
+    Key(KeyboardKey),
+    /// A mouse button.  See [`Mouse::button_state`](crate::mouse::Mouse::button_state) for the meaning of the value.
+    MouseButton(u8),
+}
+
+impl Input {
+    pub(crate) fn is_pressed(self, input: &FrameInput) -> bool {
+        match self {
+            Input::Key(key) => input.is_pressed(key),
+            Input::MouseButton(button) => input.button_is_pressed(button),
+        }
+    }
+
+    fn just_pressed(self, input: &FrameInput) -> bool {
+        match self {
+            Input::Key(key) => input.just_pressed(key),
+            Input::MouseButton(button) => input.button_just_pressed(button),
+        }
+    }
+
+    fn just_released(self, input: &FrameInput) -> bool {
+        match self {
+            Input::Key(key) => input.just_released(key),
+            Input::MouseButton(button) => input.button_just_released(button),
+        }
+    }
+}
+
+impl From<KeyboardKey> for Input {
+    fn from(key: KeyboardKey) -> Self {
+        Input::Key(key)
+    }
+}
+
+/// Formats the input as it is written in a profile.
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Input::Key(key) => write!(f, "{key}"),
+            Input::MouseButton(MOUSE_BUTTON_LEFT) => write!(f, "MouseLeft"),
+            Input::MouseButton(MOUSE_BUTTON_RIGHT) => write!(f, "MouseRight"),
+            Input::MouseButton(MOUSE_BUTTON_MIDDLE) => write!(f, "MouseMiddle"),
+            Input::MouseButton(button) => write!(f, "Mouse{button}"),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let button = match s {
+            "MouseLeft" => Some(MOUSE_BUTTON_LEFT),
+            "MouseRight" => Some(MOUSE_BUTTON_RIGHT),
+            "MouseMiddle" => Some(MOUSE_BUTTON_MIDDLE),
+            _ => s.strip_prefix("Mouse").and_then(|n| n.parse().ok()),
+        };
+        match button {
+            Some(button) => Ok(Input::MouseButton(button)),
+            None => s.parse().map(Input::Key).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/**
+An input bound to an action, with the amount it contributes to [`ActionMap::action_value`].
+
+Bind several keys with opposite scales to the same action to build a one-dimensional axis:
+`D` with scale `1.0` and `A` with scale `-1.0`.
+*/
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Binding {
+    input: Input,
+    scale: f32,
+}
+
+impl Binding {
+    /// Creates a binding with a scale of `1.0`.
+    pub const fn new(input: Input) -> Self {
+        Binding { input, scale: 1.0 }
+    }
+
+    /// Creates a binding to a mouse button, with a scale of `1.0`.
+    pub const fn mouse_button(button: u8) -> Self {
+        Binding::new(Input::MouseButton(button))
+    }
+
+    /// Returns a copy of this binding with the specified scale.
+    pub const fn with_scale(self, scale: f32) -> Self {
+        Binding {
+            input: self.input,
+            scale,
+        }
+    }
+
+    /// The bound input.
+    pub const fn input(self) -> Input {
+        self.input
+    }
+
+    /// The amount the input contributes to [`ActionMap::action_value`] while pressed.
+    pub const fn scale(self) -> f32 {
+        self.scale
+    }
+}
+
+impl From<KeyboardKey> for Binding {
+    fn from(key: KeyboardKey) -> Self {
+        Binding::new(Input::Key(key))
+    }
+}
+
+impl From<Input> for Binding {
+    fn from(input: Input) -> Self {
+        Binding::new(input)
+    }
+}
+
+/// The error returned when a context or action name cannot be written to a profile.
+///
+/// Names must be non-empty, must not start or end with whitespace, and must not contain `=`, `[`,
+/// `]`, `#`, `,` or control characters such as newlines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidNameError {
+    name: String,
+}
+
+impl InvalidNameError {
+    /// The rejected name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for InvalidNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid context or action name: {:?}", self.name)
+    }
+}
+
+impl std::error::Error for InvalidNameError {}
+
+/// Checks that `name` survives a round trip through a profile.
+fn validate_name(name: &str) -> Result<(), InvalidNameError> {
+    let valid = !name.is_empty()
+        && name.trim() == name
+        && !name
+            .chars()
+            .any(|c| matches!(c, '=' | '[' | ']' | '#' | ',') || c.is_control());
+    if valid {
+        Ok(())
+    } else {
+        Err(InvalidNameError {
+            name: name.to_string(),
+        })
+    }
+}
+
+/// The error returned when a profile cannot be loaded.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProfileError {
+    /// The profile could not be read.
+    Io(std::io::Error),
+    /// A line of the profile could not be parsed.
+    Syntax {
+        /// The 1-based line number.
+        line: usize,
+        /// A description of the problem.
+        message: String,
+    },
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::Io(e) => write!(f, "can't read profile: {e}"),
+            ProfileError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProfileError::Io(e) => Some(e),
+            ProfileError::Syntax { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(e: std::io::Error) -> Self {
+        ProfileError::Io(e)
+    }
+}
+
+/**
+Maps named actions to keys and mouse buttons, organized into contexts.
+
+A context is a named set of bindings, such as `menu` or `gameplay`.  Contexts form a stack, managed
+with [`ActionMap::push_context`] and [`ActionMap::pop_context`], and only the topmost context is
+active.  Queries for an action that the active context does not bind report it as not pressed.
+
+See the [module documentation](crate::action) for an example and the profile format.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ActionMap {
+    contexts: BTreeMap<String, BTreeMap<String, Vec<Binding>>>,
+    stack: Vec<String>,
+}
+
+impl ActionMap {
+    /// Creates a map with no bindings and no active context.
+    pub fn new() -> Self {
+        ActionMap::default()
+    }
+
+    /// Adds a binding for an action in a context, creating the context if needed.
+    ///
+    /// Binding an input that is already bound to the action replaces its scale.  Fails if either
+    /// name could not be saved in a profile; see [`InvalidNameError`].
+    pub fn bind(
+        &mut self,
+        context: &str,
+        action: &str,
+        binding: impl Into<Binding>,
+    ) -> Result<(), InvalidNameError> {
+        validate_name(context)?;
+        validate_name(action)?;
+        let binding = binding.into();
+        let bindings = self
+            .contexts
+            .entry(context.to_string())
+            .or_default()
+            .entry(action.to_string())
+            .or_default();
+        match bindings.iter_mut().find(|b| b.input == binding.input) {
+            Some(existing) => existing.scale = binding.scale,
+            None => bindings.push(binding),
+        }
+        Ok(())
+    }
+
+    /// Removes the binding of `input` from an action in a context.
+    ///
+    /// Returns `true` if the input was bound.
+    pub fn unbind(&mut self, context: &str, action: &str, input: Input) -> bool {
+        let Some(bindings) = self
+            .contexts
+            .get_mut(context)
+            .and_then(|actions| actions.get_mut(action))
+        else {
+            return false;
+        };
+        let len = bindings.len();
+        bindings.retain(|b| b.input != input);
+        bindings.len() != len
+    }
+
+    /// Removes every binding for an action in a context, for example before rebinding it.
+    pub fn clear_action(&mut self, context: &str, action: &str) {
+        if let Some(actions) = self.contexts.get_mut(context) {
+            actions.remove(action);
+        }
+    }
+
+    /// Returns the bindings for an action in a context.
+    pub fn bindings(&self, context: &str, action: &str) -> &[Binding] {
+        self.contexts
+            .get(context)
+            .and_then(|actions| actions.get(action))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the names of the actions bound in a context.
+    pub fn actions(&self, context: &str) -> impl Iterator<Item = &str> {
+        self.contexts
+            .get(context)
+            .into_iter()
+            .flat_map(|actions| actions.keys().map(String::as_str))
+    }
+
+    /// Makes `context` the active context, on top of the current one.
+    ///
+    /// Fails if `context` is not a valid name; see [`InvalidNameError`].
+    pub fn push_context(&mut self, context: &str) -> Result<(), InvalidNameError> {
+        validate_name(context)?;
+        self.stack.push(context.to_string());
+        Ok(())
+    }
+
+    /// Deactivates the active context, reactivating the one below it.  Returns the removed context.
+    pub fn pop_context(&mut self) -> Option<String> {
+        self.stack.pop()
+    }
+
+    /// Returns the active context, if any.
+    pub fn active_context(&self) -> Option<&str> {
+        self.stack.last().map(String::as_str)
+    }
+
+    fn active_bindings(&self, action: &str) -> &[Binding] {
+        match self.active_context() {
+            Some(context) => self.bindings(context, action),
+            None => &[],
+        }
+    }
+
+    /// Returns `true` if any input bound to the action was held at the start of this frame.
+    pub fn action_pressed(&self, input: &FrameInput, action: &str) -> bool {
+        self.active_bindings(action)
+            .iter()
+            .any(|b| b.input.is_pressed(input))
+    }
+
+    /// Returns `true` if any input bound to the action was pressed between the previous frame and this one.
+    ///
+    /// See [`FrameInput::just_pressed`].
+    pub fn action_just_pressed(&self, input: &FrameInput, action: &str) -> bool {
+        self.active_bindings(action)
+            .iter()
+            .any(|b| b.input.just_pressed(input))
+    }
+
+    /// Returns `true` if any input bound to the action was released between the previous frame and this one.
+    ///
+    /// See [`FrameInput::just_released`].
+    pub fn action_just_released(&self, input: &FrameInput, action: &str) -> bool {
+        self.active_bindings(action)
+            .iter()
+            .any(|b| b.input.just_released(input))
+    }
+
+    /**
+    Returns the sum of the scales of the held inputs bound to the action, clamped to `-1.0..=1.0`.
+
+    With the default scale this is `1.0` while the action is pressed and `0.0` otherwise.  With `D`
+    bound at `1.0` and `A` at `-1.0`, it is `0.0` when both or neither are held.
+    */
+    pub fn action_value(&self, input: &FrameInput, action: &str) -> f32 {
+        self.active_bindings(action)
+            .iter()
+            .filter(|b| b.input.is_pressed(input))
+            .map(|b| b.scale)
+            .sum::<f32>()
+            .clamp(-1.0, 1.0)
+    }
+
+    /**
+    Formats every binding as a profile.  See the [module documentation](crate::action) for the format.
+
+    The context stack is not saved.
+    */
+    pub fn to_profile(&self) -> String {
+        let mut out = String::new();
+        for (context, actions) in &self.contexts {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("[{context}]\n"));
+            for (action, bindings) in actions {
+                let inputs: Vec<String> = bindings
+                    .iter()
+                    .map(|b| {
+                        if b.scale == 1.0 {
+                            b.input.to_string()
+                        } else {
+                            format!("{} * {}", b.input, b.scale)
+                        }
+                    })
+                    .collect();
+                out.push_str(&format!("{action} = {}\n", inputs.join(", ")));
+            }
+        }
+        out
+    }
+
+    /// Parses a profile created by [`ActionMap::to_profile`] or written by hand.
+    pub fn from_profile(profile: &str) -> Result<Self, ProfileError> {
+        let mut map = ActionMap::new();
+        let mut context: Option<String> = None;
+        for (index, line) in profile.lines().enumerate() {
+            let syntax = |message: String| ProfileError::Syntax {
+                line: index + 1,
+                message,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                validate_name(name).map_err(|e| syntax(e.to_string()))?;
+                map.contexts.entry(name.to_string()).or_default();
+                context = Some(name.to_string());
+                continue;
+            }
+            let Some(context) = &context else {
+                return Err(syntax("binding outside of a [context] section".to_string()));
+            };
+            let Some((action, inputs)) = line.split_once('=') else {
+                return Err(syntax(format!(
+                    "expected `action = inputs`, found {line:?}"
+                )));
+            };
+            let action = action.trim();
+            validate_name(action).map_err(|e| syntax(e.to_string()))?;
+            map.contexts
+                .get_mut(context)
+                .unwrap()
+                .entry(action.to_string())
+                .or_default();
+            for item in inputs.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+                let (input, scale) = match item.split_once('*') {
+                    Some((input, scale)) => {
+                        let scale = scale
+                            .trim()
+                            .parse()
+                            .map_err(|_| syntax(format!("invalid scale in {item:?}")))?;
+                        (input, scale)
+                    }
+                    None => (item, 1.0),
+                };
+                let input: Input = input.parse().map_err(syntax)?;
+                map.bind(context, action, Binding::new(input).with_scale(scale))
+                    .map_err(|e| syntax(e.to_string()))?;
+            }
+        }
+        Ok(map)
+    }
+
+    /// Writes every binding to a profile file.  See [`ActionMap::to_profile`].
+    pub fn save_profile(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_profile())
+    }
+
+    /// Reads bindings from a profile file.  See [`ActionMap::from_profile`].
+    pub fn load_profile(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        ActionMap::from_profile(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::action::{ActionMap, Binding, Input, ProfileError};
+    use crate::frame::FrameInput;
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{MOUSE_BUTTON_RIGHT, Mouse};
+
+    fn press(input: &FrameInput, key: KeyboardKey, down: bool) {
+        input
+            .keyboard()
+            .shared_for_test()
+            .set_key_state(key, down, std::ptr::null_mut());
+    }
+
+    #[test]
+    fn test_contexts() {
+        let mut actions = ActionMap::new();
+        actions
+            .bind("gameplay", "Jump", KeyboardKey::Space)
+            .unwrap();
+        actions
+            .bind(
+                "gameplay",
+                "Jump",
+                Binding::mouse_button(MOUSE_BUTTON_RIGHT),
+            )
+            .unwrap();
+        actions.bind("menu", "Confirm", KeyboardKey::Space).unwrap();
+
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        press(&input, KeyboardKey::Space, true);
+        input.begin_frame();
+        assert!(!actions.action_pressed(&input, "Jump"));
+
+        actions.push_context("gameplay").unwrap();
+        assert!(actions.action_pressed(&input, "Jump"));
+        assert!(actions.action_just_pressed(&input, "Jump"));
+        assert!(!actions.action_pressed(&input, "Confirm"));
+
+        actions.push_context("menu").unwrap();
+        assert!(!actions.action_pressed(&input, "Jump"));
+        assert!(actions.action_pressed(&input, "Confirm"));
+        assert_eq!(actions.pop_context().as_deref(), Some("menu"));
+        assert!(actions.action_pressed(&input, "Jump"));
+
+        input.begin_frame();
+        assert!(!actions.action_just_pressed(&input, "Jump"));
+        press(&input, KeyboardKey::Space, false);
+        input.begin_frame();
+        assert!(actions.action_just_released(&input, "Jump"));
+    }
+
+    #[test]
+    fn test_value() {
+        let mut actions = ActionMap::new();
+        actions.bind("gameplay", "MoveX", KeyboardKey::D).unwrap();
+        actions
+            .bind(
+                "gameplay",
+                "MoveX",
+                Binding::from(KeyboardKey::A).with_scale(-1.0),
+            )
+            .unwrap();
+        actions.push_context("gameplay").unwrap();
+
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        press(&input, KeyboardKey::A, true);
+        input.begin_frame();
+        assert_eq!(actions.action_value(&input, "MoveX"), -1.0);
+        press(&input, KeyboardKey::D, true);
+        input.begin_frame();
+        assert_eq!(actions.action_value(&input, "MoveX"), 0.0);
+
+        assert!(actions.unbind("gameplay", "MoveX", Input::Key(KeyboardKey::A)));
+        assert_eq!(actions.action_value(&input, "MoveX"), 1.0);
+    }
+
+    #[test]
+    fn test_profile_round_trip() {
+        let profile = "\
+# comment
+[gameplay]
+Jump = Space, MouseRight
+MoveX = D, A * -1, Mouse4 * 0.5
+
+[menu]
+Confirm = Return
+";
+        let actions = ActionMap::from_profile(profile).unwrap();
+        assert_eq!(
+            actions.bindings("gameplay", "MoveX"),
+            &[
+                Binding::from(KeyboardKey::D),
+                Binding::from(KeyboardKey::A).with_scale(-1.0),
+                Binding::mouse_button(4).with_scale(0.5),
+            ]
+        );
+        let saved = actions.to_profile();
+        let reloaded = ActionMap::from_profile(&saved).unwrap();
+        assert_eq!(reloaded.to_profile(), saved);
+        assert_eq!(
+            reloaded.actions("gameplay").collect::<Vec<_>>(),
+            ["Jump", "MoveX"]
+        );
+
+        match ActionMap::from_profile("[gameplay]\nJump = Hyper") {
+            Err(ProfileError::Syntax { line: 2, .. }) => {}
+            other => panic!("{other:?}"),
+        }
+        match ActionMap::from_profile("[gameplay]\nFire = Mouse256") {
+            Err(ProfileError::Syntax { line: 2, .. }) => {}
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_last_button() {
+        let mut actions = ActionMap::from_profile("[gameplay]\nFire = Mouse255").unwrap();
+        actions.push_context("gameplay").unwrap();
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        input
+            .mouse()
+            .shared_for_test()
+            .set_key_state(u8::MAX, true, std::ptr::null_mut());
+        input.begin_frame();
+        assert!(actions.action_pressed(&input, "Fire"));
+        assert!(actions.action_just_pressed(&input, "Fire"));
+    }
+
+    #[test]
+    fn test_names() {
+        let mut actions = ActionMap::new();
+        for name in ["", " Jump", "a=b", "[x]", "#tag", "a,b", "two\nlines"] {
+            assert!(actions.bind("gameplay", name, KeyboardKey::Space).is_err());
+            assert!(actions.bind(name, "Jump", KeyboardKey::Space).is_err());
+            assert!(actions.push_context(name).is_err());
+        }
+        assert!(actions.to_profile().is_empty());
+
+        //binding an input again replaces its scale
+        actions.bind("menu", "Scroll Up", KeyboardKey::W).unwrap();
+        actions
+            .bind(
+                "menu",
+                "Scroll Up",
+                Binding::from(KeyboardKey::W).with_scale(0.5),
+            )
+            .unwrap();
+        assert_eq!(
+            actions.bindings("menu", "Scroll Up"),
+            &[Binding::from(KeyboardKey::W).with_scale(0.5)]
+        );
+        let reloaded = ActionMap::from_profile(&actions.to_profile()).unwrap();
+        assert_eq!(reloaded.to_profile(), actions.to_profile());
+    }
+}
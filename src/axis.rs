@@ -0,0 +1,342 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Analog values derived from digital keys and the scroll wheel.
+//!
+//! A [`VirtualAxis`](crate::axis::VirtualAxis) turns a pair of opposing key sets into a value
+//! between `-1.0` and `1.0`, and a [`VirtualAxis2D`](crate::axis::VirtualAxis2D) combines two of
+//! them into a vector whose length never exceeds `1.0`, so moving diagonally is no faster than
+//! moving straight.
+//!
+//! Axes read a [`FrameInput`](crate::frame::FrameInput).  Call `update` once per frame, after
+//! [`FrameInput::begin_frame`](crate::frame::FrameInput::begin_frame).
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::axis::{OppositePolicy, VirtualAxis2D};
+//! use app_input::frame::FrameInput;
+//! use app_input::keyboard::Keyboard;
+//! use app_input::mouse::Mouse;
+//!
+//! let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+//! let mut movement = VirtualAxis2D::wasd().with_policy(OppositePolicy::LastWins);
+//!
+//! // Once per frame:
+//! input.begin_frame();
+//! movement.update(&input);
+//! let (x, y) = movement.value();
+//! assert!(x * x + y * y <= 1.0);
+//! ```
+
+use crate::action::Input;
+use crate::frame::FrameInput;
+use crate::keyboard::key::KeyboardKey;
+
+/// What a [`VirtualAxis`] reports while keys on both sides are held.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OppositePolicy {
+    /// The sides cancel out, and the axis reports `0.0`.
+    #[default]
+    Cancel,
+    /// The side pressed most recently wins.
+    LastWins,
+    /// The side pressed first wins.
+    FirstWins,
+}
+
+/// A direction of the scroll wheel, as reported by [`FrameInput::scroll_delta`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollDirection {
+    /// The first element of [`FrameInput::scroll_delta`].
+    Horizontal,
+    /// The second element of [`FrameInput::scroll_delta`].
+    Vertical,
+}
+
+/// The keys on one side of an axis, and the frame on which one of them was first held.
+#[derive(Debug, Clone, PartialEq)]
+struct Side {
+    inputs: Vec<Input>,
+    held_since: Option<u64>,
+}
+
+impl Side {
+    fn new(inputs: impl IntoIterator<Item = impl Into<Input>>) -> Self {
+        Side {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            held_since: None,
+        }
+    }
+
+    fn update(&mut self, input: &FrameInput, frame: u64) {
+        let held = self.inputs.iter().any(|i| i.is_pressed(input));
+        self.held_since = match (held, self.held_since) {
+            (false, _) => None,
+            (true, None) => Some(frame),
+            (true, since) => since,
+        };
+    }
+}
+
+/**
+A value between `-1.0` and `1.0` driven by two sets of inputs, such as `A` and `D`.
+
+While only a negative input is held the axis is `-1.0`; while only a positive input is held it is
+`1.0`.  While both sides are held, the [`OppositePolicy`] decides.  The policy compares the frame on
+which each side was first held, so if both sides were first held on the same frame they cancel
+regardless of policy.
+
+Optionally, the scroll wheel can drive the axis too.  See [`VirtualAxis::with_scroll`].
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualAxis {
+    negative: Side,
+    positive: Side,
+    policy: OppositePolicy,
+    scroll: Option<(ScrollDirection, f32)>,
+    frame: u64,
+    value: f32,
+}
+
+impl VirtualAxis {
+    /// Creates an axis that is `-1.0` while any of `negative` is held and `1.0` while any of `positive` is held.
+    pub fn new(
+        negative: impl IntoIterator<Item = impl Into<Input>>,
+        positive: impl IntoIterator<Item = impl Into<Input>>,
+    ) -> Self {
+        VirtualAxis {
+            negative: Side::new(negative),
+            positive: Side::new(positive),
+            policy: OppositePolicy::default(),
+            scroll: None,
+            frame: 0,
+            value: 0.0,
+        }
+    }
+
+    /// Creates an axis driven only by the scroll wheel.  See [`VirtualAxis::with_scroll`].
+    pub fn scroll(direction: ScrollDirection, scale: f32) -> Self {
+        VirtualAxis::new([] as [Input; 0], [] as [Input; 0]).with_scroll(direction, scale)
+    }
+
+    /// Returns a copy of this axis with the specified policy for opposite inputs.
+    pub fn with_policy(mut self, policy: OppositePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /**
+    Returns a copy of this axis that also adds the scroll delta in `direction`, multiplied by `scale`.
+
+    Scroll deltas are not normalized, so the value of an axis with scroll may leave `-1.0..=1.0`.
+    Choose `scale` to suit the platform's scroll units.
+    */
+    pub fn with_scroll(mut self, direction: ScrollDirection, scale: f32) -> Self {
+        self.scroll = Some((direction, scale));
+        self
+    }
+
+    /// Reads the inputs for this frame.  Call once per frame, after [`FrameInput::begin_frame`].
+    pub fn update(&mut self, input: &FrameInput) {
+        self.frame += 1;
+        self.negative.update(input, self.frame);
+        self.positive.update(input, self.frame);
+        let keys = match (self.negative.held_since, self.positive.held_since) {
+            (None, None) => 0.0,
+            (Some(_), None) => -1.0,
+            (None, Some(_)) => 1.0,
+            (Some(negative), Some(positive)) => match (self.policy, negative.cmp(&positive)) {
+                (_, std::cmp::Ordering::Equal) | (OppositePolicy::Cancel, _) => 0.0,
+                (OppositePolicy::LastWins, std::cmp::Ordering::Greater)
+                | (OppositePolicy::FirstWins, std::cmp::Ordering::Less) => -1.0,
+                (OppositePolicy::LastWins, std::cmp::Ordering::Less)
+                | (OppositePolicy::FirstWins, std::cmp::Ordering::Greater) => 1.0,
+            },
+        };
+        let scroll = match self.scroll {
+            Some((direction, scale)) => {
+                let (x, y) = input.scroll_delta();
+                let delta = match direction {
+                    ScrollDirection::Horizontal => x,
+                    ScrollDirection::Vertical => y,
+                };
+                delta as f32 * scale
+            }
+            None => 0.0,
+        };
+        self.value = keys + scroll;
+    }
+
+    /// Returns the value computed by the most recent [`VirtualAxis::update`].
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+/**
+A vector driven by four sets of inputs, such as WASD or the arrow keys.
+
+The vector follows the crate's upper-left coordinate system: `x` increases to the right and `y`
+increases downward, so `W` produces `(0.0, -1.0)`.
+
+When the combined vector is longer than `1.0`, as when moving diagonally, it is scaled to unit
+length.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualAxis2D {
+    x: VirtualAxis,
+    y: VirtualAxis,
+    value: (f32, f32),
+}
+
+impl VirtualAxis2D {
+    /// Creates a vector from a horizontal and a vertical axis.
+    pub fn new(x: VirtualAxis, y: VirtualAxis) -> Self {
+        VirtualAxis2D {
+            x,
+            y,
+            value: (0.0, 0.0),
+        }
+    }
+
+    /// Creates a vector driven by the specified keys.
+    pub fn from_keys(
+        left: KeyboardKey,
+        right: KeyboardKey,
+        up: KeyboardKey,
+        down: KeyboardKey,
+    ) -> Self {
+        VirtualAxis2D::new(
+            VirtualAxis::new([left], [right]),
+            VirtualAxis::new([up], [down]),
+        )
+    }
+
+    /// Creates a vector driven by W, A, S and D.
+    pub fn wasd() -> Self {
+        VirtualAxis2D::from_keys(
+            KeyboardKey::A,
+            KeyboardKey::D,
+            KeyboardKey::W,
+            KeyboardKey::S,
+        )
+    }
+
+    /// Creates a vector driven by the arrow keys.
+    pub fn arrows() -> Self {
+        VirtualAxis2D::from_keys(
+            KeyboardKey::LeftArrow,
+            KeyboardKey::RightArrow,
+            KeyboardKey::UpArrow,
+            KeyboardKey::DownArrow,
+        )
+    }
+
+    /// Returns a copy of this vector with the specified policy on both axes.
+    pub fn with_policy(self, policy: OppositePolicy) -> Self {
+        VirtualAxis2D::new(self.x.with_policy(policy), self.y.with_policy(policy))
+    }
+
+    /// Reads the inputs for this frame.  Call once per frame, after [`FrameInput::begin_frame`].
+    pub fn update(&mut self, input: &FrameInput) {
+        self.x.update(input);
+        self.y.update(input);
+        let (x, y) = (self.x.value(), self.y.value());
+        let length = (x * x + y * y).sqrt();
+        self.value = if length > 1.0 {
+            (x / length, y / length)
+        } else {
+            (x, y)
+        };
+    }
+
+    /// Returns the vector computed by the most recent [`VirtualAxis2D::update`].
+    pub fn value(&self) -> (f32, f32) {
+        self.value
+    }
+
+    /// Returns the horizontal axis.
+    pub fn x(&self) -> &VirtualAxis {
+        &self.x
+    }
+
+    /// Returns the vertical axis.
+    pub fn y(&self) -> &VirtualAxis {
+        &self.y
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::axis::{OppositePolicy, ScrollDirection, VirtualAxis, VirtualAxis2D};
+    use crate::frame::FrameInput;
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::Mouse;
+
+    fn set(input: &FrameInput, key: KeyboardKey, down: bool) {
+        input
+            .keyboard()
+            .shared_for_test()
+            .set_key_state(key, down, std::ptr::null_mut());
+    }
+
+    #[test]
+    fn test_policies() {
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        let mut axes = [
+            VirtualAxis::new([KeyboardKey::A], [KeyboardKey::D]),
+            VirtualAxis::new([KeyboardKey::A], [KeyboardKey::D])
+                .with_policy(OppositePolicy::LastWins),
+            VirtualAxis::new([KeyboardKey::A], [KeyboardKey::D])
+                .with_policy(OppositePolicy::FirstWins),
+        ];
+        let mut step = |input: &mut FrameInput| {
+            input.begin_frame();
+            axes.iter_mut()
+                .map(|axis| {
+                    axis.update(input);
+                    axis.value()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(step(&mut input), [0.0, 0.0, 0.0]);
+        set(&input, KeyboardKey::A, true);
+        assert_eq!(step(&mut input), [-1.0, -1.0, -1.0]);
+        set(&input, KeyboardKey::D, true);
+        assert_eq!(step(&mut input), [0.0, 1.0, -1.0]);
+        set(&input, KeyboardKey::A, false);
+        assert_eq!(step(&mut input), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_diagonal_normalized() {
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        let mut movement = VirtualAxis2D::wasd();
+        set(&input, KeyboardKey::W, true);
+        set(&input, KeyboardKey::D, true);
+        input.begin_frame();
+        movement.update(&input);
+        let (x, y) = movement.value();
+        assert!((x - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert!((y + std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        let mut zoom = VirtualAxis::scroll(ScrollDirection::Vertical, 0.5);
+        input
+            .mouse()
+            .shared_for_test()
+            .add_scroll_delta(0.0, -4.0, std::ptr::null_mut());
+        input.begin_frame();
+        zoom.update(&input);
+        assert_eq!(zoom.value(), -2.0);
+        input.begin_frame();
+        zoom.update(&input);
+        assert_eq!(zoom.value(), 0.0);
+    }
+}
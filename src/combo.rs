@@ -0,0 +1,439 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timed sequences of presses, such as fighting-game motion inputs.
+//!
+//! A [`Combo`](crate::combo::Combo) is an ordered list of
+//! [`ComboStep`](crate::combo::ComboStep)s.  Each step is a group of inputs that must be held
+//! together, completed by pressing the last of them, within a time window of the previous step.
+//! A [`ComboRecognizer`](crate::combo::ComboRecognizer) matches any number of combos against the
+//! events from [`Keyboard::drain_events`](crate::keyboard::Keyboard::drain_events) and
+//! [`Mouse::drain_button_events`](crate::mouse::Mouse::drain_button_events).
+//!
+//! # Example
+//!
+//! A quarter-circle forward punch: Down, Down+Right, then Right+Punch, within 300 ms.
+//!
+//! ```
+//! use app_input::combo::{Combo, ComboRecognizer, ComboStep};
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//! use std::time::Duration;
+//!
+//! let fireball = Combo::new()
+//!     .then(ComboStep::press(KeyboardKey::DownArrow))
+//!     .then(ComboStep::together([KeyboardKey::DownArrow, KeyboardKey::RightArrow]))
+//!     .then(ComboStep::together([KeyboardKey::RightArrow, KeyboardKey::J]))
+//!     .within(Duration::from_millis(300));
+//! let mut combos = ComboRecognizer::new();
+//! let fireball = combos.add(fireball);
+//!
+//! let keyboard = Keyboard::coalesced();
+//! for event in &keyboard.drain_events() {
+//!     for matched in combos.feed_key_event(event) {
+//!         if matched.combo() == fireball {
+//!             println!("fireball! confidence {}", matched.confidence());
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::action::Input;
+use crate::keyboard::KeyEvent;
+use crate::mouse::ButtonEvent;
+use crate::timestamp::Timestamp;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// One step of a [`Combo`]: a group of inputs held together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComboStep {
+    inputs: Vec<Input>,
+    window: Duration,
+    max_spread: Option<Duration>,
+}
+
+impl ComboStep {
+    /// The time allowed between one step and the next, unless changed with [`ComboStep::window`].
+    pub const DEFAULT_WINDOW: Duration = Duration::from_millis(200);
+
+    /// A step completed by pressing `input`.
+    pub fn press(input: impl Into<Input>) -> Self {
+        ComboStep::together([input])
+    }
+
+    /**
+    A step completed by pressing the last of `inputs` while the others are held.
+
+    The others may have been pressed at any earlier time, for example in a previous step.  To
+    require that all the inputs are pressed at nearly the same moment, as with Punch+Kick, use
+    [`ComboStep::max_spread`].
+    */
+    pub fn together(inputs: impl IntoIterator<Item = impl Into<Input>>) -> Self {
+        ComboStep {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            window: ComboStep::DEFAULT_WINDOW,
+            max_spread: None,
+        }
+    }
+
+    /// Returns a copy of this step that must complete within `window` of the previous step.
+    ///
+    /// The window of the first step is not used.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Returns a copy of this step whose inputs must all be pressed within `spread` of each other.
+    pub fn max_spread(mut self, spread: Duration) -> Self {
+        self.max_spread = Some(spread);
+        self
+    }
+
+    /// The inputs that must be held together.
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+}
+
+/// An ordered sequence of [`ComboStep`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Combo {
+    steps: Vec<ComboStep>,
+    total: Option<Duration>,
+}
+
+impl Combo {
+    /// Creates a combo with no steps.
+    pub fn new() -> Self {
+        Combo::default()
+    }
+
+    /// Returns a copy of this combo with `step` appended.
+    pub fn then(mut self, step: ComboStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Returns a copy of this combo that must be completed within `total` of its first step,
+    /// in addition to the window of each step.
+    pub fn within(mut self, total: Duration) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// The steps in order.
+    pub fn steps(&self) -> &[ComboStep] {
+        &self.steps
+    }
+}
+
+/// Identifies a combo added with [`ComboRecognizer::add`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComboId(usize);
+
+/// A completed combo, returned by [`ComboRecognizer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComboMatch {
+    combo: ComboId,
+    step_times: Vec<Timestamp>,
+    confidence: f32,
+}
+
+impl ComboMatch {
+    /// The combo that was completed.
+    pub fn combo(&self) -> ComboId {
+        self.combo
+    }
+
+    /// The time at which each step was completed, in order.
+    pub fn step_times(&self) -> &[Timestamp] {
+        &self.step_times
+    }
+
+    /// The time at which the first step was completed.
+    pub fn started(&self) -> Timestamp {
+        self.step_times[0]
+    }
+
+    /// The time at which the final step was completed.
+    pub fn finished(&self) -> Timestamp {
+        *self.step_times.last().unwrap()
+    }
+
+    /// The time from the first step to the last.
+    pub fn duration(&self) -> Duration {
+        self.finished() - self.started()
+    }
+
+    /**
+    How comfortably the combo fit its timing windows, from `0.0` to `1.0`.
+
+    Each step after the first scores `1.0` if it followed the previous step instantly, falling
+    linearly to `0.0` at the edge of its window; the confidence is the mean of those scores.  A
+    single-step combo always has a confidence of `1.0`.  Use it to grade execution, or to prefer one
+    of several combos completed by the same press.
+    */
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+/// A partially completed combo.
+#[derive(Debug, Clone, PartialEq)]
+struct Attempt {
+    combo: usize,
+    step_times: Vec<Timestamp>,
+}
+
+/**
+Matches key and button events against a set of [`Combo`]s.
+
+Presses of inputs that are not part of the next step are ignored rather than breaking the combo,
+so mashing other buttons does not prevent a match.  Key repeats are ignored.  If several partial
+matches of the same combo are in progress, the first to complete wins and the rest are abandoned.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ComboRecognizer {
+    combos: Vec<Combo>,
+    attempts: Vec<Attempt>,
+    /// Inputs currently held, and when each was pressed.
+    held: HashMap<Input, Timestamp>,
+}
+
+impl ComboRecognizer {
+    /// Creates a recognizer with no combos.
+    pub fn new() -> Self {
+        ComboRecognizer::default()
+    }
+
+    /// Adds a combo to recognize.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the combo has no steps, or a step has no inputs.
+    pub fn add(&mut self, combo: Combo) -> ComboId {
+        assert!(!combo.steps.is_empty(), "combo has no steps");
+        assert!(
+            combo.steps.iter().all(|step| !step.inputs.is_empty()),
+            "combo step has no inputs"
+        );
+        self.combos.push(combo);
+        ComboId(self.combos.len() - 1)
+    }
+
+    /// Processes a key event, returning the combos it completed.
+    pub fn feed_key_event(&mut self, event: &KeyEvent) -> Vec<ComboMatch> {
+        if event.repeat() {
+            return Vec::new();
+        }
+        self.feed(Input::Key(event.key()), event.down(), event.timestamp())
+    }
+
+    /// Processes a mouse button event, returning the combos it completed.
+    pub fn feed_button_event(&mut self, event: &ButtonEvent) -> Vec<ComboMatch> {
+        self.feed(
+            Input::MouseButton(event.button()),
+            event.down(),
+            event.timestamp(),
+        )
+    }
+
+    /// Processes a press or release of `input` at the specified time, returning the combos it completed.
+    pub fn feed(&mut self, input: Input, down: bool, at: Timestamp) -> Vec<ComboMatch> {
+        if !down {
+            self.held.remove(&input);
+            return Vec::new();
+        }
+        self.held.entry(input).or_insert(at);
+
+        //drop attempts that ran out of time
+        let combos = &self.combos;
+        self.attempts.retain(|attempt| {
+            let combo = &combos[attempt.combo];
+            let step = &combo.steps[attempt.step_times.len()];
+            let last = *attempt.step_times.last().unwrap();
+            let first = attempt.step_times[0];
+            at - last <= step.window && combo.total.is_none_or(|total| at - first <= total)
+        });
+
+        let mut advanced = Vec::new();
+        for attempt in &mut self.attempts {
+            let step = &self.combos[attempt.combo].steps[attempt.step_times.len()];
+            if step_completed(step, input, at, &self.held) {
+                attempt.step_times.push(at);
+            }
+            advanced.push(attempt.clone());
+        }
+        for (index, combo) in self.combos.iter().enumerate() {
+            if step_completed(&combo.steps[0], input, at, &self.held) {
+                advanced.push(Attempt {
+                    combo: index,
+                    step_times: vec![at],
+                });
+            }
+        }
+
+        let mut matches = Vec::new();
+        self.attempts.clear();
+        for attempt in advanced {
+            let combo = &self.combos[attempt.combo];
+            if attempt.step_times.len() == combo.steps.len() {
+                if !matches
+                    .iter()
+                    .any(|m: &ComboMatch| m.combo.0 == attempt.combo)
+                {
+                    matches.push(ComboMatch {
+                        combo: ComboId(attempt.combo),
+                        confidence: confidence(combo, &attempt.step_times),
+                        step_times: attempt.step_times,
+                    });
+                }
+            } else if !self.attempts.contains(&attempt) {
+                self.attempts.push(attempt);
+            }
+        }
+        self.attempts
+            .retain(|attempt| !matches.iter().any(|m| m.combo.0 == attempt.combo));
+        matches
+    }
+
+    /// Forgets held inputs and partial matches, as when the application loses focus.
+    pub fn reset(&mut self) {
+        self.attempts.clear();
+        self.held.clear();
+    }
+}
+
+/// Returns `true` if pressing `input` at `at` completes `step`.
+fn step_completed(
+    step: &ComboStep,
+    input: Input,
+    at: Timestamp,
+    held: &HashMap<Input, Timestamp>,
+) -> bool {
+    if !step.inputs.contains(&input) || !step.inputs.iter().all(|i| held.contains_key(i)) {
+        return false;
+    }
+    match step.max_spread {
+        Some(spread) => step.inputs.iter().all(|i| at - held[i] <= spread),
+        None => true,
+    }
+}
+
+fn confidence(combo: &Combo, step_times: &[Timestamp]) -> f32 {
+    let scores: Vec<f32> = combo.steps[1..]
+        .iter()
+        .zip(step_times.windows(2))
+        .map(|(step, times)| {
+            if step.window.is_zero() {
+                1.0
+            } else {
+                1.0 - ((times[1] - times[0]).as_secs_f32() / step.window.as_secs_f32()).min(1.0)
+            }
+        })
+        .collect();
+    if scores.is_empty() {
+        1.0
+    } else {
+        scores.iter().sum::<f32>() / scores.len() as f32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::action::Input;
+    use crate::combo::{Combo, ComboRecognizer, ComboStep};
+    use crate::keyboard::key::KeyboardKey;
+    use crate::timestamp::Timestamp;
+    use std::time::Duration;
+
+    fn ms(ms: u64) -> Timestamp {
+        Timestamp::from_duration_since_epoch(Duration::from_millis(ms))
+    }
+
+    fn key(key: KeyboardKey) -> Input {
+        Input::Key(key)
+    }
+
+    fn fireball() -> Combo {
+        Combo::new()
+            .then(ComboStep::press(KeyboardKey::DownArrow))
+            .then(ComboStep::together([
+                KeyboardKey::DownArrow,
+                KeyboardKey::RightArrow,
+            ]))
+            .then(ComboStep::together([
+                KeyboardKey::RightArrow,
+                KeyboardKey::J,
+            ]))
+            .within(Duration::from_millis(300))
+    }
+
+    #[test]
+    fn test_motion_input() {
+        let mut combos = ComboRecognizer::new();
+        let id = combos.add(fireball());
+        assert!(
+            combos
+                .feed(key(KeyboardKey::DownArrow), true, ms(0))
+                .is_empty()
+        );
+        assert!(
+            combos
+                .feed(key(KeyboardKey::RightArrow), true, ms(50))
+                .is_empty()
+        );
+        assert!(
+            combos
+                .feed(key(KeyboardKey::DownArrow), false, ms(80))
+                .is_empty()
+        );
+        //an unrelated press does not break the combo
+        assert!(combos.feed(key(KeyboardKey::K), true, ms(90)).is_empty());
+        let matches = combos.feed(key(KeyboardKey::J), true, ms(150));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].combo(), id);
+        assert_eq!(matches[0].step_times(), &[ms(0), ms(50), ms(150)]);
+        assert_eq!(matches[0].duration(), Duration::from_millis(150));
+        //steps took 50 and 100 of their 200 ms windows
+        assert!((matches[0].confidence() - 0.625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_timing_windows() {
+        let mut combos = ComboRecognizer::new();
+        combos.add(fireball());
+        combos.feed(key(KeyboardKey::DownArrow), true, ms(0));
+        combos.feed(key(KeyboardKey::RightArrow), true, ms(150));
+        combos.feed(key(KeyboardKey::DownArrow), false, ms(200));
+        //within the step window, but outside the total window
+        assert!(combos.feed(key(KeyboardKey::J), true, ms(320)).is_empty());
+
+        //a step window exceeded
+        combos.reset();
+        combos.feed(key(KeyboardKey::DownArrow), true, ms(1000));
+        combos.feed(key(KeyboardKey::RightArrow), true, ms(1250));
+        assert!(combos.feed(key(KeyboardKey::J), true, ms(1260)).is_empty());
+    }
+
+    #[test]
+    fn test_simultaneous_press() {
+        let mut combos = ComboRecognizer::new();
+        combos.add(
+            Combo::new().then(
+                ComboStep::together([KeyboardKey::J, KeyboardKey::K])
+                    .max_spread(Duration::from_millis(30)),
+            ),
+        );
+        combos.feed(key(KeyboardKey::J), true, ms(0));
+        assert!(combos.feed(key(KeyboardKey::K), true, ms(100)).is_empty());
+        combos.feed(key(KeyboardKey::J), false, ms(110));
+        combos.feed(key(KeyboardKey::K), false, ms(110));
+        combos.feed(key(KeyboardKey::K), true, ms(200));
+        let matches = combos.feed(key(KeyboardKey::J), true, ms(220));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].confidence(), 1.0);
+    }
+}
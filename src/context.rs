@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! One ordered sequence of events from every device.
+//!
+//! [`Keyboard`](crate::keyboard::Keyboard) and [`Mouse`](crate::mouse::Mouse) buffer their events
+//! separately, so comparing a click with a key press from the two buffers loses their relative
+//! order.  An
+//! [`InputContext`](crate::context::InputContext) owns both devices and delivers all of their
+//! events, including pointer motion and focus changes, as one sequence of
+//! [`InputEvent`](crate::context::InputEvent)s.
+//!
+//! Every event also carries a [sequence number](crate::context::InputEvent::sequence) from a
+//! process-wide counter, so events from different sources can be ordered even after they are
+//! separated.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::context::{InputContext, InputEvent};
+//! use app_input::keyboard::key::KeyboardKey;
+//!
+//! let mut input = InputContext::new();
+//! let mut shift = false;
+//! for event in input.drain_events() {
+//!     match event {
+//!         InputEvent::Key(key) if key.key() == KeyboardKey::Shift => shift = key.down(),
+//!         InputEvent::Button(button) if button.down() && shift => {
+//!             // extend the selection
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use crate::Window;
+use crate::keyboard::{FocusEvent, KeyEvent, Keyboard};
+use crate::mouse::{ButtonEvent, CrossingEvent, Mouse, MouseWindowLocation, ScrollEvent};
+use crate::stream::{EventStream, Next};
+use crate::timestamp::Timestamp;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the sequence number for a new event.
+pub(crate) fn next_sequence() -> u64 {
+    SEQUENCE.fetch_add(1, Ordering::Relaxed)
+}
+
+/// An event from any device, as delivered by [`InputContext`].
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum InputEvent {
+    /// A key was pressed, released or repeated.
+    Key(KeyEvent),
+    /// A mouse button was pressed or released.
+    Button(ButtonEvent),
+    /// The pointer moved within a window.
+    Motion(MouseWindowLocation),
+    /// The scroll wheel moved.
+    Scroll(ScrollEvent),
+    /// The pointer entered a window.
+    Enter(CrossingEvent),
+    /// The pointer left a window.
+    Leave(CrossingEvent),
+    /// A window gained or lost keyboard focus.
+    Focus(FocusEvent),
+}
+
+impl InputEvent {
+    /**
+    Returns the event's position in the order of all input events.
+
+    Sequence numbers come from one process-wide counter and are assigned as each event is
+    received, so a smaller number means an earlier event, whichever device it came from.  Numbers
+    are not contiguous: other contexts, streams and synthesized events share the counter.
+    */
+    pub fn sequence(&self) -> u64 {
+        match self {
+            InputEvent::Key(event) => event.sequence(),
+            InputEvent::Button(event) => event.sequence(),
+            InputEvent::Motion(event) => event.sequence(),
+            InputEvent::Scroll(event) => event.sequence(),
+            InputEvent::Enter(event) | InputEvent::Leave(event) => event.sequence(),
+            InputEvent::Focus(event) => event.sequence(),
+        }
+    }
+
+    /// Returns the time at which the event occurred.
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            InputEvent::Key(event) => event.timestamp(),
+            InputEvent::Button(event) => event.timestamp(),
+            InputEvent::Motion(event) => event.timestamp(),
+            InputEvent::Scroll(event) => event.timestamp(),
+            InputEvent::Enter(event) | InputEvent::Leave(event) => event.timestamp(),
+            InputEvent::Focus(event) => event.timestamp(),
+        }
+    }
+
+    /// Returns the window the event was delivered to, if known.
+    pub fn window(&self) -> Option<Window> {
+        match self {
+            InputEvent::Key(event) => event.window(),
+            InputEvent::Button(event) => event.window(),
+            InputEvent::Motion(event) => event.window(),
+            InputEvent::Scroll(event) => event.window(),
+            InputEvent::Enter(event) | InputEvent::Leave(event) => event.window(),
+            InputEvent::Focus(event) => event.window(),
+        }
+    }
+}
+
+/**
+The keyboard and mouse, with their events in one ordered sequence.
+
+Events are buffered from the moment the context is created, up to
+[`InputContext::DEFAULT_EVENT_CAPACITY`].  If more arrive before they are consumed, the oldest are
+discarded.  The context's buffer is separate from those of [`Keyboard::drain_events`] and
+[`Mouse::drain_button_events`], which keep working as before.
+*/
+#[derive(Debug)]
+pub struct InputContext {
+    keyboard: Keyboard,
+    mouse: Mouse,
+    events: EventStream<InputEvent>,
+}
+
+impl InputContext {
+    /// The number of events buffered by a context.
+    pub const DEFAULT_EVENT_CAPACITY: usize = 512;
+
+    /// Creates a context for the coalesced keyboard and mouse.
+    pub fn new() -> Self {
+        let keyboard = Keyboard::coalesced();
+        let mouse = Mouse::coalesced();
+        let events = EventStream::with_capacity(InputContext::DEFAULT_EVENT_CAPACITY);
+        keyboard.attach_input_stream(&events);
+        mouse.attach_input_stream(&events);
+        InputContext {
+            keyboard,
+            mouse,
+            events,
+        }
+    }
+
+    /// Returns the keyboard, for querying its current state.
+    pub fn keyboard(&self) -> &Keyboard {
+        &self.keyboard
+    }
+
+    /// Returns the mouse, for querying its current state.
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// Returns the events received since the previous call, ordered by [`InputEvent::sequence`].
+    pub fn drain_events(&mut self) -> Vec<InputEvent> {
+        let mut events: Vec<InputEvent> = std::iter::from_fn(|| self.events.try_next()).collect();
+        //devices delivered on different threads can reach the buffer slightly out of order
+        events.sort_by_key(InputEvent::sequence);
+        events
+    }
+
+    /// Waits for the next event.
+    ///
+    /// Events are returned in the order they reached the buffer, which matches
+    /// [`InputEvent::sequence`] unless devices deliver input on different threads.
+    pub fn next_event(&mut self) -> Next<'_, InputEvent> {
+        self.events.next()
+    }
+}
+
+impl Default for InputContext {
+    fn default() -> Self {
+        InputContext::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::context::{InputContext, InputEvent};
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::MOUSE_BUTTON_LEFT;
+
+    #[test]
+    fn test_interleaved_order() {
+        let mut input = InputContext::new();
+        let keys = input.keyboard().shared_for_test();
+        let buttons = input.mouse().shared_for_test();
+        let window = 0x20 as *mut std::ffi::c_void;
+        keys.set_focus(window);
+        keys.set_key_state(KeyboardKey::Shift, true, window);
+        buttons.set_key_state(MOUSE_BUTTON_LEFT, true, window);
+        keys.set_key_state(KeyboardKey::Shift, false, window);
+        buttons.set_key_state(MOUSE_BUTTON_LEFT, false, window);
+        buttons.add_scroll_delta(0.0, 1.0, window);
+        keys.clear_focus(window);
+
+        let events = input.drain_events();
+        let summary: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                InputEvent::Key(key) => format!("key {} {}", key.key(), key.down()),
+                InputEvent::Button(button) => format!("button {}", button.down()),
+                InputEvent::Scroll(_) => "scroll".to_string(),
+                InputEvent::Focus(focus) => format!("focus {}", focus.focused()),
+                other => format!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "focus true",
+                "key Shift true",
+                "button true",
+                "key Shift false",
+                "button false",
+                "scroll",
+                "focus false",
+            ]
+        );
+        assert!(events.windows(2).all(|w| w[0].sequence() < w[1].sequence()));
+        assert!(
+            events
+                .iter()
+                .all(|e| e.window().unwrap().0.as_ptr() == window)
+        );
+        assert!(input.drain_events().is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Per-frame snapshots of keyboard and mouse state.
+//!
+//! Games and other frame-based applications usually want to ask "was this key pressed *this frame*?"
+//! rather than "is this key down right now?".  [`FrameInput`](crate::frame::FrameInput) answers
+//! that question.  Call [`FrameInput::begin_frame`](crate::frame::FrameInput::begin_frame) once at
+//! the top of each frame, then query edges with
+//! [`FrameInput::just_pressed`](crate::frame::FrameInput::just_pressed) and friends.
+//!
+//! Edges are derived from every transition the platform reported, not from sampling, so a key that
+//! was pressed and released entirely within one frame reports both `just_pressed` and `just_released`.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::frame::FrameInput;
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//! use app_input::mouse::{Mouse, MOUSE_BUTTON_LEFT};
+//!
+//! let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+//!
+//! // Once per frame:
+//! input.begin_frame();
+//! if input.just_pressed(KeyboardKey::Space) {
+//!     // jump
+//! }
+//! if input.button_just_released(MOUSE_BUTTON_LEFT) {
+//!     // finish drag
+//! }
+//! ```
+
+use crate::keyboard::Keyboard;
+use crate::keyboard::key::KeyboardKey;
+use crate::mouse::Mouse;
+use std::collections::HashMap;
+
+/// The state of one key or button, as captured by [`FrameInput::begin_frame`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct Snapshot {
+    pressed: bool,
+    press_count: u32,
+    release_count: u32,
+}
+
+impl Snapshot {
+    fn new(pressed: bool, (press_count, release_count): (u32, u32)) -> Self {
+        Snapshot {
+            pressed,
+            press_count,
+            release_count,
+        }
+    }
+}
+
+/**
+A frame-based view of a [`Keyboard`] and a [`Mouse`].
+
+`FrameInput` owns the devices it observes.  All queries describe the frame most recently started
+with [`FrameInput::begin_frame`], and do not change until the next call, even if new input arrives
+in the meantime.
+
+Before the first call to [`FrameInput::begin_frame`], nothing is reported as pressed or released.
+*/
+#[derive(Debug)]
+pub struct FrameInput {
+    keyboard: Keyboard,
+    mouse: Mouse,
+    keys_previous: HashMap<KeyboardKey, Snapshot>,
+    keys_current: HashMap<KeyboardKey, Snapshot>,
+    buttons_previous: Vec<Snapshot>,
+    buttons_current: Vec<Snapshot>,
+    scroll_delta: (f64, f64),
+}
+
+impl FrameInput {
+    /// Creates a frame-based view of the specified devices.
+    ///
+    /// Input received before this call is not reported as an edge in the first frame.
+    pub fn new(keyboard: Keyboard, mouse: Mouse) -> Self {
+        let keys = Self::capture_keys(&keyboard);
+        let buttons = Self::capture_buttons(&mouse);
+        FrameInput {
+            keyboard,
+            mouse,
+            keys_previous: keys.clone(),
+            keys_current: keys,
+            buttons_previous: buttons.clone(),
+            buttons_current: buttons,
+            scroll_delta: (0.0, 0.0),
+        }
+    }
+
+    fn capture_keys(keyboard: &Keyboard) -> HashMap<KeyboardKey, Snapshot> {
+        keyboard
+            .known_keys()
+            .into_iter()
+            .map(|key| {
+                let snapshot =
+                    Snapshot::new(keyboard.is_pressed(key), keyboard.transition_counts(key));
+                (key, snapshot)
+            })
+            .collect()
+    }
+
+    /// Returns the snapshots of a key at the start of the previous frame and this one.
+    ///
+    /// Unknown keys not seen yet have the default snapshot.
+    fn key_snapshots(&self, key: KeyboardKey) -> (Snapshot, Snapshot) {
+        let snapshot =
+            |keys: &HashMap<KeyboardKey, Snapshot>| keys.get(&key).copied().unwrap_or_default();
+        (snapshot(&self.keys_previous), snapshot(&self.keys_current))
+    }
+
+    fn capture_buttons(mouse: &Mouse) -> Vec<Snapshot> {
+        (0..=u8::MAX)
+            .map(|button| {
+                Snapshot::new(mouse.button_state(button), mouse.transition_counts(button))
+            })
+            .collect()
+    }
+
+    /// Starts a new frame.
+    ///
+    /// Captures the current state of every key and button, and the scroll delta accumulated since
+    /// the previous frame.  Call this once at the start of each frame, before querying.
+    pub fn begin_frame(&mut self) {
+        let keys = Self::capture_keys(&self.keyboard);
+        self.keys_previous = std::mem::replace(&mut self.keys_current, keys);
+        let buttons = Self::capture_buttons(&self.mouse);
+        self.buttons_previous = std::mem::replace(&mut self.buttons_current, buttons);
+        self.scroll_delta = self.mouse.load_clear_scroll_delta();
+    }
+
+    /// Returns `true` if the key was held down at the start of this frame.
+    pub fn is_pressed(&self, key: KeyboardKey) -> bool {
+        self.key_snapshots(key).1.pressed
+    }
+
+    /// Returns `true` if the key was pressed at least once between the previous frame and this one.
+    ///
+    /// This is `true` even if the key was released again before the frame began.
+    pub fn just_pressed(&self, key: KeyboardKey) -> bool {
+        let (previous, current) = self.key_snapshots(key);
+        current.press_count != previous.press_count
+    }
+
+    /// Returns `true` if the key was released at least once between the previous frame and this one.
+    ///
+    /// This is `true` even if the key was pressed again before the frame began.
+    pub fn just_released(&self, key: KeyboardKey) -> bool {
+        let (previous, current) = self.key_snapshots(key);
+        current.release_count != previous.release_count
+    }
+
+    /// Returns `true` if the mouse button was held down at the start of this frame.
+    ///
+    /// See [`Mouse::button_state`] for the meaning of `button`.
+    pub fn button_is_pressed(&self, button: u8) -> bool {
+        self.buttons_current[button as usize].pressed
+    }
+
+    /// Returns `true` if the mouse button was pressed at least once between the previous frame and this one.
+    pub fn button_just_pressed(&self, button: u8) -> bool {
+        self.buttons_current[button as usize].press_count
+            != self.buttons_previous[button as usize].press_count
+    }
+
+    /// Returns `true` if the mouse button was released at least once between the previous frame and this one.
+    pub fn button_just_released(&self, button: u8) -> bool {
+        self.buttons_current[button as usize].release_count
+            != self.buttons_previous[button as usize].release_count
+    }
+
+    /// Returns the scroll delta accumulated between the previous frame and this one.
+    ///
+    /// `FrameInput` drains the mouse's scroll delta in [`FrameInput::begin_frame`], so use this
+    /// rather than [`Mouse::load_clear_scroll_delta`].
+    pub fn scroll_delta(&self) -> (f64, f64) {
+        self.scroll_delta
+    }
+
+    /// Returns the keyboard this frame observes.
+    pub fn keyboard(&self) -> &Keyboard {
+        &self.keyboard
+    }
+
+    /// Returns the mouse this frame observes.
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::frame::FrameInput;
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{MOUSE_BUTTON_LEFT, Mouse};
+
+    #[test]
+    fn test_tap_within_frame() {
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        input.begin_frame();
+        assert!(!input.just_pressed(KeyboardKey::A));
+
+        input
+            .keyboard
+            .shared_for_test()
+            .set_key_state(KeyboardKey::A, true, std::ptr::null_mut());
+        input
+            .keyboard
+            .shared_for_test()
+            .set_key_state(KeyboardKey::A, false, std::ptr::null_mut());
+        input.begin_frame();
+        assert!(input.just_pressed(KeyboardKey::A));
+        assert!(input.just_released(KeyboardKey::A));
+        assert!(!input.is_pressed(KeyboardKey::A));
+
+        input.begin_frame();
+        assert!(!input.just_pressed(KeyboardKey::A));
+        assert!(!input.just_released(KeyboardKey::A));
+    }
+
+    #[test]
+    fn test_button_edges() {
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        input
+            .mouse
+            .shared_for_test()
+            .set_key_state(MOUSE_BUTTON_LEFT, true, std::ptr::null_mut());
+        input.begin_frame();
+        assert!(input.button_just_pressed(MOUSE_BUTTON_LEFT));
+        assert!(input.button_is_pressed(MOUSE_BUTTON_LEFT));
+        assert!(!input.button_just_released(MOUSE_BUTTON_LEFT));
+
+        input.begin_frame();
+        assert!(!input.button_just_pressed(MOUSE_BUTTON_LEFT));
+        assert!(input.button_is_pressed(MOUSE_BUTTON_LEFT));
+    }
+
+    #[test]
+    fn test_last_button() {
+        let mut input = FrameInput::new(Keyboard::coalesced(), Mouse::coalesced());
+        input
+            .mouse
+            .shared_for_test()
+            .set_key_state(u8::MAX, true, std::ptr::null_mut());
+        input.begin_frame();
+        assert!(input.button_just_pressed(u8::MAX));
+        assert!(input.button_is_pressed(u8::MAX));
+        assert!(!input.button_just_released(u8::MAX));
+    }
+}
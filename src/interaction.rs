@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Taps, holds and multi-taps on keys and mouse buttons.
+//!
+//! A [`Recognizer`](crate::interaction::Recognizer) watches one key or button for one
+//! [`Interaction`](crate::interaction::Interaction), such as "held for half a second" or "tapped
+//! twice".  Feed it the events from
+//! [`Keyboard::drain_events`](crate::keyboard::Keyboard::drain_events) or
+//! [`Mouse::drain_button_events`](crate::mouse::Mouse::drain_button_events), and call
+//! [`Recognizer::poll`](crate::interaction::Recognizer::poll) once per frame so that holds can
+//! fire while the input is still held.
+//!
+//! Timings are measured from event timestamps, and [`Recognizer::poll`](crate::interaction::Recognizer::poll)
+//! reads the current time from a [`Clock`](crate::timestamp::Clock), which tests can replace with a
+//! [`ManualClock`](crate::timestamp::ManualClock).
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::action::Input;
+//! use app_input::interaction::{Interaction, Recognizer};
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//! use app_input::mouse::{Mouse, MOUSE_BUTTON_LEFT};
+//! use std::time::Duration;
+//!
+//! let keyboard = Keyboard::coalesced();
+//! let mouse = Mouse::coalesced();
+//! let mut interact = Recognizer::new(KeyboardKey::E, Interaction::Hold { min_duration: Duration::from_millis(500) });
+//! let mut sprint = Recognizer::new(Input::MouseButton(MOUSE_BUTTON_LEFT), Interaction::double_tap());
+//!
+//! // Once per frame:
+//! for event in &keyboard.drain_events() {
+//!     interact.feed_key_event(event);
+//! }
+//! for event in mouse.drain_button_events() {
+//!     if sprint.feed_button_event(&event) {
+//!         // start sprinting
+//!     }
+//! }
+//! if interact.poll() {
+//!     // interact
+//! }
+//! ```
+
+use crate::action::Input;
+use crate::keyboard::KeyEvent;
+use crate::mouse::ButtonEvent;
+use crate::timestamp::{Clock, SystemClock, Timestamp};
+use std::time::Duration;
+
+/// A pattern of presses and releases that a [`Recognizer`] detects.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Interaction {
+    /// Released no later than `max_duration` after being pressed.  Recognized on release.
+    Tap {
+        /// The longest press that still counts as a tap.
+        max_duration: Duration,
+    },
+    /// Held for at least `min_duration`.  Recognized by [`Recognizer::poll`] while still held.
+    ///
+    /// If the input is released before a poll observes the hold, it is recognized on release instead.
+    Hold {
+        /// How long the input must be held.
+        min_duration: Duration,
+    },
+    /// `count` taps in a row.  Recognized on the release of the final tap.
+    MultiTap {
+        /// The number of taps.
+        count: u32,
+        /// The longest press that still counts as a tap.
+        max_tap_duration: Duration,
+        /// The longest time between releasing one tap and pressing the next.
+        max_gap: Duration,
+    },
+    /// Released at least `min_duration` after being pressed.  Recognized on release.
+    SlowTap {
+        /// The shortest press that counts as a slow tap.
+        min_duration: Duration,
+    },
+}
+
+impl Interaction {
+    /// A tap of at most 200 ms.
+    pub const fn tap() -> Self {
+        Interaction::Tap {
+            max_duration: Duration::from_millis(200),
+        }
+    }
+
+    /// A hold of at least 500 ms.
+    pub const fn hold() -> Self {
+        Interaction::Hold {
+            min_duration: Duration::from_millis(500),
+        }
+    }
+
+    /// Two taps of at most 200 ms each, at most 300 ms apart.
+    pub const fn double_tap() -> Self {
+        Interaction::MultiTap {
+            count: 2,
+            max_tap_duration: Duration::from_millis(200),
+            max_gap: Duration::from_millis(300),
+        }
+    }
+
+    /// A press of at least 500 ms, recognized on release.
+    pub const fn slow_tap() -> Self {
+        Interaction::SlowTap {
+            min_duration: Duration::from_millis(500),
+        }
+    }
+}
+
+/**
+Detects an [`Interaction`] on one key or mouse button.
+
+Each `feed_*` method and [`Recognizer::poll`] returns `true` when the interaction is recognized.
+Events for other inputs are ignored, as are key repeats.
+
+The clock is only used by [`Recognizer::poll`]; everything else is timed by event timestamps.  It
+must therefore agree with the timestamps of the events you feed: use the default
+[`SystemClock`] for real input.
+*/
+#[derive(Debug, Clone)]
+pub struct Recognizer<C = SystemClock> {
+    input: Input,
+    interaction: Interaction,
+    clock: C,
+    pressed_at: Option<Timestamp>,
+    hold_recognized: bool,
+    taps: u32,
+    last_tap_released_at: Option<Timestamp>,
+}
+
+impl Recognizer<SystemClock> {
+    /// Creates a recognizer that uses the system clock.
+    pub fn new(input: impl Into<Input>, interaction: Interaction) -> Self {
+        Recognizer::with_clock(input, interaction, SystemClock)
+    }
+}
+
+impl<C: Clock> Recognizer<C> {
+    /// Creates a recognizer that reads the time from `clock`.
+    pub fn with_clock(input: impl Into<Input>, interaction: Interaction, clock: C) -> Self {
+        Recognizer {
+            input: input.into(),
+            interaction,
+            clock,
+            pressed_at: None,
+            hold_recognized: false,
+            taps: 0,
+            last_tap_released_at: None,
+        }
+    }
+
+    /// The input this recognizer watches.
+    pub fn input(&self) -> Input {
+        self.input
+    }
+
+    /// The interaction this recognizer detects.
+    pub fn interaction(&self) -> Interaction {
+        self.interaction
+    }
+
+    /// Returns `true` if the input is held, according to the events fed so far.
+    pub fn is_held(&self) -> bool {
+        self.pressed_at.is_some()
+    }
+
+    /// Processes a key event.
+    pub fn feed_key_event(&mut self, event: &KeyEvent) -> bool {
+        if Input::Key(event.key()) != self.input || event.repeat() {
+            return false;
+        }
+        self.feed(event.down(), event.timestamp())
+    }
+
+    /// Processes a mouse button event.
+    pub fn feed_button_event(&mut self, event: &ButtonEvent) -> bool {
+        if Input::MouseButton(event.button()) != self.input {
+            return false;
+        }
+        self.feed(event.down(), event.timestamp())
+    }
+
+    /// Processes a press or release of the watched input at the specified time.
+    pub fn feed(&mut self, down: bool, at: Timestamp) -> bool {
+        if down {
+            if self.pressed_at.is_none() {
+                self.pressed_at = Some(at);
+                self.hold_recognized = false;
+                if let Interaction::MultiTap { max_gap, .. } = self.interaction {
+                    if self
+                        .last_tap_released_at
+                        .is_none_or(|released| at - released > max_gap)
+                    {
+                        self.taps = 0;
+                    }
+                }
+            }
+            return false;
+        }
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return false;
+        };
+        let held = at - pressed_at;
+        match self.interaction {
+            Interaction::Tap { max_duration } => held <= max_duration,
+            Interaction::SlowTap { min_duration } => held >= min_duration,
+            Interaction::Hold { min_duration } => !self.hold_recognized && held >= min_duration,
+            Interaction::MultiTap {
+                count,
+                max_tap_duration,
+                ..
+            } => {
+                if held > max_tap_duration {
+                    self.taps = 0;
+                    self.last_tap_released_at = None;
+                    return false;
+                }
+                self.taps += 1;
+                self.last_tap_released_at = Some(at);
+                if self.taps >= count {
+                    self.taps = 0;
+                    self.last_tap_released_at = None;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Checks for interactions that complete while the input is held, as of the clock's current time.
+    ///
+    /// Only [`Interaction::Hold`] completes this way.  Call this once per frame.
+    pub fn poll(&mut self) -> bool {
+        let Interaction::Hold { min_duration } = self.interaction else {
+            return false;
+        };
+        match self.pressed_at {
+            Some(pressed_at) if !self.hold_recognized => {
+                self.hold_recognized = self.clock.now() - pressed_at >= min_duration;
+                self.hold_recognized
+            }
+            _ => false,
+        }
+    }
+
+    /// Forgets any press in progress, as when the application loses focus.
+    pub fn reset(&mut self) {
+        self.pressed_at = None;
+        self.hold_recognized = false;
+        self.taps = 0;
+        self.last_tap_released_at = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::action::Input;
+    use crate::interaction::{Interaction, Recognizer};
+    use crate::keyboard::KeyEvent;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{ButtonEvent, MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT};
+    use crate::timestamp::{Clock, ManualClock, Timestamp};
+    use std::time::Duration;
+
+    fn ms(ms: u64) -> Timestamp {
+        Timestamp::from_duration_since_epoch(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn test_tap_and_slow_tap() {
+        let mut tap = Recognizer::new(KeyboardKey::E, Interaction::tap());
+        let mut slow = Recognizer::new(KeyboardKey::E, Interaction::slow_tap());
+        for recognizer in [&mut tap, &mut slow] {
+            assert!(!recognizer.feed(true, ms(0)));
+        }
+        assert!(tap.feed(false, ms(150)));
+        assert!(!slow.feed(false, ms(150)));
+
+        tap.feed(true, ms(1000));
+        slow.feed(true, ms(1000));
+        assert!(!tap.feed(false, ms(1600)));
+        assert!(slow.feed(false, ms(1600)));
+    }
+
+    #[test]
+    fn test_hold_fires_while_held() {
+        let clock = ManualClock::new();
+        let mut hold = Recognizer::with_clock(KeyboardKey::E, Interaction::hold(), clock.clone());
+        let press = KeyEvent::new(KeyboardKey::E, true, false, None, clock.now());
+        assert!(!hold.feed_key_event(&press));
+        clock.advance(Duration::from_millis(400));
+        assert!(!hold.poll());
+        //repeats don't restart the hold
+        let repeat = KeyEvent::new(KeyboardKey::E, true, true, None, clock.now());
+        assert!(!hold.feed_key_event(&repeat));
+        clock.advance(Duration::from_millis(100));
+        assert!(hold.poll());
+        assert!(!hold.poll());
+        let release = KeyEvent::new(KeyboardKey::E, false, false, None, clock.now());
+        assert!(!hold.feed_key_event(&release));
+
+        //a hold released before any poll is recognized on release
+        hold.feed(true, ms(1000));
+        assert!(hold.feed(false, ms(2000)));
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let mut double = Recognizer::new(
+            Input::MouseButton(MOUSE_BUTTON_LEFT),
+            Interaction::double_tap(),
+        );
+        let click = |down, at| ButtonEvent::new(MOUSE_BUTTON_LEFT, down, None, ms(at));
+        assert!(!double.feed_button_event(&click(true, 0)));
+        assert!(!double.feed_button_event(&click(false, 100)));
+        //other buttons are ignored
+        let other = ButtonEvent::new(MOUSE_BUTTON_RIGHT, true, None, ms(150));
+        assert!(!double.feed_button_event(&other));
+        assert!(!double.feed_button_event(&click(true, 200)));
+        assert!(double.feed_button_event(&click(false, 300)));
+
+        //too far apart
+        double.feed_button_event(&click(true, 1000));
+        double.feed_button_event(&click(false, 1100));
+        double.feed_button_event(&click(true, 1500));
+        assert!(!double.feed_button_event(&click(false, 1600)));
+        double.feed_button_event(&click(true, 1700));
+        assert!(double.feed_button_event(&click(false, 1800)));
+    }
+}
@@ -24,19 +24,62 @@
 //! assert_eq!(keyboard.is_pressed(KeyboardKey::Shift), false);
 //! ```
 //!
+//! # Buffered events
+//!
+//! [`Keyboard::is_pressed`](crate::keyboard::Keyboard::is_pressed) reports the latest state of a key,
+//! so a key that is pressed and released between two polls is invisible to it.  To observe every
+//! transition, call [`Keyboard::drain_events`](crate::keyboard::Keyboard::drain_events) periodically,
+//! which returns the [`KeyEvent`](crate::keyboard::KeyEvent)s received since the last drain in order.
+//!
+//! # Text input
+//!
+//! Key events describe physical keys.  To receive the characters the user typed, honoring their
+//! layout, dead keys and compose sequences, call
+//! [`Keyboard::drain_text_input`](crate::keyboard::Keyboard::drain_text_input) instead.
+//!
+//! To ask what a key *means*, for example so that the keypad's 7 acts as Home when NumLock is off,
+//! use [`KeyboardKey::logical`](crate::keyboard::key::KeyboardKey::logical).
+//!
+//! # Shortcuts
+//!
+//! [`KeyChord`](crate::keyboard::chord::KeyChord) parses shortcuts such as `"Ctrl+Shift+S"` and formats
+//! them per platform convention.  Test one with
+//! [`Keyboard::is_chord_pressed`](crate::keyboard::Keyboard::is_chord_pressed).
+//! To dispatch many shortcuts, including multi-chord sequences and per-window bindings, feed key
+//! events to a [`ShortcutMap`](crate::keyboard::shortcut::ShortcutMap).
+//!
 //! # Platform Requirements
 //!
 //! - **Windows**: Call `window_proc` from your window procedure  
 //! - **Linux**: Call `wl_keyboard_event` from your Wayland dispatch queue
 //! - **macOS** and **WASM**: No special integration required
 
+use std::collections::HashMap;
 use std::ffi::c_void;
 use std::hash::Hash;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicPtr};
+use std::ptr::NonNull;
+use std::sync::atomic::{
+    AtomicBool, AtomicPtr, AtomicU8, AtomicU16, AtomicU32, AtomicU64, Ordering,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+/// Keyboard shortcuts, parsed from and formatted as the strings shown in menus.
+pub mod chord;
+/// Input method (IME) composition events.
+pub mod ime;
 /// Keyboard key definitions and enumerations.
 pub mod key;
+/// The meaning of keys under a layout, modeled on W3C `key` values.
+pub mod logical;
+/// Modifier and lock key state.
+pub mod modifiers;
+/// Shortcut bindings, matched against key events.
+pub mod shortcut;
+/// Text typed by the user, as opposed to the keys they pressed.
+pub mod text;
+
+mod codes;
 
 #[cfg(target_os = "macos")]
 pub(crate) mod macos;
@@ -62,8 +105,207 @@ pub(crate) use windows as sys;
 #[cfg(target_os = "linux")]
 pub(crate) use linux as sys;
 
-use crate::keyboard::key::KeyboardKey;
+use crate::Window;
+use crate::context::{InputEvent, next_sequence};
+use crate::keyboard::chord::KeyChord;
+use crate::keyboard::ime::ImeEvent;
+use crate::keyboard::key::{KeyboardKey, RawScancode};
+use crate::keyboard::modifiers::Modifiers;
 use crate::keyboard::sys::PlatformCoalescedKeyboard;
+use crate::keyboard::text::TextInput;
+use crate::observer::{Observers, Subscription};
+use crate::queue::EventQueue;
+use crate::stream::{EventStream, Subscribers};
+use crate::timestamp::Timestamp;
+use crate::virtual_device::VirtualKeyboard;
+
+/// A single key transition, as buffered by [`Keyboard::drain_events`].
+///
+/// # Example
+///
+/// ```
+/// use app_input::keyboard::Keyboard;
+///
+/// let keyboard = Keyboard::coalesced();
+/// for event in keyboard.drain_events() {
+///     println!("{:?} down={} at {:?}", event.key(), event.down(), event.timestamp());
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyEvent {
+    key: KeyboardKey,
+    down: bool,
+    repeat: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    window: Option<Window>,
+    timestamp: Timestamp,
+    sequence: u64,
+}
+
+impl KeyEvent {
+    pub(crate) fn new(
+        key: KeyboardKey,
+        down: bool,
+        repeat: bool,
+        window: Option<Window>,
+        timestamp: Timestamp,
+    ) -> Self {
+        KeyEvent {
+            key,
+            down,
+            repeat,
+            window,
+            timestamp,
+            sequence: next_sequence(),
+        }
+    }
+
+    /// Returns the key that changed state.
+    pub fn key(&self) -> KeyboardKey {
+        self.key
+    }
+
+    /// Returns `true` if the key was pressed, or `false` if it was released.
+    pub fn down(&self) -> bool {
+        self.down
+    }
+
+    /// Returns `true` if this is an auto-repeat of a key that is being held down.
+    ///
+    /// Repeat events are always `down`, and do not change the state reported by
+    /// [`Keyboard::is_pressed`].  Text fields and menus usually want to act on them; games usually
+    /// want to ignore them.
+    ///
+    /// # Platform specifics
+    ///
+    /// * **Linux**: Wayland compositors do not send repeats, so they are synthesized using the
+    ///   rate and delay from [`crate::linux::wl_keyboard_repeat_info_event`].
+    /// * **Windows**, **macOS** and **WASM**: repeats are reported by the platform.
+    pub fn repeat(&self) -> bool {
+        self.repeat
+    }
+
+    /// Returns the window the event was delivered to, if known.
+    ///
+    /// See [`Window`] for the platform-specific meaning of this value.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which the event occurred.
+    ///
+    /// Where the platform stamps events, this is the platform's time; see [`Timestamp`].
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the event's position in the order of all input events.  See [`InputEvent::sequence`].
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/// A window gaining or losing keyboard focus, as delivered by [`InputContext`](crate::context::InputContext).
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FocusEvent {
+    focused: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    window: Option<Window>,
+    timestamp: Timestamp,
+    sequence: u64,
+}
+
+impl FocusEvent {
+    pub(crate) fn new(focused: bool, window: Option<Window>, timestamp: Timestamp) -> Self {
+        FocusEvent {
+            focused,
+            window,
+            timestamp,
+            sequence: next_sequence(),
+        }
+    }
+
+    /// Returns `true` if the window gained focus, or `false` if it lost focus.
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Returns the window that gained or lost focus, if known.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which focus changed.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the event's position in the order of all input events.  See [`InputEvent::sequence`].
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/// The events returned from [`Keyboard::drain_events`].
+///
+/// Iterate over this type to visit the events in the order they occurred.
+#[derive(Debug, Clone, Default)]
+pub struct KeyEvents {
+    events: Vec<KeyEvent>,
+    overflowed: bool,
+}
+
+impl KeyEvents {
+    /// Returns `true` if the buffer filled up since the previous drain and the oldest events were discarded.
+    ///
+    /// When this happens, the events no longer describe every transition.  Use [`Keyboard::is_pressed`]
+    /// to resynchronize if needed.
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Returns the number of events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events were received.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns an iterator over the events in the order they occurred.
+    pub fn iter(&self) -> std::slice::Iter<'_, KeyEvent> {
+        self.events.iter()
+    }
+}
+
+impl IntoIterator for KeyEvents {
+    type Item = KeyEvent;
+    type IntoIter = std::vec::IntoIter<KeyEvent>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a KeyEvents {
+    type Item = &'a KeyEvent;
+    type IntoIter = std::slice::Iter<'a, KeyEvent>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.iter()
+    }
+}
+
+/// Pressed state and transition counts of a [`KeyboardKey::Unknown`].
+#[derive(Debug, Default, Copy, Clone)]
+struct UnknownKeyState {
+    pressed: bool,
+    pressed_at: Option<Timestamp>,
+    press_count: u32,
+    release_count: u32,
+}
 
 /// Internal shared state for keyboard tracking.
 ///
@@ -71,12 +313,38 @@ use crate::keyboard::sys::PlatformCoalescedKeyboard;
 /// implementations. It maintains the current state of all keyboard keys using atomic
 /// operations for thread safety.
 #[derive(Debug)]
-struct Shared {
+pub(crate) struct Shared {
     /// Array of atomic booleans tracking the pressed state of each key.
     /// Indexed by the numeric value of `KeyboardKey`.
     key_states: Vec<AtomicBool>,
+    /// Number of up-to-down transitions of each key, indexed like `key_states`.
+    press_counts: Vec<AtomicU32>,
+    /// Number of down-to-up transitions of each key, indexed like `key_states`.
+    release_counts: Vec<AtomicU32>,
+    /// Nanoseconds since the [`Timestamp`] epoch at which each key was last pressed, indexed like `key_states`.
+    pressed_at: Vec<AtomicU64>,
+    /// State of every [`KeyboardKey::Unknown`] seen so far, which has no slot in `key_states`.
+    unknown_keys: Mutex<HashMap<RawScancode, UnknownKeyState>>,
     /// Platform-specific window pointer that received the most recent keyboard event.
     window_ptr: AtomicPtr<c_void>,
+    /// Events received since the last call to [`Keyboard::drain_events`].
+    events: Mutex<EventQueue<KeyEvent>>,
+    /// Streams returned from [`Keyboard::events`].
+    streams: Subscribers<KeyEvent>,
+    /// Closures registered with [`Keyboard::on_event`].
+    observers: Observers<KeyEvent>,
+    /// Streams of [`InputContext`](crate::context::InputContext)s that include this keyboard.
+    inputs: Subscribers<InputEvent>,
+    /// Text received since the last call to [`Keyboard::drain_text_input`].
+    text: Mutex<EventQueue<TextInput>>,
+    /// Input method events received since the last call to [`Keyboard::drain_ime_events`].
+    ime: Mutex<EventQueue<ImeEvent>>,
+    /// Bits of [`Modifiers::all_locks`] that are toggled on.
+    locks: AtomicU8,
+    /// Modifiers as reported by the platform, or [`Shared::NO_PLATFORM_MODIFIERS`].
+    platform_modifiers: AtomicU16,
+    /// Platform-specific window pointer that currently has keyboard focus, or null.
+    focused_window: AtomicPtr<c_void>,
 }
 
 impl Shared {
@@ -85,13 +353,47 @@ impl Shared {
         for _ in 0..key::KeyboardKey::all_keys().len() {
             vec.push(AtomicBool::new(false));
         }
+        let counts = || {
+            (0..key::KeyboardKey::all_keys().len())
+                .map(|_| AtomicU32::new(0))
+                .collect()
+        };
+        let times = (0..key::KeyboardKey::all_keys().len())
+            .map(|_| AtomicU64::new(0))
+            .collect();
         Shared {
             key_states: vec,
+            press_counts: counts(),
+            release_counts: counts(),
+            pressed_at: times,
+            unknown_keys: Mutex::new(HashMap::new()),
             window_ptr: AtomicPtr::new(std::ptr::null_mut()),
+            events: Mutex::new(EventQueue::new(Keyboard::DEFAULT_EVENT_CAPACITY)),
+            streams: Subscribers::new(),
+            observers: Observers::new(),
+            inputs: Subscribers::new(),
+            text: Mutex::new(EventQueue::new(Keyboard::DEFAULT_EVENT_CAPACITY)),
+            ime: Mutex::new(EventQueue::new(Keyboard::DEFAULT_EVENT_CAPACITY)),
+            locks: AtomicU8::new(0),
+            platform_modifiers: AtomicU16::new(Shared::NO_PLATFORM_MODIFIERS),
+            focused_window: AtomicPtr::new(std::ptr::null_mut()),
         }
     }
 
-    fn set_key_state(&self, key: KeyboardKey, state: bool, window_ptr: *mut c_void) {
+    const NO_PLATFORM_MODIFIERS: u16 = u16::MAX;
+
+    pub(crate) fn set_key_state(&self, key: KeyboardKey, state: bool, window_ptr: *mut c_void) {
+        self.set_key_state_at(key, state, window_ptr, Timestamp::now());
+    }
+
+    /// Like [`Shared::set_key_state`], for an event that occurred at `timestamp`.
+    pub(crate) fn set_key_state_at(
+        &self,
+        key: KeyboardKey,
+        state: bool,
+        window_ptr: *mut c_void,
+        timestamp: Timestamp,
+    ) {
         logwise::debuginternal_sync!(
             "Setting key {key} to {state}",
             key = logwise::privacy::LogIt(key),
@@ -99,7 +401,294 @@ impl Shared {
         );
         self.window_ptr
             .store(window_ptr, std::sync::atomic::Ordering::Relaxed);
-        self.key_states[key as usize].store(state, std::sync::atomic::Ordering::Relaxed);
+        let was = match key.index() {
+            Some(index) => {
+                let was = self.key_states[index].swap(state, std::sync::atomic::Ordering::Relaxed);
+                if state && !was {
+                    self.pressed_at[index].store(
+                        timestamp.duration_since_epoch().as_nanos() as u64,
+                        Ordering::Relaxed,
+                    );
+                    self.press_counts[index].fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                } else if !state && was {
+                    self.release_counts[index].fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                }
+                was
+            }
+            None => self.set_unknown_key_state(key, state, timestamp),
+        };
+        if state && !was {
+            self.locks.fetch_xor(
+                Modifiers::lock_from_key(key).bits(),
+                std::sync::atomic::Ordering::Relaxed,
+            );
+        }
+        self.push_event(key, state, false, window_ptr, timestamp);
+    }
+
+    /// Updates the state of a [`KeyboardKey::Unknown`], returning whether it was pressed.
+    fn set_unknown_key_state(&self, key: KeyboardKey, state: bool, timestamp: Timestamp) -> bool {
+        let KeyboardKey::Unknown(scancode) = key else {
+            unreachable!("named keys have an index")
+        };
+        let mut unknown_keys = self.unknown_keys.lock().unwrap();
+        let entry = unknown_keys.entry(scancode).or_default();
+        let was = entry.pressed;
+        entry.pressed = state;
+        if state && !was {
+            entry.pressed_at = Some(timestamp);
+            entry.press_count = entry.press_count.wrapping_add(1);
+        } else if !state && was {
+            entry.release_count = entry.release_count.wrapping_add(1);
+        }
+        was
+    }
+
+    pub(crate) fn is_pressed(&self, key: KeyboardKey) -> bool {
+        match key {
+            KeyboardKey::Unknown(scancode) => self
+                .unknown_keys
+                .lock()
+                .unwrap()
+                .get(&scancode)
+                .is_some_and(|state| state.pressed),
+            _ => self.key_states[key.index().unwrap()].load(Ordering::Relaxed),
+        }
+    }
+
+    fn pressed_since(&self, key: KeyboardKey) -> Option<Timestamp> {
+        match key {
+            KeyboardKey::Unknown(scancode) => self
+                .unknown_keys
+                .lock()
+                .unwrap()
+                .get(&scancode)
+                .filter(|state| state.pressed)
+                .and_then(|state| state.pressed_at),
+            _ => {
+                let index = key.index().unwrap();
+                self.key_states[index].load(Ordering::Relaxed).then(|| {
+                    Timestamp::from_duration_since_epoch(Duration::from_nanos(
+                        self.pressed_at[index].load(Ordering::Relaxed),
+                    ))
+                })
+            }
+        }
+    }
+
+    fn transition_counts(&self, key: KeyboardKey) -> (u32, u32) {
+        match key {
+            KeyboardKey::Unknown(scancode) => self
+                .unknown_keys
+                .lock()
+                .unwrap()
+                .get(&scancode)
+                .map_or((0, 0), |state| (state.press_count, state.release_count)),
+            _ => {
+                let index = key.index().unwrap();
+                (
+                    self.press_counts[index].load(Ordering::Relaxed),
+                    self.release_counts[index].load(Ordering::Relaxed),
+                )
+            }
+        }
+    }
+
+    /// Returns every named key, followed by every unknown key seen so far.
+    fn known_keys(&self) -> Vec<KeyboardKey> {
+        let mut keys = KeyboardKey::all_keys();
+        keys.extend(
+            self.unknown_keys
+                .lock()
+                .unwrap()
+                .keys()
+                .map(|scancode| KeyboardKey::Unknown(*scancode)),
+        );
+        keys
+    }
+
+    /// Records an auto-repeat of a held key.
+    ///
+    /// If the key is not held (for example, because the initial press was missed), this is treated
+    /// as a press instead.
+    pub(crate) fn repeat_key(&self, key: KeyboardKey, window_ptr: *mut c_void) {
+        self.repeat_key_at(key, window_ptr, Timestamp::now());
+    }
+
+    /// Like [`Shared::repeat_key`], for a repeat that occurred at `timestamp`.
+    pub(crate) fn repeat_key_at(
+        &self,
+        key: KeyboardKey,
+        window_ptr: *mut c_void,
+        timestamp: Timestamp,
+    ) {
+        if !self.is_pressed(key) {
+            self.set_key_state_at(key, true, window_ptr, timestamp);
+            return;
+        }
+        logwise::debuginternal_sync!("Repeating key {key}", key = logwise::privacy::LogIt(key));
+        self.window_ptr.store(window_ptr, Ordering::Relaxed);
+        self.push_event(key, true, true, window_ptr, timestamp);
+    }
+
+    /// Records text typed by the user.
+    ///
+    /// Control characters are removed, since they are better observed as key events.
+    pub(crate) fn push_text(&self, text: &str, window_ptr: *mut c_void) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        if text.is_empty() {
+            return;
+        }
+        logwise::debuginternal_sync!("Text input {text}", text = logwise::privacy::LogIt(&text));
+        self.text.lock().unwrap().push(TextInput::new(
+            text,
+            NonNull::new(window_ptr).map(Window),
+            Timestamp::now(),
+        ));
+    }
+
+    /// Records an event from the input method.
+    pub(crate) fn push_ime_event(&self, event: ImeEvent) {
+        logwise::debuginternal_sync!("IME event {event}", event = logwise::privacy::LogIt(&event));
+        self.ime.lock().unwrap().push(event);
+    }
+
+    fn push_event(
+        &self,
+        key: KeyboardKey,
+        down: bool,
+        repeat: bool,
+        window_ptr: *mut c_void,
+        timestamp: Timestamp,
+    ) {
+        let event = KeyEvent::new(
+            key,
+            down,
+            repeat,
+            NonNull::new(window_ptr).map(Window),
+            timestamp,
+        );
+        self.events.lock().unwrap().push(event);
+        self.streams.send(&event);
+        self.observers.notify(&event);
+        self.inputs.send(&InputEvent::Key(event));
+    }
+
+    /// Sets the toggle state of a lock, for platforms that report it directly.
+    pub(crate) fn set_lock(&self, lock: Modifiers, on: bool) {
+        if on {
+            self.locks.fetch_or(lock.bits(), Ordering::Relaxed);
+        } else {
+            self.locks.fetch_and(!lock.bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Records the complete modifier state, for platforms that report it authoritatively.
+    ///
+    /// Once called, [`Keyboard::modifiers`] returns the reported value instead of deriving it from key state.
+    pub(crate) fn set_platform_modifiers(&self, modifiers: Modifiers) {
+        logwise::debuginternal_sync!(
+            "Setting platform modifiers {modifiers}",
+            modifiers = logwise::privacy::LogIt(modifiers)
+        );
+        self.platform_modifiers
+            .store(modifiers.bits() as u16, Ordering::Relaxed);
+        self.set_lock(modifiers.locks(), true);
+        self.set_lock(Modifiers::all_locks() - modifiers.locks(), false);
+    }
+
+    /// Records that a window gained keyboard focus.
+    pub(crate) fn set_focus(&self, window_ptr: *mut c_void) {
+        self.set_focus_at(window_ptr, Timestamp::now());
+    }
+
+    /// Like [`Shared::set_focus`], for a change that occurred at `timestamp`.
+    pub(crate) fn set_focus_at(&self, window_ptr: *mut c_void, timestamp: Timestamp) {
+        logwise::debuginternal_sync!(
+            "Keyboard focus entered {window}",
+            window = logwise::privacy::LogIt(window_ptr)
+        );
+        self.focused_window.store(window_ptr, Ordering::Relaxed);
+        self.inputs.send(&InputEvent::Focus(FocusEvent::new(
+            true,
+            NonNull::new(window_ptr).map(Window),
+            timestamp,
+        )));
+    }
+
+    /// Records that a window lost keyboard focus, and releases every held key.
+    ///
+    /// Returns the keys that were released.
+    pub(crate) fn clear_focus(&self, window_ptr: *mut c_void) -> Vec<KeyboardKey> {
+        self.clear_focus_at(window_ptr, Timestamp::now())
+    }
+
+    /// Like [`Shared::clear_focus`], for a change that occurred at `timestamp`.
+    pub(crate) fn clear_focus_at(
+        &self,
+        window_ptr: *mut c_void,
+        timestamp: Timestamp,
+    ) -> Vec<KeyboardKey> {
+        logwise::debuginternal_sync!(
+            "Keyboard focus left {window}",
+            window = logwise::privacy::LogIt(window_ptr)
+        );
+        self.focused_window
+            .store(std::ptr::null_mut(), Ordering::Relaxed);
+        self.inputs.send(&InputEvent::Focus(FocusEvent::new(
+            false,
+            NonNull::new(window_ptr).map(Window),
+            timestamp,
+        )));
+        let mut released = Vec::new();
+        for key in self.known_keys() {
+            if self.is_pressed(key) {
+                self.set_key_state_at(key, false, window_ptr, timestamp);
+                released.push(key);
+            }
+        }
+        //whatever the platform said was held is no longer held
+        let platform = self.platform_modifiers.load(Ordering::Relaxed);
+        if platform != Shared::NO_PLATFORM_MODIFIERS {
+            let locks = Modifiers::from_bits_truncate(platform as u8).locks();
+            self.platform_modifiers
+                .store(locks.bits() as u16, Ordering::Relaxed);
+        }
+        released
+    }
+
+    /// Sets the state of every key so that exactly `pressed` are held.
+    ///
+    /// Keys whose state changes produce events as if they were pressed or released.
+    pub(crate) fn sync_pressed_keys(&self, pressed: &[KeyboardKey], window_ptr: *mut c_void) {
+        let mut keys = self.known_keys();
+        //unknown keys pressed before we first saw them
+        let unseen: Vec<KeyboardKey> = pressed
+            .iter()
+            .filter(|key| !keys.contains(key))
+            .copied()
+            .collect();
+        keys.extend(unseen);
+        for key in keys {
+            let should_press = pressed.contains(&key);
+            if self.is_pressed(key) != should_press {
+                self.set_key_state(key, should_press, window_ptr);
+            }
+        }
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        let platform = self.platform_modifiers.load(Ordering::Relaxed);
+        if platform != Shared::NO_PLATFORM_MODIFIERS {
+            return Modifiers::from_bits_truncate(platform as u8);
+        }
+        let mut modifiers = Modifiers::from_bits_truncate(self.locks.load(Ordering::Relaxed));
+        for (index, key) in KeyboardKey::all_keys().into_iter().enumerate() {
+            if self.key_states[index].load(Ordering::Relaxed) {
+                modifiers |= Modifiers::from_key(key);
+            }
+        }
+        modifiers
     }
 }
 
@@ -132,10 +721,14 @@ impl Shared {
 #[derive(Debug)]
 pub struct Keyboard {
     shared: Arc<Shared>,
-    _platform_coalesced_keyboard: PlatformCoalescedKeyboard,
+    /// `None` for a [virtual device](Keyboard::virtual_device).
+    _platform_coalesced_keyboard: Option<PlatformCoalescedKeyboard>,
 }
 
 impl Keyboard {
+    /// The number of events [`Keyboard::drain_events`] buffers by default.
+    pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
     /// Creates a keyboard instance representing all physical keyboards on the system.
     ///
     /// This constructor creates a single logical keyboard that coalesces input from all
@@ -151,13 +744,34 @@ impl Keyboard {
     /// ```
     pub fn coalesced() -> Self {
         let shared = Arc::new(Shared::new());
-        let _platform_coalesced_keyboard = PlatformCoalescedKeyboard::new(&shared);
+        let _platform_coalesced_keyboard = Some(PlatformCoalescedKeyboard::new(&shared));
         Self {
             shared,
             _platform_coalesced_keyboard,
         }
     }
 
+    /// Creates a keyboard that receives no platform input, only the input injected through the
+    /// returned [`VirtualKeyboard`].
+    ///
+    /// Use this to test input-dependent code without a window system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::{Keyboard, key::KeyboardKey};
+    ///
+    /// let device = Keyboard::virtual_device();
+    /// device.press(KeyboardKey::Control);
+    /// assert!(device.keyboard().is_pressed(KeyboardKey::Control));
+    /// ```
+    pub fn virtual_device() -> VirtualKeyboard {
+        VirtualKeyboard::new(Keyboard {
+            shared: Arc::new(Shared::new()),
+            _platform_coalesced_keyboard: None,
+        })
+    }
+
     /// Checks if the specified key is currently pressed.
     ///
     /// Returns `true` if the key is currently held down, `false` otherwise.
@@ -193,7 +807,295 @@ impl Keyboard {
     /// }
     /// ```
     pub fn is_pressed(&self, key: KeyboardKey) -> bool {
-        self.shared.key_states[key as usize].load(std::sync::atomic::Ordering::Relaxed)
+        self.shared.is_pressed(key)
+    }
+
+    /// Returns `true` if the chord's key is pressed and exactly the chord's modifiers are held.
+    ///
+    /// Extra held modifiers prevent a match, so `Ctrl+S` is not pressed while `Ctrl+Shift+S` is.
+    /// Lock toggles are ignored.  See [`KeyChord::matches_modifiers`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::Keyboard;
+    /// use app_input::keyboard::chord::KeyChord;
+    ///
+    /// let keyboard = Keyboard::coalesced();
+    /// let save: KeyChord = "Ctrl+S".parse().unwrap();
+    /// if keyboard.is_chord_pressed(&save) {
+    ///     // Handle save
+    /// }
+    /// ```
+    pub fn is_chord_pressed(&self, chord: &KeyChord) -> bool {
+        self.is_pressed(chord.key()) && chord.matches_modifiers(self.modifiers())
+    }
+
+    /// Returns the time at which the specified key was pressed, or `None` if it is not pressed.
+    ///
+    /// Subtract this from [`Timestamp::now`] to find how long the key has been held.  Repeats do
+    /// not restart the hold.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::{Keyboard, key::KeyboardKey};
+    /// use app_input::timestamp::Timestamp;
+    /// use std::time::Duration;
+    ///
+    /// let keyboard = Keyboard::coalesced();
+    /// if let Some(since) = keyboard.pressed_since(KeyboardKey::Space) {
+    ///     let charge = Timestamp::now() - since;
+    ///     if charge > Duration::from_secs(1) {
+    ///         // fully charged jump
+    ///     }
+    /// }
+    /// ```
+    pub fn pressed_since(&self, key: KeyboardKey) -> Option<Timestamp> {
+        self.shared.pressed_since(key)
+    }
+
+    /// Returns the modifiers that are currently held and the locks that are currently toggled on.
+    ///
+    /// Left and right variants of each modifier are combined.  See [`Modifiers`] for details.
+    ///
+    /// # Platform specifics
+    ///
+    /// * **Linux**: the state reported by [`crate::linux::wl_keyboard_modifiers_event`] is authoritative.
+    ///   Until it is called, modifiers are derived from key state and lock toggles are assumed to
+    ///   start off.
+    /// * **Windows**, **macOS** and **WASM**: lock state is read from the platform with each key event.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::Keyboard;
+    /// use app_input::keyboard::modifiers::Modifiers;
+    ///
+    /// let keyboard = Keyboard::coalesced();
+    /// let modifiers = keyboard.modifiers();
+    /// if modifiers.held() == Modifiers::CONTROL {
+    ///     // Control, and no other modifier, is held
+    /// }
+    /// if modifiers.contains(Modifiers::CAPS_LOCK) {
+    ///     // Caps lock is on
+    /// }
+    /// ```
+    pub fn modifiers(&self) -> Modifiers {
+        self.shared.modifiers()
+    }
+
+    /// Returns the window that currently has keyboard focus, if known.
+    ///
+    /// When a window loses focus, every key that was held is released, producing release events.
+    /// This prevents keys from appearing stuck when the release happens in another application.
+    ///
+    /// # Platform specifics
+    ///
+    /// * **Linux**: You must call [`crate::linux::wl_keyboard_enter_event`] and
+    ///   [`crate::linux::wl_keyboard_leave_event`] from your Wayland dispatch queue.
+    /// * **Windows**: You must call `window_proc` from your window procedure.
+    /// * **macOS**: Focus follows the application's key window.
+    /// * **WASM**: Focus follows the browser window's `focus` and `blur` events.
+    pub fn focused_window(&self) -> Option<Window> {
+        NonNull::new(self.shared.focused_window.load(Ordering::Relaxed)).map(Window)
+    }
+
+    /// Returns the key events received since the previous call, in the order they occurred.
+    ///
+    /// Unlike [`Keyboard::is_pressed`], this observes every transition, so a key that was pressed and
+    /// released between two calls is reported as two events.
+    ///
+    /// Events are buffered up to a bounded capacity ([`Keyboard::DEFAULT_EVENT_CAPACITY`] unless changed
+    /// with [`Keyboard::set_event_capacity`]).  If more events arrive before the next drain, the oldest
+    /// events are discarded and [`KeyEvents::overflowed`] returns `true`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::{Keyboard, key::KeyboardKey};
+    ///
+    /// let keyboard = Keyboard::coalesced();
+    ///
+    /// // Once per frame:
+    /// let events = keyboard.drain_events();
+    /// if events.overflowed() {
+    ///     // some events were lost; fall back to polling
+    /// }
+    /// for event in &events {
+    ///     if event.key() == KeyboardKey::Space && event.down() {
+    ///         // Handle a tap, no matter how short
+    ///     }
+    /// }
+    /// ```
+    pub fn drain_events(&self) -> KeyEvents {
+        let (events, overflowed) = self.shared.events.lock().unwrap().drain();
+        KeyEvents { events, overflowed }
+    }
+
+    /// Calls `callback` with every key event, including repeats, as soon as it is received.
+    ///
+    /// The closure runs synchronously on the thread that delivers input, such as the thread running
+    /// your event loop.  It should return quickly, and must not create a new `Keyboard`, which would
+    /// deadlock.  Querying this `Keyboard` and registering or dropping subscriptions are fine.
+    ///
+    /// On Linux under Wayland, key repeats are synthesized by the crate on a background thread, so
+    /// the closure runs on that thread for repeats.
+    ///
+    /// The closure is unregistered when the returned [`Subscription`] is dropped.  See the
+    /// [`observer`](crate::observer) module.
+    pub fn on_event(&self, callback: impl Fn(&KeyEvent) + Send + Sync + 'static) -> Subscription {
+        self.shared.observers.subscribe(callback)
+    }
+
+    /// Returns a stream of the key events received from now on.
+    ///
+    /// See the [`stream`](crate::stream) module.  Each stream buffers up to the event capacity; see
+    /// [`Keyboard::set_event_capacity`].
+    pub fn events(&self) -> EventStream<KeyEvent> {
+        let capacity = self.shared.events.lock().unwrap().capacity();
+        self.shared.streams.subscribe(capacity)
+    }
+
+    /// Waits for the next key to be pressed, ignoring repeats, and returns the press.
+    ///
+    /// Only presses after this call count, even if the future is first polled later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::Keyboard;
+    ///
+    /// async fn press_any_key(keyboard: &Keyboard) {
+    ///     let event = keyboard.next_press().await;
+    ///     println!("{} pressed", event.key());
+    /// }
+    /// ```
+    pub fn next_press(&self) -> impl Future<Output = KeyEvent> + Send + 'static {
+        let mut events = self.events();
+        async move {
+            loop {
+                let event = events.next().await;
+                if event.down() && !event.repeat() {
+                    return event;
+                }
+            }
+        }
+    }
+
+    /// Returns the text typed since the previous call, in order, and clears the buffer.
+    ///
+    /// Text input is separate from key events: it reflects the user's layout, shift state, dead
+    /// keys and compose sequences, and one [`TextInput`] may correspond to several key presses or
+    /// none.  Use it for text fields; use [`Keyboard::drain_events`] for shortcuts and gameplay.
+    ///
+    /// Control characters (such as those produced by Return, Backspace or Tab) are not reported
+    /// as text.  Observe the keys instead.
+    ///
+    /// Text is buffered with the same capacity as key events; see [`Keyboard::set_event_capacity`].
+    ///
+    /// # Platform specifics
+    ///
+    /// * **Linux**: Text is produced from the compositor's keymap and the locale's compose table, see
+    ///   [`crate::linux::wl_keyboard_keymap_event`].  Without a keymap, no text is reported.
+    /// * **Windows**: Text comes from `WM_CHAR`, so your message loop must call `TranslateMessage`.
+    /// * **macOS**: Text comes from the characters of each key down event.  Keys pressed with
+    ///   Command held do not produce text.
+    /// * **WASM**: Text comes from `KeyboardEvent.key`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::keyboard::Keyboard;
+    ///
+    /// let keyboard = Keyboard::coalesced();
+    /// let mut name = String::new();
+    ///
+    /// // Once per frame:
+    /// for input in keyboard.drain_text_input() {
+    ///     name.push_str(input.text());
+    /// }
+    /// ```
+    pub fn drain_text_input(&self) -> Vec<TextInput> {
+        self.shared.text.lock().unwrap().drain().0
+    }
+
+    /// Returns the input method events received since the previous call, in order, and clears the buffer.
+    ///
+    /// Input methods are only active after [`Keyboard::set_ime_allowed`].  Key events continue to be
+    /// reported as usual, except for keys the input method consumes.  See [`ImeEvent`] for how
+    /// to apply the events.
+    ///
+    /// Events are buffered with the same capacity as key events; see [`Keyboard::set_event_capacity`].
+    ///
+    /// # Platform specifics
+    ///
+    /// * **Linux**: You must forward `text-input-unstable-v3` events with
+    ///   [`crate::linux::zwp_text_input_v3_event`].
+    /// * **WASM**: Composition events are delivered while an editable element has focus.
+    /// * **Windows** and **macOS**: Input methods are not yet supported, and no events are produced.
+    ///   Composed text is reported by [`Keyboard::drain_text_input`].
+    pub fn drain_ime_events(&self) -> Vec<ImeEvent> {
+        self.shared.ime.lock().unwrap().drain().0
+    }
+
+    /// Allows or disallows the input method.
+    ///
+    /// Allow the input method while a text field has focus, and disallow it otherwise, so that keys
+    /// pressed during gameplay are not intercepted.  Input methods start out disallowed.
+    ///
+    /// This setting is shared by every `Keyboard` in the process.
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        sys::set_ime_allowed(allowed)
+    }
+
+    /// Tells the input method where the text cursor is, so it can position its candidate window nearby.
+    ///
+    /// The rectangle is in the same coordinate space as
+    /// [`crate::mouse::MouseWindowLocation`]: logical pixels relative to the focused window.
+    ///
+    /// This setting is shared by every `Keyboard` in the process.
+    pub fn set_ime_cursor_area(&self, x: f64, y: f64, width: f64, height: f64) {
+        sys::set_ime_cursor_area(x, y, width, height)
+    }
+
+    /// Returns the number of times `key` has been pressed and released, respectively.
+    ///
+    /// The counts wrap on overflow, so compare them for equality only.
+    pub(crate) fn transition_counts(&self, key: KeyboardKey) -> (u32, u32) {
+        self.shared.transition_counts(key)
+    }
+
+    /// Returns every key whose state is tracked: all named keys, and every unknown key seen so far.
+    pub(crate) fn known_keys(&self) -> Vec<KeyboardKey> {
+        self.shared.known_keys()
+    }
+
+    pub(crate) fn shared(&self) -> &Arc<Shared> {
+        &self.shared
+    }
+
+    /// Delivers this keyboard's events to an [`InputContext`](crate::context::InputContext)'s stream.
+    pub(crate) fn attach_input_stream(&self, stream: &EventStream<InputEvent>) {
+        self.shared.inputs.attach(stream);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn shared_for_test(&self) -> &Shared {
+        &self.shared
+    }
+
+    /// Sets the maximum number of events buffered between calls to [`Keyboard::drain_events`].
+    ///
+    /// The same capacity applies separately to [`Keyboard::drain_text_input`],
+    /// [`Keyboard::drain_ime_events`] and streams created afterwards by [`Keyboard::events`].
+    ///
+    /// If the buffer currently holds more events than `capacity`, the oldest are discarded.
+    /// A capacity of zero disables buffering.
+    pub fn set_event_capacity(&self, capacity: usize) {
+        self.shared.events.lock().unwrap().set_capacity(capacity);
+        self.shared.text.lock().unwrap().set_capacity(capacity);
+        self.shared.ime.lock().unwrap().set_capacity(capacity);
     }
 }
 
@@ -232,6 +1134,9 @@ impl Default for Keyboard {
 #[cfg(test)]
 mod test {
     use crate::keyboard::Keyboard;
+    use crate::keyboard::key::{KeyboardKey, RawScancode, ScancodeKind};
+    use crate::timestamp::Timestamp;
+    use std::time::Duration;
 
     #[test]
     fn test_send_sync() {
@@ -247,4 +1152,220 @@ mod test {
         assert_sync::<Keyboard>();
         assert_unpin::<Keyboard>();
     }
+
+    #[test]
+    fn test_drain_events() {
+        let keyboard = Keyboard::coalesced();
+        let window = 0x10 as *mut std::ffi::c_void;
+        keyboard.shared.set_key_state(KeyboardKey::A, true, window);
+        keyboard.shared.set_key_state(KeyboardKey::A, false, window);
+        assert!(!keyboard.is_pressed(KeyboardKey::A));
+
+        let events = keyboard.drain_events();
+        assert!(!events.overflowed());
+        let transitions: Vec<_> = events.iter().map(|e| (e.key(), e.down())).collect();
+        assert_eq!(
+            transitions,
+            [(KeyboardKey::A, true), (KeyboardKey::A, false)]
+        );
+        assert_eq!(
+            events.iter().next().unwrap().window().unwrap().0.as_ptr(),
+            window
+        );
+        assert!(keyboard.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_drain_events_overflow() {
+        let keyboard = Keyboard::coalesced();
+        keyboard.set_event_capacity(2);
+        for key in [KeyboardKey::A, KeyboardKey::S, KeyboardKey::D] {
+            keyboard
+                .shared
+                .set_key_state(key, true, std::ptr::null_mut());
+        }
+        let events = keyboard.drain_events();
+        assert!(events.overflowed());
+        let keys: Vec<_> = events.iter().map(|e| e.key()).collect();
+        assert_eq!(keys, [KeyboardKey::S, KeyboardKey::D]);
+        assert!(!keyboard.drain_events().overflowed());
+    }
+
+    #[test]
+    fn test_modifiers() {
+        use crate::keyboard::modifiers::Modifiers;
+        let keyboard = Keyboard::coalesced();
+        let shared = &keyboard.shared;
+        shared.set_key_state(KeyboardKey::RightShift, true, std::ptr::null_mut());
+        shared.set_key_state(KeyboardKey::Control, true, std::ptr::null_mut());
+        assert_eq!(keyboard.modifiers(), Modifiers::SHIFT | Modifiers::CONTROL);
+
+        //locks toggle on press, not on release
+        shared.set_key_state(KeyboardKey::CapsLock, true, std::ptr::null_mut());
+        shared.set_key_state(KeyboardKey::CapsLock, false, std::ptr::null_mut());
+        assert!(keyboard.modifiers().contains(Modifiers::CAPS_LOCK));
+        shared.set_key_state(KeyboardKey::CapsLock, true, std::ptr::null_mut());
+        assert!(!keyboard.modifiers().contains(Modifiers::CAPS_LOCK));
+
+        shared.set_platform_modifiers(Modifiers::NUM_LOCK);
+        assert_eq!(keyboard.modifiers(), Modifiers::NUM_LOCK);
+    }
+
+    #[test]
+    fn test_chord_pressed() {
+        use crate::keyboard::chord::KeyChord;
+        let keyboard = Keyboard::coalesced();
+        let shared = &keyboard.shared;
+        let save: KeyChord = "Ctrl+S".parse().unwrap();
+        let save_as: KeyChord = "Ctrl+Shift+S".parse().unwrap();
+        shared.set_key_state(KeyboardKey::Control, true, std::ptr::null_mut());
+        assert!(!keyboard.is_chord_pressed(&save));
+        shared.set_key_state(KeyboardKey::S, true, std::ptr::null_mut());
+        assert!(keyboard.is_chord_pressed(&save));
+        assert!(!keyboard.is_chord_pressed(&save_as));
+        shared.set_key_state(KeyboardKey::RightShift, true, std::ptr::null_mut());
+        assert!(!keyboard.is_chord_pressed(&save));
+        assert!(keyboard.is_chord_pressed(&save_as));
+    }
+
+    #[test]
+    fn test_focus_loss_releases_keys() {
+        let keyboard = Keyboard::coalesced();
+        let window = 0x10 as *mut std::ffi::c_void;
+        let shared = &keyboard.shared;
+        shared.set_focus(window);
+        assert_eq!(keyboard.focused_window().unwrap().0.as_ptr(), window);
+        shared.set_key_state(KeyboardKey::W, true, window);
+        let _ = keyboard.drain_events();
+
+        assert_eq!(shared.clear_focus(window), [KeyboardKey::W]);
+        assert!(keyboard.focused_window().is_none());
+        assert!(!keyboard.is_pressed(KeyboardKey::W));
+        let events = keyboard.drain_events();
+        let transitions: Vec<_> = events.iter().map(|e| (e.key(), e.down())).collect();
+        assert_eq!(transitions, [(KeyboardKey::W, false)]);
+
+        shared.sync_pressed_keys(&[KeyboardKey::A], window);
+        assert!(keyboard.is_pressed(KeyboardKey::A));
+        shared.sync_pressed_keys(&[], window);
+        assert!(!keyboard.is_pressed(KeyboardKey::A));
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let keyboard = Keyboard::coalesced();
+        let window = 0x10 as *mut std::ffi::c_void;
+        let shared = &keyboard.shared;
+        let unknown = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x290));
+        assert!(!keyboard.is_pressed(unknown));
+        shared.set_key_state(unknown, true, window);
+        assert!(keyboard.is_pressed(unknown));
+        assert_eq!(keyboard.transition_counts(unknown), (1, 0));
+        let events = keyboard.drain_events();
+        let transitions: Vec<_> = events.iter().map(|e| (e.key(), e.down())).collect();
+        assert_eq!(transitions, [(unknown, true)]);
+
+        assert_eq!(shared.clear_focus(window), [unknown]);
+        assert!(!keyboard.is_pressed(unknown));
+        assert_eq!(keyboard.transition_counts(unknown), (1, 1));
+
+        let other = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x291));
+        shared.sync_pressed_keys(&[other], window);
+        assert!(keyboard.is_pressed(other));
+        assert_eq!(
+            keyboard.modifiers(),
+            crate::keyboard::modifiers::Modifiers::empty()
+        );
+    }
+
+    #[test]
+    fn test_key_index() {
+        for (index, key) in KeyboardKey::all_keys().into_iter().enumerate() {
+            assert_eq!(key.index(), Some(index), "{key:?}");
+        }
+        let unknown = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::WebKeyCode, 0));
+        assert_eq!(unknown.index(), None);
+    }
+
+    #[test]
+    fn test_repeat_key() {
+        let keyboard = Keyboard::coalesced();
+        let shared = &keyboard.shared;
+        //a repeat without a press is treated as a press
+        shared.repeat_key(KeyboardKey::J, std::ptr::null_mut());
+        shared.repeat_key(KeyboardKey::J, std::ptr::null_mut());
+        assert_eq!(keyboard.transition_counts(KeyboardKey::J), (1, 0));
+        let events = keyboard.drain_events();
+        let repeats: Vec<_> = events.iter().map(|e| (e.down(), e.repeat())).collect();
+        assert_eq!(repeats, [(true, false), (true, true)]);
+    }
+
+    #[test]
+    fn test_pressed_since() {
+        let keyboard = Keyboard::coalesced();
+        let shared = &keyboard.shared;
+        let at = |ms| Timestamp::from_duration_since_epoch(Duration::from_millis(ms));
+        let window = std::ptr::null_mut();
+        let unknown = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x2ff));
+        for key in [KeyboardKey::Space, unknown] {
+            assert_eq!(keyboard.pressed_since(key), None);
+            shared.set_key_state_at(key, true, window, at(100));
+            //repeats don't restart the hold
+            shared.repeat_key_at(key, window, at(600));
+            assert_eq!(keyboard.pressed_since(key), Some(at(100)));
+            shared.set_key_state_at(key, false, window, at(700));
+            assert_eq!(keyboard.pressed_since(key), None);
+        }
+        let events = keyboard.drain_events();
+        let times: Vec<_> = events.iter().take(3).map(|e| e.timestamp()).collect();
+        assert_eq!(times, [at(100), at(600), at(700)]);
+    }
+
+    #[test]
+    fn test_text_input() {
+        let keyboard = Keyboard::coalesced();
+        let shared = &keyboard.shared;
+        shared.push_text("é", std::ptr::null_mut());
+        //control characters are observed as keys instead
+        shared.push_text("\r", std::ptr::null_mut());
+        shared.push_text("a\u{8}b", std::ptr::null_mut());
+        let text: Vec<_> = keyboard
+            .drain_text_input()
+            .into_iter()
+            .map(|t| t.into_text())
+            .collect();
+        assert_eq!(text, ["é", "ab"]);
+        assert!(keyboard.drain_text_input().is_empty());
+        //text does not produce key events
+        assert!(keyboard.drain_events().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_names() {
+        assert_eq!(
+            serde_json::to_string(&KeyboardKey::KeypadEnter).unwrap(),
+            "\"KeypadEnter\""
+        );
+        assert_eq!(
+            serde_json::to_string(&KeyboardKey::RightCommand).unwrap(),
+            "\"RightCommand\""
+        );
+        let key: KeyboardKey = serde_json::from_str("\"Space\"").unwrap();
+        assert_eq!(key, KeyboardKey::Space);
+
+        let unknown = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x2ff));
+        let json = serde_json::to_string(&unknown).unwrap();
+        assert_eq!(serde_json::from_str::<KeyboardKey>(&json).unwrap(), unknown);
+        let key: KeyboardKey = serde_json::from_str("\"Esc\"").unwrap();
+        assert_eq!(key, KeyboardKey::Escape);
+
+        //formats that aren't self-describing store the name too, not the variant index
+        let encoded = bincode::serialize(&KeyboardKey::KeypadEnter).unwrap();
+        assert!(encoded.ends_with(b"KeypadEnter"));
+        for key in [KeyboardKey::KeypadEnter, unknown] {
+            let encoded = bincode::serialize(&key).unwrap();
+            assert_eq!(bincode::deserialize::<KeyboardKey>(&encoded).unwrap(), key);
+        }
+    }
 }
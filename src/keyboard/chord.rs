@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::keyboard::key::{KeyboardKey, ParseKeyError};
+use crate::keyboard::modifiers::Modifiers;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/**
+A conventional way of writing keyboard shortcuts.
+
+[`ChordStyle::native`] picks the style of the platform the library was compiled for.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ChordStyle {
+    /// Modifier glyphs in the order ⌃⌥⇧⌘, followed by the key, e.g. `⇧⌘S`.
+    MacOS,
+    /// Names joined by `+`, e.g. `Ctrl+Shift+S`, with [`Modifiers::COMMAND`] written as `Win`.
+    Windows,
+    /// Names joined by `+`, e.g. `Ctrl+Shift+S`, with [`Modifiers::COMMAND`] written as `Super`.
+    Linux,
+}
+
+impl ChordStyle {
+    /// Returns the style of the platform the library was compiled for.
+    ///
+    /// WASM uses [`ChordStyle::Linux`], since the browser's host platform is not known at compile time.
+    pub const fn native() -> Self {
+        if cfg!(target_os = "macos") {
+            ChordStyle::MacOS
+        } else if cfg!(target_os = "windows") {
+            ChordStyle::Windows
+        } else {
+            ChordStyle::Linux
+        }
+    }
+}
+
+/// Modifier names in the order [`ChordStyle::Windows`] and [`ChordStyle::Linux`] write them.
+const TEXT_ORDER: [Modifiers; 5] = [
+    Modifiers::CONTROL,
+    Modifiers::SHIFT,
+    Modifiers::OPTION,
+    Modifiers::COMMAND,
+    Modifiers::FUNCTION,
+];
+
+/// Modifier glyphs in the order macOS menus write them.
+const GLYPH_ORDER: [(Modifiers, &str); 5] = [
+    (Modifiers::FUNCTION, "fn"),
+    (Modifiers::CONTROL, "⌃"),
+    (Modifiers::OPTION, "⌥"),
+    (Modifiers::SHIFT, "⇧"),
+    (Modifiers::COMMAND, "⌘"),
+];
+
+/**
+A key pressed together with a set of modifiers, such as a menu shortcut.
+
+Chords parse from and format to the strings users see in menus and settings screens.
+
+# Example
+
+```
+use app_input::keyboard::chord::{ChordStyle, KeyChord};
+use app_input::keyboard::key::KeyboardKey;
+use app_input::keyboard::modifiers::Modifiers;
+
+let save: KeyChord = "Ctrl+Shift+S".parse().unwrap();
+assert_eq!(save, KeyChord::new(Modifiers::CONTROL | Modifiers::SHIFT, KeyboardKey::S));
+assert_eq!(save.format(ChordStyle::Linux), "Ctrl+Shift+S");
+assert_eq!(save.format(ChordStyle::MacOS), "⌃⇧S");
+
+//macOS notation parses too
+assert_eq!("⇧⌘S".parse(), Ok(KeyChord::new(Modifiers::SHIFT | Modifiers::COMMAND, KeyboardKey::S)));
+```
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyChord {
+    modifiers: Modifiers,
+    key: KeyboardKey,
+}
+
+impl KeyChord {
+    /// Creates a chord.
+    ///
+    /// Lock toggles are discarded from `modifiers`, since they are not held.
+    pub const fn new(modifiers: Modifiers, key: KeyboardKey) -> Self {
+        KeyChord {
+            modifiers: modifiers.held(),
+            key,
+        }
+    }
+
+    /// The modifiers that must be held.
+    pub const fn modifiers(self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// The key that must be pressed.
+    pub const fn key(self) -> KeyboardKey {
+        self.key
+    }
+
+    /**
+    Returns `true` if `modifiers` are exactly the chord's modifiers.
+
+    Lock toggles are ignored.  If the chord's key is itself a modifier, holding it does not
+    count against the match.
+    */
+    pub fn matches_modifiers(self, modifiers: Modifiers) -> bool {
+        let own = Modifiers::from_key(self.key);
+        modifiers.held() - own == self.modifiers - own
+    }
+
+    /// Formats the chord in the specified style.
+    pub fn format(self, style: ChordStyle) -> String {
+        let mut out = String::new();
+        match style {
+            ChordStyle::MacOS => {
+                for (modifier, glyph) in GLYPH_ORDER {
+                    if self.modifiers.contains(modifier) {
+                        out.push_str(glyph);
+                    }
+                }
+            }
+            ChordStyle::Windows | ChordStyle::Linux => {
+                for modifier in TEXT_ORDER {
+                    if self.modifiers.contains(modifier) {
+                        out.push_str(text_name(modifier, style));
+                        out.push('+');
+                    }
+                }
+            }
+        }
+        match key_label(self.key, style) {
+            Some(label) => out.push_str(label),
+            None => out.push_str(&self.key.to_string()),
+        }
+        out
+    }
+}
+
+fn text_name(modifier: Modifiers, style: ChordStyle) -> &'static str {
+    match modifier {
+        Modifiers::CONTROL => "Ctrl",
+        Modifiers::SHIFT => "Shift",
+        Modifiers::OPTION => "Alt",
+        Modifiers::COMMAND if style == ChordStyle::Windows => "Win",
+        Modifiers::COMMAND => "Super",
+        Modifiers::FUNCTION => "Fn",
+        _ => unreachable!(),
+    }
+}
+
+/// The label a user expects for a key, when it differs from [`KeyboardKey::name`].
+fn key_label(key: KeyboardKey, style: ChordStyle) -> Option<&'static str> {
+    let shared = match key {
+        KeyboardKey::Num0 => "0",
+        KeyboardKey::Num1 => "1",
+        KeyboardKey::Num2 => "2",
+        KeyboardKey::Num3 => "3",
+        KeyboardKey::Num4 => "4",
+        KeyboardKey::Num5 => "5",
+        KeyboardKey::Num6 => "6",
+        KeyboardKey::Num7 => "7",
+        KeyboardKey::Num8 => "8",
+        KeyboardKey::Num9 => "9",
+        KeyboardKey::Equal => "=",
+        KeyboardKey::Minus => "-",
+        KeyboardKey::LeftBracket => "[",
+        KeyboardKey::RightBracket => "]",
+        KeyboardKey::Quote => "'",
+        KeyboardKey::Semicolon => ";",
+        KeyboardKey::Backslash => "\\",
+        KeyboardKey::Comma => ",",
+        KeyboardKey::Slash => "/",
+        KeyboardKey::Period => ".",
+        KeyboardKey::Grave => "`",
+        _ => "",
+    };
+    if !shared.is_empty() {
+        return Some(shared);
+    }
+    if style == ChordStyle::MacOS {
+        Some(match key {
+            KeyboardKey::Return => "↩",
+            KeyboardKey::Tab => "⇥",
+            KeyboardKey::Delete => "⌫",
+            KeyboardKey::ForwardDelete => "⌦",
+            KeyboardKey::Escape => "⎋",
+            KeyboardKey::LeftArrow => "←",
+            KeyboardKey::RightArrow => "→",
+            KeyboardKey::UpArrow => "↑",
+            KeyboardKey::DownArrow => "↓",
+            KeyboardKey::Home => "↖",
+            KeyboardKey::End => "↘",
+            KeyboardKey::PageUp => "⇞",
+            KeyboardKey::PageDown => "⇟",
+            KeyboardKey::KeypadEnter => "⌤",
+            _ => return None,
+        })
+    } else {
+        Some(match key {
+            KeyboardKey::Return => "Enter",
+            KeyboardKey::Delete => "Backspace",
+            KeyboardKey::ForwardDelete => "Del",
+            KeyboardKey::Escape => "Esc",
+            KeyboardKey::LeftArrow => "Left",
+            KeyboardKey::RightArrow => "Right",
+            KeyboardKey::UpArrow => "Up",
+            KeyboardKey::DownArrow => "Down",
+            _ => return None,
+        })
+    }
+}
+
+fn parse_modifier(s: &str) -> Option<Modifiers> {
+    const NAMES: [(&str, Modifiers); 14] = [
+        ("Ctrl", Modifiers::CONTROL),
+        ("Control", Modifiers::CONTROL),
+        ("Shift", Modifiers::SHIFT),
+        ("Alt", Modifiers::OPTION),
+        ("Option", Modifiers::OPTION),
+        ("Opt", Modifiers::OPTION),
+        ("Cmd", Modifiers::COMMAND),
+        ("Command", Modifiers::COMMAND),
+        ("Super", Modifiers::COMMAND),
+        ("Meta", Modifiers::COMMAND),
+        ("Win", Modifiers::COMMAND),
+        ("Windows", Modifiers::COMMAND),
+        ("Fn", Modifiers::FUNCTION),
+        ("Function", Modifiers::FUNCTION),
+    ];
+    NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        .map(|(_, modifier)| *modifier)
+}
+
+/// Formats the chord in [`ChordStyle::native`].
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(ChordStyle::native()))
+    }
+}
+
+/**
+Parses a chord written in any [`ChordStyle`], regardless of the current platform.
+
+Modifiers and the key are separated by `+`, with optional surrounding whitespace.  Modifier names
+are case-insensitive and include the common aliases `Cmd`/`Command`/`Super`/`Meta`/`Win` and
+`Alt`/`Option`.  macOS glyphs may instead be written directly before the key, as in `⇧⌘S`.  The
+key is parsed with [`KeyboardKey::from_str`].
+*/
+impl FromStr for KeyChord {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::empty();
+        let mut rest = s.trim();
+        //glyph prefixes, as written by ChordStyle::MacOS
+        'glyphs: loop {
+            for (modifier, glyph) in GLYPH_ORDER {
+                if let Some(after) = rest.strip_prefix(glyph) {
+                    if !after.is_empty() && !after.starts_with('+') {
+                        modifiers |= modifier;
+                        rest = after;
+                        continue 'glyphs;
+                    }
+                }
+            }
+            break;
+        }
+        let (names, key) = match rest.rsplit_once('+') {
+            Some((names, key)) => (Some(names), key),
+            None => (None, rest),
+        };
+        for name in names.into_iter().flat_map(|names| names.split('+')) {
+            let name = name.trim();
+            modifiers |= parse_modifier(name)
+                .or_else(|| {
+                    GLYPH_ORDER
+                        .iter()
+                        .find(|(_, glyph)| *glyph == name)
+                        .map(|(modifier, _)| *modifier)
+                })
+                .ok_or_else(|| ParseKeyError::new(name))?;
+        }
+        Ok(KeyChord::new(modifiers, key.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ChordStyle, KeyChord};
+    use crate::keyboard::key::KeyboardKey;
+    use crate::keyboard::modifiers::Modifiers;
+
+    #[test]
+    fn test_parse_aliases() {
+        let expected = KeyChord::new(Modifiers::COMMAND | Modifiers::OPTION, KeyboardKey::P);
+        for text in [
+            "Cmd+Alt+P",
+            "command+option+p",
+            "Super + Alt + P",
+            "Meta+Opt+P",
+            "Win+Alt+P",
+            "⌥⌘P",
+        ] {
+            assert_eq!(text.parse(), Ok(expected), "{text}");
+        }
+        assert!("Hyper+P".parse::<KeyChord>().is_err());
+        assert!("Ctrl+".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let chord = KeyChord::new(Modifiers::SHIFT | Modifiers::COMMAND, KeyboardKey::S);
+        assert_eq!(chord.format(ChordStyle::MacOS), "⇧⌘S");
+        assert_eq!(chord.format(ChordStyle::Linux), "Shift+Super+S");
+        assert_eq!(chord.format(ChordStyle::Windows), "Shift+Win+S");
+
+        let chord = KeyChord::new(Modifiers::CONTROL | Modifiers::OPTION, KeyboardKey::Delete);
+        assert_eq!(chord.format(ChordStyle::MacOS), "⌃⌥⌫");
+        assert_eq!(chord.format(ChordStyle::Windows), "Ctrl+Alt+Backspace");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let modifiers = [
+            Modifiers::empty(),
+            Modifiers::CONTROL | Modifiers::SHIFT,
+            Modifiers::all_held(),
+        ];
+        for style in [ChordStyle::MacOS, ChordStyle::Windows, ChordStyle::Linux] {
+            for key in KeyboardKey::all_keys() {
+                for modifiers in modifiers {
+                    let chord = KeyChord::new(modifiers, key);
+                    let text = chord.format(style);
+                    assert_eq!(text.parse(), Ok(chord), "{text}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_modifiers() {
+        let chord = KeyChord::new(Modifiers::CONTROL, KeyboardKey::S);
+        assert!(chord.matches_modifiers(Modifiers::CONTROL | Modifiers::CAPS_LOCK));
+        assert!(!chord.matches_modifiers(Modifiers::CONTROL | Modifiers::SHIFT));
+        assert!(!chord.matches_modifiers(Modifiers::empty()));
+
+        let chord = KeyChord::new(Modifiers::CONTROL, KeyboardKey::Shift);
+        assert!(chord.matches_modifiers(Modifiers::CONTROL | Modifiers::SHIFT));
+    }
+}
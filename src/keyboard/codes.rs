@@ -0,0 +1,466 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+The canonical table of key codes.
+
+Every platform backend, and the public conversions on `KeyboardKey`, translate through this table,
+so a key maps to the same code everywhere.  Backends that receive codes outside the table (legacy
+aliases, generic modifiers) handle those themselves before falling back to `KeyboardKey::Unknown`.
+
+Sources:
+* evdev: linux/include/uapi/linux/input-event-codes.h
+* USB HID: HID Usage Tables, encoded as (usage page << 16) | usage id
+* Windows: virtual-key codes, and scan code set 1 with 0xE0 in the high byte for extended keys,
+  following the conventions of Chromium's dom_code_data.inc
+* macOS: HIToolbox/Events.h
+* Web: https://www.w3.org/TR/uievents-code/
+*/
+use crate::keyboard::key::{KeyboardKey, ScancodeKind};
+
+/// Offset between evdev codes and the X11 keycodes of the xkb evdev keycode set.
+const X11_EVDEV_OFFSET: u32 = 8;
+
+/// The code of one key in each code space, or `None` where the key has no code.
+struct Codes {
+    key: KeyboardKey,
+    evdev: Option<u16>,
+    usb_hid: Option<u32>,
+    windows_vk: Option<u16>,
+    windows_scancode: Option<u16>,
+    macos_keycode: Option<u16>,
+    web_code: Option<&'static str>,
+}
+
+macro_rules! code {
+    (_) => {
+        None
+    };
+    ($value:expr) => {
+        Some($value)
+    };
+}
+
+macro_rules! codes {
+    ($($key:ident: $evdev:tt, $usb_hid:tt, $windows_vk:tt, $windows_scancode:tt, $macos:tt, $web:tt;)*) => {
+        /// One row per key, in the order of [`KeyboardKey::all_keys`].
+        const CODES: &[Codes] = &[$(
+            Codes {
+                key: KeyboardKey::$key,
+                evdev: code!($evdev),
+                usb_hid: code!($usb_hid),
+                windows_vk: code!($windows_vk),
+                windows_scancode: code!($windows_scancode),
+                macos_keycode: code!($macos),
+                web_code: code!($web),
+            },
+        )*];
+    };
+}
+
+codes! {
+    //key:                  evdev, usb hid,  vk,   scancode, macOS, web
+    A:                      30,  0x070004, 0x41, 0x001E, 0x00, "KeyA";
+    S:                      31,  0x070016, 0x53, 0x001F, 0x01, "KeyS";
+    D:                      32,  0x070007, 0x44, 0x0020, 0x02, "KeyD";
+    F:                      33,  0x070009, 0x46, 0x0021, 0x03, "KeyF";
+    H:                      35,  0x07000B, 0x48, 0x0023, 0x04, "KeyH";
+    G:                      34,  0x07000A, 0x47, 0x0022, 0x05, "KeyG";
+    Z:                      44,  0x07001D, 0x5A, 0x002C, 0x06, "KeyZ";
+    X:                      45,  0x07001B, 0x58, 0x002D, 0x07, "KeyX";
+    C:                      46,  0x070006, 0x43, 0x002E, 0x08, "KeyC";
+    V:                      47,  0x070019, 0x56, 0x002F, 0x09, "KeyV";
+    B:                      48,  0x070005, 0x42, 0x0030, 0x0B, "KeyB";
+    Q:                      16,  0x070014, 0x51, 0x0010, 0x0C, "KeyQ";
+    W:                      17,  0x07001A, 0x57, 0x0011, 0x0D, "KeyW";
+    E:                      18,  0x070008, 0x45, 0x0012, 0x0E, "KeyE";
+    R:                      19,  0x070015, 0x52, 0x0013, 0x0F, "KeyR";
+    Y:                      21,  0x07001C, 0x59, 0x0015, 0x10, "KeyY";
+    T:                      20,  0x070017, 0x54, 0x0014, 0x11, "KeyT";
+    Num1:                   2,   0x07001E, 0x31, 0x0002, 0x12, "Digit1";
+    Num2:                   3,   0x07001F, 0x32, 0x0003, 0x13, "Digit2";
+    Num3:                   4,   0x070020, 0x33, 0x0004, 0x14, "Digit3";
+    Num4:                   5,   0x070021, 0x34, 0x0005, 0x15, "Digit4";
+    Num6:                   7,   0x070023, 0x36, 0x0007, 0x16, "Digit6";
+    Num5:                   6,   0x070022, 0x35, 0x0006, 0x17, "Digit5";
+    Equal:                  13,  0x07002E, 0xBB, 0x000D, 0x18, "Equal";
+    Num9:                   10,  0x070026, 0x39, 0x000A, 0x19, "Digit9";
+    Num7:                   8,   0x070024, 0x37, 0x0008, 0x1A, "Digit7";
+    Minus:                  12,  0x07002D, 0xBD, 0x000C, 0x1B, "Minus";
+    Num8:                   9,   0x070025, 0x38, 0x0009, 0x1C, "Digit8";
+    Num0:                   11,  0x070027, 0x30, 0x000B, 0x1D, "Digit0";
+    RightBracket:           27,  0x070030, 0xDD, 0x001B, 0x1E, "BracketRight";
+    O:                      24,  0x070012, 0x4F, 0x0018, 0x1F, "KeyO";
+    U:                      22,  0x070018, 0x55, 0x0016, 0x20, "KeyU";
+    LeftBracket:            26,  0x07002F, 0xDB, 0x001A, 0x21, "BracketLeft";
+    I:                      23,  0x07000C, 0x49, 0x0017, 0x22, "KeyI";
+    P:                      25,  0x070013, 0x50, 0x0019, 0x23, "KeyP";
+    L:                      38,  0x07000F, 0x4C, 0x0026, 0x25, "KeyL";
+    J:                      36,  0x07000D, 0x4A, 0x0024, 0x26, "KeyJ";
+    Quote:                  40,  0x070034, 0xDE, 0x0028, 0x27, "Quote";
+    K:                      37,  0x07000E, 0x4B, 0x0025, 0x28, "KeyK";
+    Semicolon:              39,  0x070033, 0xBA, 0x0027, 0x29, "Semicolon";
+    Backslash:              43,  0x070031, 0xDC, 0x002B, 0x2A, "Backslash";
+    Comma:                  51,  0x070036, 0xBC, 0x0033, 0x2B, "Comma";
+    Slash:                  53,  0x070038, 0xBF, 0x0035, 0x2C, "Slash";
+    N:                      49,  0x070011, 0x4E, 0x0031, 0x2D, "KeyN";
+    M:                      50,  0x070010, 0x4D, 0x0032, 0x2E, "KeyM";
+    Period:                 52,  0x070037, 0xBE, 0x0034, 0x2F, "Period";
+    Grave:                  41,  0x070035, 0xC0, 0x0029, 0x32, "Backquote";
+    KeypadDecimal:          83,  0x070063, 0x6E, 0x0053, 0x41, "NumpadDecimal";
+    KeypadMultiply:         55,  0x070055, 0x6A, 0x0037, 0x43, "NumpadMultiply";
+    KeypadPlus:             78,  0x070057, 0x6B, 0x004E, 0x45, "NumpadAdd";
+    KeypadClear:            _,   0x0700D8, _,    _,      _,    "NumpadClear";
+    KeypadDivide:           98,  0x070054, 0x6F, 0xE035, 0x4B, "NumpadDivide";
+    KeypadEnter:            96,  0x070058, _,    0xE01C, 0x4C, "NumpadEnter";
+    KeypadMinus:            74,  0x070056, 0x6D, 0x004A, 0x4E, "NumpadSubtract";
+    KeypadEquals:           117, 0x070067, _,    0x0059, 0x51, "NumpadEqual";
+    Keypad0:                82,  0x070062, 0x60, 0x0052, 0x52, "Numpad0";
+    Keypad1:                79,  0x070059, 0x61, 0x004F, 0x53, "Numpad1";
+    Keypad2:                80,  0x07005A, 0x62, 0x0050, 0x54, "Numpad2";
+    Keypad3:                81,  0x07005B, 0x63, 0x0051, 0x55, "Numpad3";
+    Keypad4:                75,  0x07005C, 0x64, 0x004B, 0x56, "Numpad4";
+    Keypad5:                76,  0x07005D, 0x65, 0x004C, 0x57, "Numpad5";
+    Keypad6:                77,  0x07005E, 0x66, 0x004D, 0x58, "Numpad6";
+    Keypad7:                71,  0x07005F, 0x67, 0x0047, 0x59, "Numpad7";
+    Keypad8:                72,  0x070060, 0x68, 0x0048, 0x5B, "Numpad8";
+    Keypad9:                73,  0x070061, 0x69, 0x0049, 0x5C, "Numpad9";
+    Return:                 28,  0x070028, 0x0D, 0x001C, 0x24, "Enter";
+    Tab:                    15,  0x07002B, 0x09, 0x000F, 0x30, "Tab";
+    Space:                  57,  0x07002C, 0x20, 0x0039, 0x31, "Space";
+    Delete:                 14,  0x07002A, 0x08, 0x000E, 0x33, "Backspace";
+    Escape:                 1,   0x070029, 0x1B, 0x0001, 0x35, "Escape";
+    Command:                125, 0x0700E3, 0x5B, 0xE05B, 0x37, "MetaLeft";
+    Shift:                  42,  0x0700E1, 0xA0, 0x002A, 0x38, "ShiftLeft";
+    CapsLock:               58,  0x070039, 0x14, 0x003A, 0x39, "CapsLock";
+    Option:                 56,  0x0700E2, 0xA4, 0x0038, 0x3A, "AltLeft";
+    Control:                29,  0x0700E0, 0xA2, 0x001D, 0x3B, "ControlLeft";
+    RightCommand:           126, 0x0700E7, 0x5C, 0xE05C, 0x36, "MetaRight";
+    RightShift:             54,  0x0700E5, 0xA1, 0x0036, 0x3C, "ShiftRight";
+    RightOption:            100, 0x0700E6, 0xA5, 0xE038, 0x3D, "AltRight";
+    RightControl:           97,  0x0700E4, 0xA3, 0xE01D, 0x3E, "ControlRight";
+    Function:               464, _,        _,    _,      0x3F, "Fn";
+    F17:                    187, 0x07006C, 0x80, 0x0068, 0x40, "F17";
+    VolumeUp:               115, 0x070080, 0xAF, 0xE030, 0x48, "AudioVolumeUp";
+    VolumeDown:             114, 0x070081, 0xAE, 0xE02E, 0x49, "AudioVolumeDown";
+    Mute:                   113, 0x07007F, 0xAD, 0xE020, 0x4A, "AudioVolumeMute";
+    F18:                    188, 0x07006D, 0x81, 0x0069, 0x4F, "F18";
+    F19:                    189, 0x07006E, 0x82, 0x006A, 0x50, "F19";
+    F20:                    190, 0x07006F, 0x83, 0x006B, 0x5A, "F20";
+    F5:                     63,  0x07003E, 0x74, 0x003F, 0x60, "F5";
+    F6:                     64,  0x07003F, 0x75, 0x0040, 0x61, "F6";
+    F7:                     65,  0x070040, 0x76, 0x0041, 0x62, "F7";
+    F3:                     61,  0x07003C, 0x72, 0x003D, 0x63, "F3";
+    F8:                     66,  0x070041, 0x77, 0x0042, 0x64, "F8";
+    F9:                     67,  0x070042, 0x78, 0x0043, 0x65, "F9";
+    F11:                    87,  0x070044, 0x7A, 0x0057, 0x67, "F11";
+    F13:                    183, 0x070068, 0x7C, 0x0064, 0x69, "F13";
+    F16:                    186, 0x07006B, 0x7F, 0x0067, 0x6A, "F16";
+    F14:                    184, 0x070069, 0x7D, 0x0065, 0x6B, "F14";
+    F10:                    68,  0x070043, 0x79, 0x0044, 0x6D, "F10";
+    ContextualMenu:         _,   _,        _,    _,      0x6E, _;
+    F12:                    88,  0x070045, 0x7B, 0x0058, 0x6F, "F12";
+    F15:                    185, 0x07006A, 0x7E, 0x0066, 0x71, "F15";
+    Help:                   138, 0x070075, 0x2F, _,      0x72, "Help";
+    Home:                   102, 0x07004A, 0x24, 0xE047, 0x73, "Home";
+    PageUp:                 104, 0x07004B, 0x21, 0xE049, 0x74, "PageUp";
+    ForwardDelete:          111, 0x07004C, 0x2E, 0xE053, 0x75, "Delete";
+    F4:                     62,  0x07003D, 0x73, 0x003E, 0x76, "F4";
+    End:                    107, 0x07004D, 0x23, 0xE04F, 0x77, "End";
+    F2:                     60,  0x07003B, 0x71, 0x003C, 0x78, "F2";
+    PageDown:               109, 0x07004E, 0x22, 0xE051, 0x79, "PageDown";
+    F1:                     59,  0x07003A, 0x70, 0x003B, 0x7A, "F1";
+    LeftArrow:              105, 0x070050, 0x25, 0xE04B, 0x7B, "ArrowLeft";
+    RightArrow:             106, 0x07004F, 0x27, 0xE04D, 0x7C, "ArrowRight";
+    DownArrow:              108, 0x070051, 0x28, 0xE050, 0x7D, "ArrowDown";
+    UpArrow:                103, 0x070052, 0x26, 0xE048, 0x7E, "ArrowUp";
+    ISOSection:             _,   _,        _,    _,      _,    _;
+    JISYen:                 124, 0x070089, _,    0x007D, 0x5D, "IntlYen";
+    JISUnderscore:          89,  0x070087, _,    0x0073, 0x5E, "IntlRo";
+    JISKeypadComma:         121, 0x070085, 0x6C, 0x007E, 0x5F, "NumpadComma";
+    JISEisu:                123, 0x070091, _,    0x0071, 0x66, "Lang2";
+    JISKana:                93,  0x070088, 0x15, 0x0070, 0x68, "KanaMode";
+    Pause:                  119, 0x070048, 0x13, 0x0045, _,    "Pause";
+    ScrollLock:             70,  0x070047, 0x91, 0x0046, _,    "ScrollLock";
+    PrintScreen:            99,  0x070046, 0x2C, 0xE037, _,    "PrintScreen";
+    InternationalBackslash: 86,  0x070064, 0xE2, 0x0056, 0x0A, "IntlBackslash";
+    F21:                    191, 0x070070, 0x84, 0x006C, _,    "F21";
+    F22:                    192, 0x070071, 0x85, 0x006D, _,    "F22";
+    F23:                    193, 0x070072, 0x86, 0x006E, _,    "F23";
+    F24:                    194, 0x070073, 0x87, 0x0076, _,    "F24";
+    Convert:                92,  0x07008A, 0x1C, 0x0079, _,    "Convert";
+    NonConvert:             94,  0x07008B, 0x1D, 0x007B, _,    "NonConvert";
+    PreviousTrack:          165, 0x0C00B6, 0xB1, 0xE010, _,    "MediaTrackPrevious";
+    NextTrack:              163, 0x0C00B5, 0xB0, 0xE019, _,    "MediaTrackNext";
+    LaunchApp2:             149, 0x0C0192, 0xB7, 0xE021, _,    "LaunchApp2";
+    Play:                   164, 0x0C00CD, 0xB3, 0xE022, _,    "MediaPlayPause";
+    Stop:                   166, 0x0C00B7, 0xB2, 0xE024, _,    "MediaStop";
+    BrowserHome:            172, 0x0C0223, 0xAC, 0xE032, _,    "BrowserHome";
+    NumLock:                69,  0x070053, 0x90, 0xE045, 0x47, "NumLock";
+    Insert:                 110, 0x070049, 0x2D, 0xE052, _,    "Insert";
+    ContextMenu:            127, 0x070065, 0x5D, 0xE05D, _,    "ContextMenu";
+    Power:                  116, 0x070066, _,    0xE05E, _,    "Power";
+    Eject:                  161, 0x0C00B8, _,    _,      _,    "Eject";
+    BrowserSearch:          217, 0x0C0221, 0xAA, 0xE065, _,    "BrowserSearch";
+    BrowserFavorites:       156, 0x0C022A, 0xAB, 0xE066, _,    "BrowserFavorites";
+    BrowserRefresh:         173, 0x0C0227, 0xA8, 0xE067, _,    "BrowserRefresh";
+    BrowserStop:            128, 0x0C0226, 0xA9, 0xE068, _,    "BrowserStop";
+    BrowserForward:         159, 0x0C0225, 0xA7, 0xE069, _,    "BrowserForward";
+    BrowserBack:            158, 0x0C0224, 0xA6, 0xE06A, _,    "BrowserBack";
+    LaunchApp1:             148, 0x0C0194, 0xB6, 0xE06B, _,    "LaunchApp1";
+    LaunchMail:             155, 0x0C018A, 0xB4, 0xE06C, _,    "LaunchMail";
+    MediaSelect:            226, 0x0C0183, 0xB5, 0xE06D, _,    "MediaSelect";
+    Again:                  129, 0x070079, _,    _,      _,    "Again";
+    Props:                  130, 0x0700A3, _,    _,      _,    "Props";
+    Undo:                   131, 0x07007A, _,    0xE008, _,    "Undo";
+    Select:                 353, 0x070077, 0x29, _,      _,    "Select";
+    Copy:                   133, 0x07007C, _,    0xE018, _,    "Copy";
+    Open:                   134, 0x070074, _,    _,      _,    "Open";
+    Paste:                  135, 0x07007D, _,    0xE00A, _,    "Paste";
+    Find:                   136, 0x07007E, _,    _,      _,    "Find";
+    Cut:                    137, 0x07007B, _,    0xE017, _,    "Cut";
+    WakeUp:                 143, 0x010083, _,    0xE063, _,    "WakeUp";
+}
+
+impl KeyboardKey {
+    fn codes(self) -> Option<&'static Codes> {
+        self.index().map(|index| &CODES[index])
+    }
+
+    fn find<F: Fn(&Codes) -> bool>(f: F) -> Option<KeyboardKey> {
+        CODES.iter().find(|codes| f(codes)).map(|codes| codes.key)
+    }
+
+    /// The raw code of an unknown key, if it is of the specified kind.
+    fn unknown_code(self, kind: ScancodeKind) -> Option<u32> {
+        match self {
+            KeyboardKey::Unknown(scancode) if scancode.kind() == kind => Some(scancode.code()),
+            _ => None,
+        }
+    }
+
+    /**
+    Returns the key with the specified Linux evdev code, as used by Wayland and libinput.
+
+    Returns `None` if no named key has the code; wrap it in [`KeyboardKey::Unknown`] to keep it.
+    */
+    pub fn from_evdev(code: u32) -> Option<KeyboardKey> {
+        KeyboardKey::find(|codes| codes.evdev.map(u32::from) == Some(code))
+    }
+
+    /**
+    Returns the Linux evdev code of this key.
+
+    [`KeyboardKey::Unknown`] keys return their code if it is an evdev code.
+    */
+    pub fn to_evdev(self) -> Option<u32> {
+        match self.codes() {
+            Some(codes) => codes.evdev.map(u32::from),
+            None => self.unknown_code(ScancodeKind::Evdev),
+        }
+    }
+
+    /**
+    Returns the key with the specified USB HID usage, encoded as `(usage page << 16) | usage id`.
+
+    Most keys are on the keyboard page, so `A` is `0x070004`; media and browser keys are on the
+    consumer page, `0x0C`.
+    */
+    pub fn from_usb_hid(usage: u32) -> Option<KeyboardKey> {
+        KeyboardKey::find(|codes| codes.usb_hid == Some(usage))
+    }
+
+    /**
+    Returns the USB HID usage of this key, encoded as in [`KeyboardKey::from_usb_hid`].
+    */
+    pub fn to_usb_hid(self) -> Option<u32> {
+        self.codes().and_then(|codes| codes.usb_hid)
+    }
+
+    /**
+    Returns the key with the specified Windows virtual-key code.
+
+    Left and right modifiers have distinct codes, such as `VK_LSHIFT`; the generic `VK_SHIFT`,
+    `VK_CONTROL` and `VK_MENU` return `None`.
+    */
+    pub fn from_windows_vk(vk: u16) -> Option<KeyboardKey> {
+        KeyboardKey::find(|codes| codes.windows_vk == Some(vk))
+    }
+
+    /**
+    Returns the Windows virtual-key code of this key.
+
+    [`KeyboardKey::Unknown`] keys return their code if it is a virtual-key code.
+    */
+    pub fn to_windows_vk(self) -> Option<u16> {
+        match self.codes() {
+            Some(codes) => codes.windows_vk,
+            None => self
+                .unknown_code(ScancodeKind::WindowsVirtualKey)
+                .and_then(|code| u16::try_from(code).ok()),
+        }
+    }
+
+    /**
+    Returns the key with the specified Windows scan code.
+
+    Codes are from scan code set 1.  Extended keys have `0xE0` in the high byte, so the right
+    control key is `0xE01D`.
+    */
+    pub fn from_windows_scancode(scancode: u16) -> Option<KeyboardKey> {
+        KeyboardKey::find(|codes| codes.windows_scancode == Some(scancode))
+    }
+
+    /**
+    Returns the Windows scan code of this key, encoded as in [`KeyboardKey::from_windows_scancode`].
+    */
+    pub fn to_windows_scancode(self) -> Option<u16> {
+        self.codes().and_then(|codes| codes.windows_scancode)
+    }
+
+    /**
+    Returns the key with the specified macOS virtual key code, as in `NSEvent.keyCode`.
+    */
+    pub fn from_macos_keycode(code: u16) -> Option<KeyboardKey> {
+        KeyboardKey::find(|codes| codes.macos_keycode == Some(code))
+    }
+
+    /**
+    Returns the macOS virtual key code of this key.
+
+    [`KeyboardKey::Unknown`] keys return their code if it is a macOS key code.
+    */
+    pub fn to_macos_keycode(self) -> Option<u16> {
+        match self.codes() {
+            Some(codes) => codes.macos_keycode,
+            None => self
+                .unknown_code(ScancodeKind::MacOSKeyCode)
+                .and_then(|code| u16::try_from(code).ok()),
+        }
+    }
+
+    /**
+    Returns the key with the specified W3C `code` value, such as `"KeyA"`.
+    */
+    pub fn from_web_code(code: &str) -> Option<KeyboardKey> {
+        KeyboardKey::find(|codes| codes.web_code == Some(code))
+    }
+
+    /**
+    Returns the W3C `code` value of this key.
+    */
+    pub fn to_web_code(self) -> Option<&'static str> {
+        self.codes().and_then(|codes| codes.web_code)
+    }
+
+    /**
+    Returns the key with the specified X11 keycode.
+
+    X11 keycodes are those of the xkb `evdev` keycode set used by Xorg and Xwayland, which are the
+    evdev codes offset by 8.
+    */
+    pub fn from_x11_keycode(code: u32) -> Option<KeyboardKey> {
+        code.checked_sub(X11_EVDEV_OFFSET)
+            .and_then(KeyboardKey::from_evdev)
+    }
+
+    /**
+    Returns the X11 keycode of this key.
+
+    [`KeyboardKey::Unknown`] keys return a keycode if they carry an evdev code.
+    */
+    pub fn to_x11_keycode(self) -> Option<u32> {
+        self.to_evdev().map(|code| code + X11_EVDEV_OFFSET)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CODES;
+    use crate::keyboard::key::{KeyboardKey, RawScancode, ScancodeKind};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_table_order() {
+        let keys: Vec<KeyboardKey> = CODES.iter().map(|codes| codes.key).collect();
+        assert_eq!(keys, KeyboardKey::all_keys());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for key in KeyboardKey::all_keys() {
+            if let Some(code) = key.to_evdev() {
+                assert_eq!(KeyboardKey::from_evdev(code), Some(key), "evdev {key:?}");
+            }
+            if let Some(code) = key.to_usb_hid() {
+                assert_eq!(KeyboardKey::from_usb_hid(code), Some(key), "usb {key:?}");
+            }
+            if let Some(code) = key.to_windows_vk() {
+                assert_eq!(KeyboardKey::from_windows_vk(code), Some(key), "vk {key:?}");
+            }
+            if let Some(code) = key.to_windows_scancode() {
+                assert_eq!(
+                    KeyboardKey::from_windows_scancode(code),
+                    Some(key),
+                    "scancode {key:?}"
+                );
+            }
+            if let Some(code) = key.to_macos_keycode() {
+                assert_eq!(
+                    KeyboardKey::from_macos_keycode(code),
+                    Some(key),
+                    "macOS {key:?}"
+                );
+            }
+            if let Some(code) = key.to_web_code() {
+                assert_eq!(KeyboardKey::from_web_code(code), Some(key), "web {key:?}");
+            }
+            if let Some(code) = key.to_x11_keycode() {
+                assert_eq!(
+                    KeyboardKey::from_x11_keycode(code),
+                    Some(key),
+                    "x11 {key:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_codes_unique() {
+        fn assert_unique<T: std::hash::Hash + Eq + std::fmt::Debug>(
+            name: &str,
+            codes: impl Iterator<Item = Option<T>>,
+        ) {
+            let mut seen = HashSet::new();
+            for code in codes.flatten() {
+                assert!(seen.insert(code), "{name} code is used twice");
+            }
+        }
+        assert_unique("evdev", CODES.iter().map(|c| c.evdev));
+        assert_unique("usb", CODES.iter().map(|c| c.usb_hid));
+        assert_unique("vk", CODES.iter().map(|c| c.windows_vk));
+        assert_unique("scancode", CODES.iter().map(|c| c.windows_scancode));
+        assert_unique("macOS", CODES.iter().map(|c| c.macos_keycode));
+        assert_unique("web", CODES.iter().map(|c| c.web_code));
+    }
+
+    #[test]
+    fn test_known_codes() {
+        assert_eq!(KeyboardKey::A.to_evdev(), Some(30));
+        assert_eq!(KeyboardKey::A.to_x11_keycode(), Some(38));
+        assert_eq!(KeyboardKey::A.to_usb_hid(), Some(0x070004));
+        assert_eq!(KeyboardKey::A.to_windows_vk(), Some(0x41));
+        assert_eq!(KeyboardKey::A.to_windows_scancode(), Some(0x1E));
+        assert_eq!(KeyboardKey::A.to_macos_keycode(), Some(0x00));
+        assert_eq!(KeyboardKey::A.to_web_code(), Some("KeyA"));
+        assert_eq!(
+            KeyboardKey::from_web_code("Delete"),
+            Some(KeyboardKey::ForwardDelete)
+        );
+        assert_eq!(KeyboardKey::from_evdev(0x2ff), None);
+        assert_eq!(KeyboardKey::from_x11_keycode(3), None);
+
+        let unknown = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x290));
+        assert_eq!(unknown.to_evdev(), Some(0x290));
+        assert_eq!(unknown.to_x11_keycode(), Some(0x298));
+        assert_eq!(unknown.to_macos_keycode(), None);
+    }
+}
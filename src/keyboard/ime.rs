@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MPL-2.0
+
+/**
+An input method (IME) event, as returned from [`Keyboard::drain_ime_events`](crate::keyboard::Keyboard::drain_ime_events).
+
+Input methods let users compose text that can't be typed directly, such as Chinese, Japanese or
+Korean.  While the user is composing, the input method shows *preedit* text, which the application
+should draw inline at the cursor (usually underlined).  When the user accepts a candidate, the
+input method *commits* the final text.
+
+Events that arrive together should be applied in order.  A commit is always followed by a
+[`ImeEvent::Preedit`] describing what remains of the composition, which is often empty.
+
+# Example
+
+```
+use app_input::keyboard::Keyboard;
+use app_input::keyboard::ime::ImeEvent;
+
+let keyboard = Keyboard::coalesced();
+keyboard.set_ime_allowed(true);
+
+let mut text = String::new();
+let mut preedit = String::new();
+for event in keyboard.drain_ime_events() {
+    match event {
+        ImeEvent::Preedit { text, .. } => preedit = text,
+        ImeEvent::Commit(committed) => text.push_str(&committed),
+        ImeEvent::DeleteSurrounding { before_length, .. } => {
+            let keep = text.len().saturating_sub(before_length);
+            while text.len() > keep {
+                text.pop();
+            }
+        }
+    }
+}
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImeEvent {
+    /// The in-progress composition changed.
+    ///
+    /// An empty `text` means there is no composition and any previous preedit should be removed.
+    Preedit {
+        /// The text being composed.
+        text: String,
+        /// The byte range of the cursor within `text`, or `None` if the cursor should be hidden.
+        ///
+        /// If the start and end are equal, the cursor is a caret rather than a selection.
+        cursor: Option<(usize, usize)>,
+    },
+    /// The input method committed text, which should be inserted at the cursor.
+    ///
+    /// Text committed here is not also reported by
+    /// [`Keyboard::drain_text_input`](crate::keyboard::Keyboard::drain_text_input).
+    Commit(String),
+    /// The input method asked to delete text around the cursor, before applying the commit that follows.
+    DeleteSurrounding {
+        /// The number of bytes to delete before the cursor.
+        before_length: usize,
+        /// The number of bytes to delete after the cursor.
+        after_length: usize,
+    },
+}
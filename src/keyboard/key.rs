@@ -1,9 +1,15 @@
 // SPDX-License-Identifier: MPL-2.0
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 #[repr(usize)]
 #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 /**
 A key on the keyboard.  Maps to an OS scancode.
+
+Keys that the library has no name for are reported as [`KeyboardKey::Unknown`] rather than
+dropped, so they can still be bound by their platform code.
 */
 pub enum KeyboardKey {
     A,
@@ -166,11 +172,74 @@ pub enum KeyboardKey {
     Find,
     Cut,
     WakeUp,
+    /// A key with no named variant, identified by its platform code.
+    ///
+    /// A key reported this way may gain a named variant in a future version.
+    Unknown(RawScancode),
+}
+
+/**
+The kind of platform code carried by a [`RawScancode`].
+*/
+#[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ScancodeKind {
+    /// A Linux evdev code, as sent by Wayland in `wl_keyboard::Event::Key`.
+    Evdev,
+    /// A Windows virtual-key code, as sent in `WM_KEYDOWN`.
+    WindowsVirtualKey,
+    /// A macOS virtual key code, as returned by `NSEvent.keyCode`.
+    MacOSKeyCode,
+    /// The legacy numeric `keyCode` of a browser `KeyboardEvent`.
+    WebKeyCode,
+}
+
+/**
+A platform key code with no corresponding named [`KeyboardKey`].
+
+Codes are only meaningful on the platform that produced them; compare the [`ScancodeKind`] before
+interpreting the code.
+
+# Example
+
+```
+use app_input::keyboard::key::{KeyboardKey, RawScancode, ScancodeKind};
+
+//a binding for an extra key on a gaming keyboard
+let macro_key = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x290));
+assert_eq!(macro_key, KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x290)));
+```
+*/
+#[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawScancode {
+    kind: ScancodeKind,
+    code: u32,
+}
+
+impl RawScancode {
+    /// Creates a scancode of the specified kind.
+    pub const fn new(kind: ScancodeKind, code: u32) -> Self {
+        RawScancode { kind, code }
+    }
+
+    /// The platform the code came from.
+    pub const fn kind(self) -> ScancodeKind {
+        self.kind
+    }
+
+    /// The platform code.
+    pub const fn code(self) -> u32 {
+        self.code
+    }
 }
 
 impl KeyboardKey {
     /**
         Returns all keys supported by the library.
+
+        [`KeyboardKey::Unknown`] is not included.
     */
     pub fn all_keys() -> Vec<KeyboardKey> {
         vec![
@@ -336,4 +405,373 @@ impl KeyboardKey {
             KeyboardKey::WakeUp,
         ]
     }
+
+    /// Returns the position of a named key in [`KeyboardKey::all_keys`], or `None` for
+    /// [`KeyboardKey::Unknown`].
+    pub(crate) fn index(self) -> Option<usize> {
+        match self {
+            KeyboardKey::Unknown(_) => None,
+            //SAFETY: a primitive representation puts the usize discriminant first, and named
+            //variants are declared in the same order as all_keys
+            _ => Some(unsafe { *(&self as *const KeyboardKey as *const usize) }),
+        }
+    }
+
+    /**
+    Returns the name of the variant, or `None` for [`KeyboardKey::Unknown`].
+
+    This is the string that [`Display`] writes and [`FromStr`] reads back.
+
+    ```
+    use app_input::keyboard::key::KeyboardKey;
+
+    assert_eq!(KeyboardKey::KeypadEnter.name(), Some("KeypadEnter"));
+    ```
+    */
+    pub const fn name(self) -> Option<&'static str> {
+        Some(match self {
+            KeyboardKey::A => "A",
+            KeyboardKey::S => "S",
+            KeyboardKey::D => "D",
+            KeyboardKey::F => "F",
+            KeyboardKey::H => "H",
+            KeyboardKey::G => "G",
+            KeyboardKey::Z => "Z",
+            KeyboardKey::X => "X",
+            KeyboardKey::C => "C",
+            KeyboardKey::V => "V",
+            KeyboardKey::B => "B",
+            KeyboardKey::Q => "Q",
+            KeyboardKey::W => "W",
+            KeyboardKey::E => "E",
+            KeyboardKey::R => "R",
+            KeyboardKey::Y => "Y",
+            KeyboardKey::T => "T",
+            KeyboardKey::Num1 => "Num1",
+            KeyboardKey::Num2 => "Num2",
+            KeyboardKey::Num3 => "Num3",
+            KeyboardKey::Num4 => "Num4",
+            KeyboardKey::Num6 => "Num6",
+            KeyboardKey::Num5 => "Num5",
+            KeyboardKey::Equal => "Equal",
+            KeyboardKey::Num9 => "Num9",
+            KeyboardKey::Num7 => "Num7",
+            KeyboardKey::Minus => "Minus",
+            KeyboardKey::Num8 => "Num8",
+            KeyboardKey::Num0 => "Num0",
+            KeyboardKey::RightBracket => "RightBracket",
+            KeyboardKey::O => "O",
+            KeyboardKey::U => "U",
+            KeyboardKey::LeftBracket => "LeftBracket",
+            KeyboardKey::I => "I",
+            KeyboardKey::P => "P",
+            KeyboardKey::L => "L",
+            KeyboardKey::J => "J",
+            KeyboardKey::Quote => "Quote",
+            KeyboardKey::K => "K",
+            KeyboardKey::Semicolon => "Semicolon",
+            KeyboardKey::Backslash => "Backslash",
+            KeyboardKey::Comma => "Comma",
+            KeyboardKey::Slash => "Slash",
+            KeyboardKey::N => "N",
+            KeyboardKey::M => "M",
+            KeyboardKey::Period => "Period",
+            KeyboardKey::Grave => "Grave",
+            KeyboardKey::KeypadDecimal => "KeypadDecimal",
+            KeyboardKey::KeypadMultiply => "KeypadMultiply",
+            KeyboardKey::KeypadPlus => "KeypadPlus",
+            KeyboardKey::KeypadClear => "KeypadClear",
+            KeyboardKey::KeypadDivide => "KeypadDivide",
+            KeyboardKey::KeypadEnter => "KeypadEnter",
+            KeyboardKey::KeypadMinus => "KeypadMinus",
+            KeyboardKey::KeypadEquals => "KeypadEquals",
+            KeyboardKey::Keypad0 => "Keypad0",
+            KeyboardKey::Keypad1 => "Keypad1",
+            KeyboardKey::Keypad2 => "Keypad2",
+            KeyboardKey::Keypad3 => "Keypad3",
+            KeyboardKey::Keypad4 => "Keypad4",
+            KeyboardKey::Keypad5 => "Keypad5",
+            KeyboardKey::Keypad6 => "Keypad6",
+            KeyboardKey::Keypad7 => "Keypad7",
+            KeyboardKey::Keypad8 => "Keypad8",
+            KeyboardKey::Keypad9 => "Keypad9",
+            KeyboardKey::Return => "Return",
+            KeyboardKey::Tab => "Tab",
+            KeyboardKey::Space => "Space",
+            KeyboardKey::Delete => "Delete",
+            KeyboardKey::Escape => "Escape",
+            KeyboardKey::Command => "Command",
+            KeyboardKey::Shift => "Shift",
+            KeyboardKey::CapsLock => "CapsLock",
+            KeyboardKey::Option => "Option",
+            KeyboardKey::Control => "Control",
+            KeyboardKey::RightCommand => "RightCommand",
+            KeyboardKey::RightShift => "RightShift",
+            KeyboardKey::RightOption => "RightOption",
+            KeyboardKey::RightControl => "RightControl",
+            KeyboardKey::Function => "Function",
+            KeyboardKey::F17 => "F17",
+            KeyboardKey::VolumeUp => "VolumeUp",
+            KeyboardKey::VolumeDown => "VolumeDown",
+            KeyboardKey::Mute => "Mute",
+            KeyboardKey::F18 => "F18",
+            KeyboardKey::F19 => "F19",
+            KeyboardKey::F20 => "F20",
+            KeyboardKey::F5 => "F5",
+            KeyboardKey::F6 => "F6",
+            KeyboardKey::F7 => "F7",
+            KeyboardKey::F3 => "F3",
+            KeyboardKey::F8 => "F8",
+            KeyboardKey::F9 => "F9",
+            KeyboardKey::F11 => "F11",
+            KeyboardKey::F13 => "F13",
+            KeyboardKey::F16 => "F16",
+            KeyboardKey::F14 => "F14",
+            KeyboardKey::F10 => "F10",
+            KeyboardKey::ContextualMenu => "ContextualMenu",
+            KeyboardKey::F12 => "F12",
+            KeyboardKey::F15 => "F15",
+            KeyboardKey::Help => "Help",
+            KeyboardKey::Home => "Home",
+            KeyboardKey::PageUp => "PageUp",
+            KeyboardKey::ForwardDelete => "ForwardDelete",
+            KeyboardKey::F4 => "F4",
+            KeyboardKey::End => "End",
+            KeyboardKey::F2 => "F2",
+            KeyboardKey::PageDown => "PageDown",
+            KeyboardKey::F1 => "F1",
+            KeyboardKey::LeftArrow => "LeftArrow",
+            KeyboardKey::RightArrow => "RightArrow",
+            KeyboardKey::DownArrow => "DownArrow",
+            KeyboardKey::UpArrow => "UpArrow",
+            KeyboardKey::ISOSection => "ISOSection",
+            KeyboardKey::JISYen => "JISYen",
+            KeyboardKey::JISUnderscore => "JISUnderscore",
+            KeyboardKey::JISKeypadComma => "JISKeypadComma",
+            KeyboardKey::JISEisu => "JISEisu",
+            KeyboardKey::JISKana => "JISKana",
+            KeyboardKey::Pause => "Pause",
+            KeyboardKey::ScrollLock => "ScrollLock",
+            KeyboardKey::PrintScreen => "PrintScreen",
+            KeyboardKey::InternationalBackslash => "InternationalBackslash",
+            KeyboardKey::F21 => "F21",
+            KeyboardKey::F22 => "F22",
+            KeyboardKey::F23 => "F23",
+            KeyboardKey::F24 => "F24",
+            KeyboardKey::Convert => "Convert",
+            KeyboardKey::NonConvert => "NonConvert",
+            KeyboardKey::PreviousTrack => "PreviousTrack",
+            KeyboardKey::NextTrack => "NextTrack",
+            KeyboardKey::LaunchApp2 => "LaunchApp2",
+            KeyboardKey::Play => "Play",
+            KeyboardKey::Stop => "Stop",
+            KeyboardKey::BrowserHome => "BrowserHome",
+            KeyboardKey::NumLock => "NumLock",
+            KeyboardKey::Insert => "Insert",
+            KeyboardKey::ContextMenu => "ContextMenu",
+            KeyboardKey::Power => "Power",
+            KeyboardKey::Eject => "Eject",
+            KeyboardKey::BrowserSearch => "BrowserSearch",
+            KeyboardKey::BrowserFavorites => "BrowserFavorites",
+            KeyboardKey::BrowserRefresh => "BrowserRefresh",
+            KeyboardKey::BrowserStop => "BrowserStop",
+            KeyboardKey::BrowserForward => "BrowserForward",
+            KeyboardKey::BrowserBack => "BrowserBack",
+            KeyboardKey::LaunchApp1 => "LaunchApp1",
+            KeyboardKey::LaunchMail => "LaunchMail",
+            KeyboardKey::MediaSelect => "MediaSelect",
+            KeyboardKey::Again => "Again",
+            KeyboardKey::Props => "Props",
+            KeyboardKey::Undo => "Undo",
+            KeyboardKey::Select => "Select",
+            KeyboardKey::Copy => "Copy",
+            KeyboardKey::Open => "Open",
+            KeyboardKey::Paste => "Paste",
+            KeyboardKey::Find => "Find",
+            KeyboardKey::Cut => "Cut",
+            KeyboardKey::WakeUp => "WakeUp",
+            KeyboardKey::Unknown(_) => return None,
+        })
+    }
+}
+
+/// Common spellings of keys that differ from the variant name, matched case-insensitively.
+const ALIASES: [(&str, KeyboardKey); 43] = [
+    ("0", KeyboardKey::Num0),
+    ("1", KeyboardKey::Num1),
+    ("2", KeyboardKey::Num2),
+    ("3", KeyboardKey::Num3),
+    ("4", KeyboardKey::Num4),
+    ("5", KeyboardKey::Num5),
+    ("6", KeyboardKey::Num6),
+    ("7", KeyboardKey::Num7),
+    ("8", KeyboardKey::Num8),
+    ("9", KeyboardKey::Num9),
+    ("=", KeyboardKey::Equal),
+    ("-", KeyboardKey::Minus),
+    ("[", KeyboardKey::LeftBracket),
+    ("]", KeyboardKey::RightBracket),
+    ("'", KeyboardKey::Quote),
+    (";", KeyboardKey::Semicolon),
+    ("\\", KeyboardKey::Backslash),
+    (",", KeyboardKey::Comma),
+    ("/", KeyboardKey::Slash),
+    (".", KeyboardKey::Period),
+    ("`", KeyboardKey::Grave),
+    ("Enter", KeyboardKey::Return),
+    ("Esc", KeyboardKey::Escape),
+    ("Backspace", KeyboardKey::Delete),
+    ("Del", KeyboardKey::ForwardDelete),
+    ("Ins", KeyboardKey::Insert),
+    ("Left", KeyboardKey::LeftArrow),
+    ("Right", KeyboardKey::RightArrow),
+    ("Up", KeyboardKey::UpArrow),
+    ("Down", KeyboardKey::DownArrow),
+    ("PgUp", KeyboardKey::PageUp),
+    ("PgDn", KeyboardKey::PageDown),
+    ("Ctrl", KeyboardKey::Control),
+    ("Alt", KeyboardKey::Option),
+    ("Cmd", KeyboardKey::Command),
+    ("Super", KeyboardKey::Command),
+    ("Meta", KeyboardKey::Command),
+    ("Win", KeyboardKey::Command),
+    ("Fn", KeyboardKey::Function),
+    ("↩", KeyboardKey::Return),
+    ("⇥", KeyboardKey::Tab),
+    ("⌫", KeyboardKey::Delete),
+    ("⌦", KeyboardKey::ForwardDelete),
+];
+
+/// Glyphs used by macOS menus, in addition to [`ALIASES`].
+const GLYPHS: [(&str, KeyboardKey); 10] = [
+    ("⎋", KeyboardKey::Escape),
+    ("←", KeyboardKey::LeftArrow),
+    ("→", KeyboardKey::RightArrow),
+    ("↑", KeyboardKey::UpArrow),
+    ("↓", KeyboardKey::DownArrow),
+    ("↖", KeyboardKey::Home),
+    ("↘", KeyboardKey::End),
+    ("⇞", KeyboardKey::PageUp),
+    ("⇟", KeyboardKey::PageDown),
+    ("⌤", KeyboardKey::KeypadEnter),
+];
+
+/**
+Formats the variant name, e.g. `"KeypadEnter"`.
+
+[`KeyboardKey::Unknown`] formats as `Unknown(<kind>:<code>)`, e.g. `"Unknown(Evdev:656)"`.
+
+For the label a user would expect to see in a menu, use
+[`KeyChord::format`](crate::keyboard::chord::KeyChord::format).
+*/
+impl Display for KeyboardKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyboardKey::Unknown(raw) => write!(f, "Unknown({:?}:{})", raw.kind, raw.code),
+            named => f.write_str(named.name().unwrap()),
+        }
+    }
+}
+
+/// Serializes as a string in the [`Display`] form, so the encoding doesn't depend on the order of
+/// the variants, even in formats that aren't self-describing.
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyboardKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserializes a string as parsed by [`FromStr`], so aliases such as `"Esc"` are accepted.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyboardKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/**
+Parses a key from its variant name or a common alias, ignoring case.
+
+Everything [`Display`] writes parses back to the same key.  In addition, digits and punctuation
+parse as the key that types them (`"1"`, `"/"`), and common names such as `"Enter"`, `"Esc"`,
+`"Ctrl"`, `"Alt"`, `"Cmd"`, `"Super"` and `"Meta"` are accepted, as are the glyphs macOS uses in
+menus (`"↩"`, `"⌫"`, `"←"`).
+
+`"Backspace"` parses as [`KeyboardKey::Delete`] and `"Del"` as [`KeyboardKey::ForwardDelete`],
+following the macOS naming the variants use.
+
+```
+use app_input::keyboard::key::KeyboardKey;
+
+assert_eq!("keypadenter".parse(), Ok(KeyboardKey::KeypadEnter));
+assert_eq!("Esc".parse(), Ok(KeyboardKey::Escape));
+assert_eq!("1".parse(), Ok(KeyboardKey::Num1));
+assert!("Hyper".parse::<KeyboardKey>().is_err());
+```
+*/
+impl FromStr for KeyboardKey {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Some(key) = KeyboardKey::all_keys()
+            .into_iter()
+            .find(|key| key.name().unwrap().eq_ignore_ascii_case(trimmed))
+        {
+            return Ok(key);
+        }
+        if let Some((_, key)) = ALIASES
+            .iter()
+            .chain(GLYPHS.iter())
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(trimmed))
+        {
+            return Ok(*key);
+        }
+        parse_unknown(trimmed).ok_or_else(|| ParseKeyError::new(s))
+    }
+}
+
+fn parse_unknown(s: &str) -> Option<KeyboardKey> {
+    let inner = s.strip_prefix("Unknown(")?.strip_suffix(')')?;
+    let (kind, code) = inner.split_once(':')?;
+    let kind = match kind {
+        "Evdev" => ScancodeKind::Evdev,
+        "WindowsVirtualKey" => ScancodeKind::WindowsVirtualKey,
+        "MacOSKeyCode" => ScancodeKind::MacOSKeyCode,
+        "WebKeyCode" => ScancodeKind::WebKeyCode,
+        _ => return None,
+    };
+    Some(KeyboardKey::Unknown(RawScancode::new(
+        kind,
+        code.parse().ok()?,
+    )))
+}
+
+/// The error returned when a string does not name a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError {
+    input: String,
+}
+
+impl ParseKeyError {
+    pub(crate) fn new(input: &str) -> Self {
+        ParseKeyError {
+            input: input.to_string(),
+        }
+    }
+
+    /// The text that could not be parsed.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
 }
+
+impl Display for ParseKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unrecognized key: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
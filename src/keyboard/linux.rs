@@ -1,12 +1,16 @@
 // SPDX-License-Identifier: MPL-2.0
 use crate::keyboard::Shared;
-use crate::keyboard::key::KeyboardKey;
+use crate::keyboard::key::{KeyboardKey, RawScancode, ScancodeKind};
+use crate::keyboard::modifiers::Modifiers;
 use crate::mouse::linux::motion_event;
-use crate::mouse::sys::{axis_event, button_event, xdg_toplevel_configure_event};
+use crate::mouse::sys::{
+    axis_event, button_event, pointer_enter_event, pointer_leave_event,
+    xdg_toplevel_configure_event,
+};
 use memmap2::MmapMut;
 use std::ffi::c_void;
 use std::fs::File;
-use std::os::fd::AsFd;
+use std::os::fd::{AsFd, OwnedFd};
 use std::sync::{Arc, Mutex, OnceLock, Weak};
 use wayland_client::backend::ObjectId;
 use wayland_client::globals::{GlobalListContents, registry_queue_init};
@@ -20,18 +24,40 @@ use wayland_client::protocol::wl_shm_pool::WlShmPool;
 use wayland_client::protocol::wl_surface::WlSurface;
 use wayland_client::protocol::{wl_compositor, wl_registry, wl_shm};
 use wayland_client::{Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 use wayland_protocols::xdg::shell::client::xdg_surface::XdgSurface;
 use wayland_protocols::xdg::shell::client::xdg_toplevel;
 use wayland_protocols::xdg::shell::client::xdg_toplevel::XdgToplevel;
 use wayland_protocols::xdg::shell::client::xdg_wm_base::{Event, XdgWmBase};
 
 pub(crate) mod ax;
+pub(crate) mod ime;
+mod repeat;
+pub(crate) mod time;
+mod xkb;
+
+pub(crate) use ime::{set_ime_allowed, set_ime_cursor_area};
 
 #[derive(Default)]
 struct KeyboardState {
     shareds: Vec<Weak<Shared>>,
 }
 impl KeyboardState {
+    /// Returns the keyboards that are still alive, so they can be delivered to without the lock.
+    fn live(&mut self) -> Vec<Arc<Shared>> {
+        let mut live = Vec::with_capacity(self.shareds.len());
+        self.shareds.retain(|shared| {
+            if let Some(shared) = shared.upgrade() {
+                live.push(shared);
+                true
+            } else {
+                false
+            }
+        });
+        live
+    }
+
     fn apply_all<F: Fn(&Shared)>(&mut self, f: F) {
         self.shareds.retain(|shared| {
             if let Some(shared) = shared.upgrade() {
@@ -44,6 +70,8 @@ impl KeyboardState {
     }
 }
 static KEYBOARD_STATE: OnceLock<Mutex<KeyboardState>> = OnceLock::new();
+/// The compositor's keymap, once we have received it.
+static KEYMAP: Mutex<Option<xkb::Keymap>> = Mutex::new(None);
 
 #[derive(Debug)]
 pub(super) struct PlatformCoalescedKeyboard {}
@@ -184,6 +212,35 @@ impl Dispatch<XdgSurface, ()> for AppData {
     }
 }
 
+impl Dispatch<ZwpTextInputManagerV3, ()> for AppData {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwpTextInputManagerV3,
+        event: <ZwpTextInputManagerV3 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        logwise::debuginternal_sync!(
+            "got ZwpTextInputManagerV3 event {event}",
+            event = logwise::privacy::LogIt(&event)
+        );
+    }
+}
+
+impl Dispatch<ZwpTextInputV3, ()> for AppData {
+    fn event(
+        _state: &mut Self,
+        proxy: &ZwpTextInputV3,
+        event: <ZwpTextInputV3 as Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        ime::zwp_text_input_v3_event(proxy, event);
+    }
+}
+
 impl Dispatch<WlSeat, ()> for AppData {
     fn event(
         _state: &mut Self,
@@ -263,6 +320,17 @@ impl Dispatch<WlPointer, ObjectId> for AppData {
             wayland_client::protocol::wl_pointer::Event::Axis { time, axis, value } => {
                 axis_event(time, axis.into(), value, window.clone());
             }
+            wayland_client::protocol::wl_pointer::Event::Enter {
+                serial,
+                surface,
+                surface_x,
+                surface_y,
+            } => {
+                pointer_enter_event(serial, surface.id(), surface_x, surface_y);
+            }
+            wayland_client::protocol::wl_pointer::Event::Leave { serial, surface } => {
+                pointer_leave_event(serial, surface.id());
+            }
             _ => println!("got WlPointer event {:?}", event),
         }
     }
@@ -271,20 +339,187 @@ impl Dispatch<WlPointer, ObjectId> for AppData {
 /**
 Call this from [WlKeyboard] dispatch for [wayland_client::protocol::wl_keyboard::Event::Key] event.
 */
-pub fn wl_keyboard_event(_serial: u32, _time: u32, key: u32, state: u32, surface_id: ObjectId) {
-    if let Some(key) = KeyboardKey::from_vk(key) {
-        let down = state == 1;
-        KEYBOARD_STATE
-            .get_or_init(Mutex::default)
-            .lock()
-            .unwrap()
-            .apply_all(|shared| {
-                shared.set_key_state(key, down, surface_id.protocol_id() as *mut c_void)
-            });
-        ax::ax_press(key, down);
+pub fn wl_keyboard_event(_serial: u32, time: u32, key: u32, state: u32, surface_id: ObjectId) {
+    let timestamp = time::keyboard_time(time);
+    let code = key;
+    let key = KeyboardKey::from_evdev_or_unknown(code);
+    let down = state == 1;
+    let window = surface_id.protocol_id() as usize;
+    //resolve before the compositor sends the modifiers that result from this key
+    let (resolved, repeats, text) = match KEYMAP.lock().unwrap().as_mut() {
+        Some(keymap) => {
+            let resolved = keymap.resolve(code);
+            let text = match &resolved {
+                Some(resolved) if down => keymap.type_key(resolved),
+                _ => String::new(),
+            };
+            (resolved, keymap.key_repeats(code), text)
+        }
+        None => (None, repeat::key_repeats(key), String::new()),
+    };
+    repeat::update(|repeater, now| repeater.key(key, code, down, repeats, window, now));
+    KEYBOARD_STATE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap()
+        .apply_all(|shared| {
+            let window_ptr = surface_id.protocol_id() as *mut c_void;
+            shared.set_key_state_at(key, down, window_ptr, timestamp);
+            shared.push_text(&text, window_ptr);
+        });
+    ax::ax_press(key, down, resolved);
+}
+
+/// Delivers a synthesized repeat from the repeat thread.
+fn deliver_repeat(key: KeyboardKey, code: u32, window_ptr: *mut c_void) {
+    //repeats type the key's text again, but do not take part in compose sequences
+    let text = KEYMAP
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|keymap| keymap.resolve(code))
+        .map(|resolved| resolved.text)
+        .unwrap_or_default();
+    let keyboards = KEYBOARD_STATE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap()
+        .live();
+    for shared in keyboards {
+        //the key may have been released since the repeat fell due
+        if shared.is_pressed(key) {
+            shared.repeat_key(key, window_ptr);
+            shared.push_text(&text, window_ptr);
+        }
+    }
+}
+
+/**
+Call this from [WlKeyboard] dispatch for [wayland_client::protocol::wl_keyboard::Event::RepeatInfo] event.
+
+Wayland compositors do not send repeated key events, so we synthesize them at `rate` repeats per
+second after the key has been held for `delay` milliseconds.  A `rate` of zero disables repeat.
+Until this is called, a rate of 25 and a delay of 600 are used.
+
+See [crate::keyboard::KeyEvent::repeat].
+*/
+pub fn wl_keyboard_repeat_info_event(rate: i32, delay: i32) {
+    repeat::update(|repeater, _| repeater.set_info(rate, delay));
+}
+
+/**
+Call this from [WlKeyboard] dispatch for [wayland_client::protocol::wl_keyboard::Event::Keymap] event.
+
+The keymap is compiled with libxkbcommon, which is loaded at runtime.  It is used to produce
+[crate::keyboard::Keyboard::drain_text_input], to honor the user's layout when reporting keys to
+assistive technologies, and to decide which keys repeat.
+If libxkbcommon is not available, or `format` is not `xkb_v1`, a US layout is assumed and no text
+input is reported.
+*/
+pub fn wl_keyboard_keymap_event(format: u32, fd: OwnedFd, size: u32) {
+    let keymap = if format == xkb::KEYMAP_FORMAT_XKB_V1 {
+        xkb::Keymap::from_fd(fd, size)
     } else {
-        println!("Unknown key {key}");
+        logwise::warn_sync!("Unsupported keymap format {format}", format = format);
+        None
+    };
+    if keymap.is_none() {
+        logwise::warn_sync!("No keymap available; assuming a US layout");
+    }
+    *KEYMAP.lock().unwrap() = keymap;
+}
+
+/**
+Call this from [WlKeyboard] dispatch for [wayland_client::protocol::wl_keyboard::Event::Enter] event.
+
+`keys` is the array of currently pressed keys from the event.  Key state is resynchronized
+from it, so keys pressed while another window had focus are reported as pressed.
+*/
+pub fn wl_keyboard_enter_event(_serial: u32, surface_id: ObjectId, keys: &[u8]) {
+    let window_ptr = surface_id.protocol_id() as *mut c_void;
+    //the array contains u32 evdev codes in native byte order
+    let pressed: Vec<KeyboardKey> = keys
+        .chunks_exact(4)
+        .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap()))
+        .map(KeyboardKey::from_evdev_or_unknown)
+        .collect();
+    KEYBOARD_STATE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap()
+        .apply_all(|shared| {
+            shared.set_focus(window_ptr);
+            shared.sync_pressed_keys(&pressed, window_ptr);
+        });
+}
+
+/**
+Call this from [WlKeyboard] dispatch for [wayland_client::protocol::wl_keyboard::Event::Leave] event.
+
+Every held key is released, since the compositor will not tell us about releases while another window has focus.
+*/
+pub fn wl_keyboard_leave_event(_serial: u32, surface_id: ObjectId) {
+    let window_ptr = surface_id.protocol_id() as *mut c_void;
+    repeat::update(|repeater, _| repeater.stop());
+    let released = std::cell::RefCell::new(Vec::new());
+    KEYBOARD_STATE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap()
+        .apply_all(|shared| {
+            let mut released = released.borrow_mut();
+            for key in shared.clear_focus(window_ptr) {
+                if !released.contains(&key) {
+                    released.push(key);
+                }
+            }
+        });
+    for key in released.into_inner() {
+        ax::ax_press(key, false, None);
+    }
+}
+
+/**
+Call this from [WlKeyboard] dispatch for [wayland_client::protocol::wl_keyboard::Event::Modifiers] event.
+
+The compositor's modifier state is authoritative, see [crate::keyboard::Keyboard::modifiers].
+Modifier masks are interpreted according to the conventional xkb assignment
+(Shift, Lock, Control, Mod1 = Alt, Mod2 = NumLock, Mod4 = Super).
+*/
+pub fn wl_keyboard_modifiers_event(
+    _serial: u32,
+    mods_depressed: u32,
+    mods_latched: u32,
+    mods_locked: u32,
+    group: u32,
+) {
+    if let Some(keymap) = KEYMAP.lock().unwrap().as_mut() {
+        keymap.update_mask(mods_depressed, mods_latched, mods_locked, group);
     }
+    let modifiers = xkb_mask_to_modifiers(mods_depressed | mods_latched).held()
+        | xkb_mask_to_modifiers(mods_locked).locks();
+    KEYBOARD_STATE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap()
+        .apply_all(|shared| shared.set_platform_modifiers(modifiers));
+}
+
+fn xkb_mask_to_modifiers(mask: u32) -> Modifiers {
+    const XKB_SHIFT: u32 = 1 << 0;
+    const XKB_LOCK: u32 = 1 << 1;
+    const XKB_CONTROL: u32 = 1 << 2;
+    const XKB_MOD1: u32 = 1 << 3;
+    const XKB_MOD2: u32 = 1 << 4;
+    const XKB_MOD4: u32 = 1 << 6;
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, mask & XKB_SHIFT != 0);
+    modifiers.set(Modifiers::CAPS_LOCK, mask & XKB_LOCK != 0);
+    modifiers.set(Modifiers::CONTROL, mask & XKB_CONTROL != 0);
+    modifiers.set(Modifiers::OPTION, mask & XKB_MOD1 != 0);
+    modifiers.set(Modifiers::NUM_LOCK, mask & XKB_MOD2 != 0);
+    modifiers.set(Modifiers::COMMAND, mask & XKB_MOD4 != 0);
+    modifiers
 }
 
 impl Dispatch<WlKeyboard, ObjectId> for AppData {
@@ -305,6 +540,37 @@ impl Dispatch<WlKeyboard, ObjectId> for AppData {
             } => {
                 wl_keyboard_event(serial, time, key, state.into(), data.clone());
             }
+            wayland_client::protocol::wl_keyboard::Event::Enter {
+                serial,
+                surface,
+                keys,
+            } => {
+                wl_keyboard_enter_event(serial, surface.id(), &keys);
+            }
+            wayland_client::protocol::wl_keyboard::Event::Leave { serial, surface } => {
+                wl_keyboard_leave_event(serial, surface.id());
+            }
+            wayland_client::protocol::wl_keyboard::Event::Keymap { format, fd, size } => {
+                wl_keyboard_keymap_event(format.into(), fd, size);
+            }
+            wayland_client::protocol::wl_keyboard::Event::RepeatInfo { rate, delay } => {
+                wl_keyboard_repeat_info_event(rate, delay);
+            }
+            wayland_client::protocol::wl_keyboard::Event::Modifiers {
+                serial,
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+            } => {
+                wl_keyboard_modifiers_event(
+                    serial,
+                    mods_depressed,
+                    mods_latched,
+                    mods_locked,
+                    group,
+                );
+            }
             _ => println!("got WlKeyboard event {:?}", event),
         }
     }
@@ -339,6 +605,11 @@ pub fn debug_window_show() {
     let seat: WlSeat = globals.bind(&qh, 8..=9, ()).expect("Can't bind seat");
     let _pointer = seat.get_pointer(&qh, surface.id());
     let _keyboard = seat.get_keyboard(&qh, surface.id());
+    //input methods are optional
+    let _text_input = globals
+        .bind::<ZwpTextInputManagerV3, _, _>(&qh, 1..=1, ())
+        .ok()
+        .map(|manager| manager.get_text_input(&seat, &qh, ()));
 
     println!("Window should be displayed. Running event loop...");
 
@@ -352,208 +623,45 @@ pub fn debug_window_hide() {
 }
 
 impl KeyboardKey {
-    fn from_vk(vk: u32) -> Option<Self> {
-        //taken from https://github.com/torvalds/linux/blob/master/include/uapi/linux/input-event-codes.h
+    /// Maps an evdev code, passing through codes with no named key as [`KeyboardKey::Unknown`].
+    fn from_evdev_or_unknown(code: u32) -> Self {
+        KeyboardKey::from_evdev(code)
+            .or_else(|| KeyboardKey::from_evdev_alias(code))
+            .unwrap_or(KeyboardKey::Unknown(RawScancode::new(
+                ScancodeKind::Evdev,
+                code,
+            )))
+    }
 
-        match vk {
-            1 => Some(KeyboardKey::Escape),
-            2 => Some(KeyboardKey::Num1),
-            3 => Some(KeyboardKey::Num2),
-            4 => Some(KeyboardKey::Num3),
-            5 => Some(KeyboardKey::Num4),
-            6 => Some(KeyboardKey::Num5),
-            7 => Some(KeyboardKey::Num6),
-            8 => Some(KeyboardKey::Num7),
-            9 => Some(KeyboardKey::Num8),
-            10 => Some(KeyboardKey::Num9),
-            11 => Some(KeyboardKey::Num0),
-            12 => Some(KeyboardKey::Minus),
-            13 => Some(KeyboardKey::Equal),
-            14 => Some(KeyboardKey::Delete),
-            15 => Some(KeyboardKey::Tab),
-            16 => Some(KeyboardKey::Q),
-            17 => Some(KeyboardKey::W),
-            18 => Some(KeyboardKey::E),
-            19 => Some(KeyboardKey::R),
-            20 => Some(KeyboardKey::T),
-            21 => Some(KeyboardKey::Y),
-            22 => Some(KeyboardKey::U),
-            23 => Some(KeyboardKey::I),
-            24 => Some(KeyboardKey::O),
-            25 => Some(KeyboardKey::P),
-            26 => Some(KeyboardKey::LeftBracket),
-            27 => Some(KeyboardKey::RightBracket),
-            28 => Some(KeyboardKey::Return),
-            29 => Some(KeyboardKey::Control),
-            30 => Some(KeyboardKey::A),
-            31 => Some(KeyboardKey::S),
-            32 => Some(KeyboardKey::D),
-            33 => Some(KeyboardKey::F),
-            34 => Some(KeyboardKey::G),
-            35 => Some(KeyboardKey::H),
-            36 => Some(KeyboardKey::J),
-            37 => Some(KeyboardKey::K),
-            38 => Some(KeyboardKey::L),
-            39 => Some(KeyboardKey::Semicolon),
-            40 => Some(KeyboardKey::Quote),
-            41 => Some(KeyboardKey::Grave),
-            42 => Some(KeyboardKey::Shift),
-            43 => Some(KeyboardKey::Backslash),
-            44 => Some(KeyboardKey::Z),
-            45 => Some(KeyboardKey::X),
-            46 => Some(KeyboardKey::C),
-            47 => Some(KeyboardKey::V),
-            48 => Some(KeyboardKey::B),
-            49 => Some(KeyboardKey::N),
-            50 => Some(KeyboardKey::M),
-            51 => Some(KeyboardKey::Comma),
-            52 => Some(KeyboardKey::Period),
-            53 => Some(KeyboardKey::Slash),
-            54 => Some(KeyboardKey::RightShift),
-            55 => Some(KeyboardKey::KeypadMultiply),
-            56 => Some(KeyboardKey::Option),
-            57 => Some(KeyboardKey::Space),
-            58 => Some(KeyboardKey::CapsLock),
-            59 => Some(KeyboardKey::F1),
-            60 => Some(KeyboardKey::F2),
-            61 => Some(KeyboardKey::F3),
-            62 => Some(KeyboardKey::F4),
-            63 => Some(KeyboardKey::F5),
-            64 => Some(KeyboardKey::F6),
-            65 => Some(KeyboardKey::F7),
-            66 => Some(KeyboardKey::F8),
-            67 => Some(KeyboardKey::F9),
-            68 => Some(KeyboardKey::F10),
-            69 => Some(KeyboardKey::NumLock),
-            70 => Some(KeyboardKey::ScrollLock),
-            71 => Some(KeyboardKey::Keypad7),
-            72 => Some(KeyboardKey::Keypad8),
-            73 => Some(KeyboardKey::Keypad9),
-            74 => Some(KeyboardKey::KeypadMinus),
-            75 => Some(KeyboardKey::Keypad4),
-            76 => Some(KeyboardKey::Keypad5),
-            77 => Some(KeyboardKey::Keypad6),
-            78 => Some(KeyboardKey::KeypadPlus),
-            79 => Some(KeyboardKey::Keypad1),
-            80 => Some(KeyboardKey::Keypad2),
-            81 => Some(KeyboardKey::Keypad3),
-            82 => Some(KeyboardKey::Keypad0),
-            83 => Some(KeyboardKey::KeypadDecimal),
-            //84 ??
-            //85 - KEY_ZENKAKUHANKAKU
-            //86 - KEY_102ND
-            87 => Some(KeyboardKey::F11),
-            88 => Some(KeyboardKey::F12),
-            89 => Some(KeyboardKey::JISUnderscore),
-            //KEY_KATAKANA
-            //KEY_HIRAGANA
-            //KEY_KATAKANAHIRAGANA
-            //KEY_MUHENKAN
-            95 => Some(KeyboardKey::JISKeypadComma),
-            96 => Some(KeyboardKey::KeypadEnter),
-            97 => Some(KeyboardKey::RightControl),
-            98 => Some(KeyboardKey::KeypadDivide),
-            //sysreq
-            100 => Some(KeyboardKey::RightOption),
-            //linefeed
-            102 => Some(KeyboardKey::Home),
-            103 => Some(KeyboardKey::UpArrow),
-            104 => Some(KeyboardKey::PageUp),
-            105 => Some(KeyboardKey::LeftArrow),
-            106 => Some(KeyboardKey::RightArrow),
-            107 => Some(KeyboardKey::End),
-            108 => Some(KeyboardKey::DownArrow),
-            109 => Some(KeyboardKey::PageDown),
-            110 => Some(KeyboardKey::Insert),
-            111 => Some(KeyboardKey::ForwardDelete),
-            //macro
-            113 => Some(KeyboardKey::Mute),
-            114 => Some(KeyboardKey::VolumeDown),
-            115 => Some(KeyboardKey::VolumeUp),
-            116 => Some(KeyboardKey::Power),
-            117 => Some(KeyboardKey::KeypadEquals),
-            119 => Some(KeyboardKey::Pause),
-            //scale
-            121 => Some(KeyboardKey::JISKeypadComma),
-            //key_hanguel, hanja,
-            124 => Some(KeyboardKey::JISYen),
-            125 => Some(KeyboardKey::Command),
-            126 => Some(KeyboardKey::RightCommand),
-            127 => Some(KeyboardKey::ContextMenu),
-            //compose
-            128 => Some(KeyboardKey::Stop),
-            129 => Some(KeyboardKey::Again),
-            130 => Some(KeyboardKey::Props),
-            131 => Some(KeyboardKey::Undo),
-            //front
-            133 => Some(KeyboardKey::Copy),
-            134 => Some(KeyboardKey::Open),
-            135 => Some(KeyboardKey::Paste),
-            136 => Some(KeyboardKey::Find),
-            137 => Some(KeyboardKey::Cut),
-            138 => Some(KeyboardKey::Help),
-            139 => Some(KeyboardKey::ContextMenu),
-            //calc
-            //setup
-            //sleep
-            //wakeup
-            //file, sendfile, deletefile
-            //xfer
-            148 => Some(KeyboardKey::LaunchApp1),
-            149 => Some(KeyboardKey::LaunchApp2),
-            150 => Some(KeyboardKey::BrowserHome),
-            //msdos
-            //coffee (lock)
-            //screenlock
-            //rotate display
-            //direction
-            //cycle windows
-            155 => Some(KeyboardKey::LaunchMail),
-            //bookmarks
-            //computer
-            158 => Some(KeyboardKey::BrowserBack),
-            159 => Some(KeyboardKey::BrowserForward),
-            //close cd
-            161 => Some(KeyboardKey::Eject),
-            //eject+close
-            163 => Some(KeyboardKey::NextTrack),
-            164 => Some(KeyboardKey::Play),
-            165 => Some(KeyboardKey::PreviousTrack),
-            166 => Some(KeyboardKey::Stop),
-            //record, rewind, phone, iso, config
-            172 => Some(KeyboardKey::BrowserHome),
-            173 => Some(KeyboardKey::BrowserRefresh),
-            //exit, move, edit, scrollup, scrolldown, kpleftparen, kprightparen,
-            //key new, redo
-            183 => Some(KeyboardKey::F13),
-            184 => Some(KeyboardKey::F14),
-            185 => Some(KeyboardKey::F15),
-            186 => Some(KeyboardKey::F16),
-            187 => Some(KeyboardKey::F17),
-            188 => Some(KeyboardKey::F18),
-            189 => Some(KeyboardKey::F19),
-            190 => Some(KeyboardKey::F20),
-            191 => Some(KeyboardKey::F21),
-            192 => Some(KeyboardKey::F22),
-            193 => Some(KeyboardKey::F23),
-            194 => Some(KeyboardKey::F24),
-
-            200 => Some(KeyboardKey::Play),
-            201 => Some(KeyboardKey::Pause),
-            //prog3, prog4
-            //all applications
-            //dashboard, suspend, close
-            207 => Some(KeyboardKey::Play),
-            //fastforward, bass boost
-            //print
-            //hp, camera, sound, question, email, chat,
-            217 => Some(KeyboardKey::BrowserSearch),
-            //connect, finance, sport, shop
-            //alterase
-            //cancel
-            //brightness up/down
-            226 => Some(KeyboardKey::MediaSelect),
+    /// Maps evdev codes that share a named key with another code.
+    fn from_evdev_alias(code: u32) -> Option<Self> {
+        //taken from https://github.com/torvalds/linux/blob/master/include/uapi/linux/input-event-codes.h
+        match code {
+            95 => Some(KeyboardKey::JISKeypadComma), //KEY_KPJPCOMMA
+            139 => Some(KeyboardKey::ContextMenu),   //KEY_MENU
+            150 => Some(KeyboardKey::BrowserHome),   //KEY_WWW
+            200 => Some(KeyboardKey::Play),          //KEY_PLAYCD
+            201 => Some(KeyboardKey::Pause),         //KEY_PAUSECD
+            207 => Some(KeyboardKey::Play),          //KEY_PLAY
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::keyboard::linux::xkb_mask_to_modifiers;
+    use crate::keyboard::modifiers::Modifiers;
+
+    #[test]
+    fn test_xkb_mask() {
+        assert_eq!(
+            xkb_mask_to_modifiers(0b101),
+            Modifiers::SHIFT | Modifiers::CONTROL
+        );
+        assert_eq!(
+            xkb_mask_to_modifiers(0b1001_0010),
+            Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK
+        );
+    }
+}
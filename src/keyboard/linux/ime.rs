@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+Input method support via the `text-input-unstable-v3` protocol.
+
+The application binds `zwp_text_input_manager_v3` and creates a text input for its seat, then
+forwards the events to us.  We keep the proxy so that we can enable the input method and report
+the cursor rectangle when the application asks.
+
+The protocol delivers changes in batches terminated by `done`.  Within a batch they are applied in
+a fixed order: delete surrounding text, then commit, then preedit.
+*/
+use crate::keyboard::ime::ImeEvent;
+use std::sync::Mutex;
+use wayland_client::Proxy;
+use wayland_client::backend::ObjectId;
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::{self, ZwpTextInputV3};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct CursorArea {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+#[derive(Debug, Default)]
+struct Pending {
+    delete: Option<(u32, u32)>,
+    commit: Option<String>,
+    preedit: Option<(String, i32, i32)>,
+}
+
+#[derive(Debug)]
+struct ImeState {
+    text_input: Option<ZwpTextInputV3>,
+    /// The surface that currently has text input focus.
+    entered: Option<ObjectId>,
+    allowed: bool,
+    cursor_area: Option<CursorArea>,
+    pending: Pending,
+}
+
+static IME_STATE: Mutex<ImeState> = Mutex::new(ImeState {
+    text_input: None,
+    entered: None,
+    allowed: false,
+    cursor_area: None,
+    pending: Pending {
+        delete: None,
+        commit: None,
+        preedit: None,
+    },
+});
+
+impl ImeState {
+    /// Sends our state to the compositor, if a surface has focus.
+    fn send(&self) {
+        let Some(text_input) = &self.text_input else {
+            return;
+        };
+        if self.entered.is_none() {
+            return;
+        }
+        if self.allowed {
+            text_input.enable();
+            if let Some(area) = self.cursor_area {
+                text_input.set_cursor_rectangle(area.x, area.y, area.width, area.height);
+            }
+        } else {
+            text_input.disable();
+        }
+        text_input.commit();
+    }
+}
+
+pub(crate) fn set_ime_allowed(allowed: bool) {
+    let mut state = IME_STATE.lock().unwrap();
+    if state.allowed != allowed {
+        state.allowed = allowed;
+        state.send();
+    }
+}
+
+pub(crate) fn set_ime_cursor_area(x: f64, y: f64, width: f64, height: f64) {
+    let mut state = IME_STATE.lock().unwrap();
+    let area = CursorArea {
+        x: x.round() as i32,
+        y: y.round() as i32,
+        width: width.round() as i32,
+        height: height.round() as i32,
+    };
+    if state.cursor_area != Some(area) {
+        state.cursor_area = Some(area);
+        if state.allowed {
+            state.send();
+        }
+    }
+}
+
+/// Converts a protocol cursor to a byte range, where negative values hide the cursor.
+fn cursor(begin: i32, end: i32) -> Option<(usize, usize)> {
+    if begin < 0 || end < 0 {
+        None
+    } else {
+        Some((begin as usize, end as usize))
+    }
+}
+
+/**
+Call this from [ZwpTextInputV3] dispatch for every event.
+
+Create the text input with `zwp_text_input_manager_v3::get_text_input` for the same seat as your
+keyboard.  Then use [crate::keyboard::Keyboard::set_ime_allowed] to turn the input method on while
+a text field has focus, and receive events with [crate::keyboard::Keyboard::drain_ime_events].
+*/
+pub fn zwp_text_input_v3_event(text_input: &ZwpTextInputV3, event: zwp_text_input_v3::Event) {
+    let mut state = IME_STATE.lock().unwrap();
+    if state.text_input.as_ref() != Some(text_input) {
+        state.text_input = Some(text_input.clone());
+    }
+    match event {
+        zwp_text_input_v3::Event::Enter { surface } => {
+            state.entered = Some(surface.id());
+            state.send();
+        }
+        zwp_text_input_v3::Event::Leave { surface } => {
+            if state.entered == Some(surface.id()) {
+                state.entered = None;
+            }
+            text_input.disable();
+            text_input.commit();
+        }
+        zwp_text_input_v3::Event::PreeditString {
+            text,
+            cursor_begin,
+            cursor_end,
+        } => {
+            state.pending.preedit = Some((text.unwrap_or_default(), cursor_begin, cursor_end));
+        }
+        zwp_text_input_v3::Event::CommitString { text } => {
+            state.pending.commit = Some(text.unwrap_or_default());
+        }
+        zwp_text_input_v3::Event::DeleteSurroundingText {
+            before_length,
+            after_length,
+        } => {
+            state.pending.delete = Some((before_length, after_length));
+        }
+        zwp_text_input_v3::Event::Done { serial: _ } => {
+            let pending = std::mem::take(&mut state.pending);
+            drop(state);
+            let mut events = Vec::new();
+            if let Some((before, after)) = pending.delete {
+                events.push(ImeEvent::DeleteSurrounding {
+                    before_length: before as usize,
+                    after_length: after as usize,
+                });
+            }
+            if let Some(commit) = pending.commit {
+                events.push(ImeEvent::Commit(commit));
+            }
+            //a batch without preedit_string clears the preedit
+            let (text, begin, end) = pending.preedit.unwrap_or((String::new(), -1, -1));
+            events.push(ImeEvent::Preedit {
+                text,
+                cursor: cursor(begin, end),
+            });
+            super::KEYBOARD_STATE
+                .get_or_init(Mutex::default)
+                .lock()
+                .unwrap()
+                .apply_all(|shared| {
+                    for event in &events {
+                        shared.push_ime_event(event.clone());
+                    }
+                });
+        }
+        _ => logwise::warn_sync!(
+            "Unknown text input event {event}",
+            event = logwise::privacy::LogIt(&event)
+        ),
+    }
+}
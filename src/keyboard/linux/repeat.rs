@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+Client-side key repeat.
+
+Wayland compositors only send the initial press and the final release of a key.  Clients are
+expected to synthesize repeats themselves, using the rate and delay from
+`wl_keyboard::Event::RepeatInfo`.
+
+`KeyRepeater` is the state machine that decides when repeats are due; a background thread
+sleeps until the next deadline and delivers them.  Repeats are delivered on that thread, so
+callbacks and stream wakers for them run there rather than on the dispatch thread.
+*/
+use crate::keyboard::key::KeyboardKey;
+use crate::keyboard::modifiers::Modifiers;
+use std::ffi::c_void;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Repeat rate used until the compositor tells us otherwise, in repeats per second.
+const DEFAULT_RATE: u32 = 25;
+/// Delay used until the compositor tells us otherwise.
+const DEFAULT_DELAY: Duration = Duration::from_millis(600);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Repeating {
+    key: KeyboardKey,
+    /// The evdev code of `key`.
+    code: u32,
+    /// Protocol id of the surface, as used for the window pointer.
+    window: usize,
+    next: Instant,
+}
+
+/// Decides when a held key should repeat.
+///
+/// Only the most recently pressed key repeats, matching the behavior of other toolkits.
+#[derive(Debug)]
+pub(crate) struct KeyRepeater {
+    /// Repeats per second, or 0 if repeat is disabled.
+    rate: u32,
+    delay: Duration,
+    repeating: Option<Repeating>,
+}
+
+impl KeyRepeater {
+    pub(crate) const fn new() -> Self {
+        KeyRepeater {
+            rate: DEFAULT_RATE,
+            delay: DEFAULT_DELAY,
+            repeating: None,
+        }
+    }
+
+    /// Applies the values from `wl_keyboard::Event::RepeatInfo`.
+    ///
+    /// A rate of zero disables repeat.
+    pub(crate) fn set_info(&mut self, rate: i32, delay: i32) {
+        self.rate = rate.max(0) as u32;
+        self.delay = Duration::from_millis(delay.max(0) as u64);
+        if self.rate == 0 {
+            self.repeating = None;
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(1) / self.rate
+    }
+
+    /// Records a press or release at `now`.
+    ///
+    /// `repeats` is whether the keymap says the key repeats, see [`key_repeats`] for a fallback.
+    pub(crate) fn key(
+        &mut self,
+        key: KeyboardKey,
+        code: u32,
+        down: bool,
+        repeats: bool,
+        window: usize,
+        now: Instant,
+    ) {
+        if down {
+            if self.rate != 0 && repeats {
+                self.repeating = Some(Repeating {
+                    key,
+                    code,
+                    window,
+                    next: now + self.delay,
+                });
+            }
+        } else if self.repeating.is_some_and(|r| r.key == key) {
+            self.repeating = None;
+        }
+    }
+
+    /// Stops repeating, for example because focus was lost.
+    pub(crate) fn stop(&mut self) {
+        self.repeating = None;
+    }
+
+    /// Returns the time at which the next repeat is due, if any key is repeating.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.repeating.map(|r| r.next)
+    }
+
+    /// Returns the key, its evdev code, and the window to repeat if a repeat is due at `now`,
+    /// and schedules the next one.
+    ///
+    /// Call repeatedly until it returns `None` to catch up on every repeat that is due.
+    pub(crate) fn poll(&mut self, now: Instant) -> Option<(KeyboardKey, u32, usize)> {
+        //a rate of zero has no interval
+        if self.rate == 0 {
+            return None;
+        }
+        let interval = self.interval();
+        let repeating = self.repeating.as_mut()?;
+        if repeating.next > now {
+            return None;
+        }
+        repeating.next += interval;
+        Some((repeating.key, repeating.code, repeating.window))
+    }
+}
+
+/// Whether a key repeats, for use when no keymap is available.
+///
+/// Modifiers and locks do not repeat.  Strictly this is a property of the keymap, but no common
+/// keymap repeats them.
+pub(crate) fn key_repeats(key: KeyboardKey) -> bool {
+    Modifiers::from_key(key).is_empty() && Modifiers::lock_from_key(key).is_empty()
+}
+
+struct RepeatThread {
+    repeater: Mutex<KeyRepeater>,
+    wake: Condvar,
+}
+
+static REPEAT_THREAD: OnceLock<RepeatThread> = OnceLock::new();
+
+fn repeat_thread() -> &'static RepeatThread {
+    REPEAT_THREAD.get_or_init(|| {
+        std::thread::Builder::new()
+            .name("app_input key repeat".to_string())
+            .spawn(run)
+            .expect("Can't spawn key repeat thread");
+        RepeatThread {
+            repeater: Mutex::new(KeyRepeater::new()),
+            wake: Condvar::new(),
+        }
+    })
+}
+
+fn run() {
+    let thread = repeat_thread();
+    loop {
+        let due: Vec<_> = {
+            let repeater = thread.repeater.lock().unwrap();
+            let mut repeater = match repeater.deadline() {
+                None => thread.wake.wait(repeater).unwrap(),
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    thread.wake.wait_timeout(repeater, timeout).unwrap().0
+                }
+            };
+            let now = Instant::now();
+            std::iter::from_fn(|| repeater.poll(now)).collect()
+        };
+        //deliver without the lock, so callbacks that wait on the dispatch thread can't deadlock
+        //with its next update
+        for (key, code, window) in due {
+            super::deliver_repeat(key, code, window as *mut c_void);
+        }
+    }
+}
+
+/// Updates the repeater with the specified change, and wakes the thread to reschedule.
+pub(crate) fn update<F: FnOnce(&mut KeyRepeater, Instant)>(f: F) {
+    let thread = repeat_thread();
+    f(&mut thread.repeater.lock().unwrap(), Instant::now());
+    thread.wake.notify_one();
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KeyRepeater, key_repeats};
+    use crate::keyboard::key::KeyboardKey;
+    use std::time::{Duration, Instant};
+
+    const WINDOW: usize = 1;
+
+    fn key(repeater: &mut KeyRepeater, key: KeyboardKey, code: u32, down: bool, now: Instant) {
+        repeater.key(key, code, down, key_repeats(key), WINDOW, now);
+    }
+
+    #[test]
+    fn test_repeat_schedule() {
+        let start = Instant::now();
+        let mut repeater = KeyRepeater::new();
+        repeater.set_info(10, 500);
+        key(&mut repeater, KeyboardKey::DownArrow, 108, true, start);
+        assert_eq!(repeater.poll(start), None);
+        assert_eq!(
+            repeater.deadline(),
+            Some(start + Duration::from_millis(500))
+        );
+
+        //two repeats are due 600ms after the press at 10 per second
+        let later = start + Duration::from_millis(600);
+        let due = Some((KeyboardKey::DownArrow, 108, WINDOW));
+        assert_eq!(repeater.poll(later), due);
+        assert_eq!(repeater.poll(later), due);
+        assert_eq!(repeater.poll(later), None);
+
+        key(&mut repeater, KeyboardKey::DownArrow, 108, false, later);
+        assert_eq!(repeater.deadline(), None);
+    }
+
+    #[test]
+    fn test_repeat_exclusions() {
+        let start = Instant::now();
+        let mut repeater = KeyRepeater::new();
+        key(&mut repeater, KeyboardKey::Shift, 42, true, start);
+        assert_eq!(repeater.deadline(), None);
+
+        //releasing a key other than the repeating one does not stop repeat
+        key(&mut repeater, KeyboardKey::A, 30, true, start);
+        key(&mut repeater, KeyboardKey::Shift, 42, false, start);
+        assert!(repeater.deadline().is_some());
+
+        repeater.set_info(0, 500);
+        assert_eq!(repeater.deadline(), None);
+        key(&mut repeater, KeyboardKey::A, 30, true, start);
+        assert_eq!(repeater.deadline(), None);
+    }
+
+    #[test]
+    fn test_repeat_disabled_poll() {
+        let start = Instant::now();
+        let mut repeater = KeyRepeater::new();
+        key(&mut repeater, KeyboardKey::A, 30, true, start);
+        repeater.set_info(0, 600);
+        //the repeat thread polls after every update
+        assert_eq!(repeater.poll(start + Duration::from_secs(1)), None);
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+Event timestamps.
+
+Wayland input events carry a `time` in milliseconds from an unspecified clock, which wraps every 49
+days.  Compositors that support `zwp_input_timestamps_v1` additionally send a nanosecond timestamp
+from the same clock just before each input event.  When the application forwards those, we use them
+for the next input event in place of the millisecond time.
+
+The two kinds of time are converted separately, since the millisecond time is truncated and so does
+not share an epoch with the full timestamp.
+*/
+use crate::timestamp::{PlatformClock, Timestamp};
+use std::sync::Mutex;
+use std::time::Duration;
+use wayland_protocols::wp::input_timestamps::zv1::client::zwp_input_timestamps_v1;
+
+#[derive(Debug)]
+struct Clocks {
+    millis: PlatformClock,
+    precise: PlatformClock,
+    pending_keyboard: Option<Duration>,
+    pending_pointer: Option<Duration>,
+}
+
+static CLOCKS: Mutex<Clocks> = Mutex::new(Clocks {
+    millis: PlatformClock::new(),
+    precise: PlatformClock::new(),
+    pending_keyboard: None,
+    pending_pointer: None,
+});
+
+fn convert(time: u32, pending: fn(&mut Clocks) -> &mut Option<Duration>) -> Timestamp {
+    let now = Timestamp::now();
+    let mut clocks = CLOCKS.lock().unwrap();
+    match pending(&mut clocks).take() {
+        Some(precise) => clocks.precise.convert(precise, now),
+        None => clocks.millis.convert_millis(time, now),
+    }
+}
+
+/// Converts the `time` of a `wl_keyboard` event.
+pub(crate) fn keyboard_time(time: u32) -> Timestamp {
+    convert(time, |clocks| &mut clocks.pending_keyboard)
+}
+
+/// Converts the `time` of a `wl_pointer` event.
+pub(crate) fn pointer_time(time: u32) -> Timestamp {
+    convert(time, |clocks| &mut clocks.pending_pointer)
+}
+
+fn event_duration(event: zwp_input_timestamps_v1::Event) -> Option<Duration> {
+    match event {
+        zwp_input_timestamps_v1::Event::Timestamp {
+            tv_sec_hi,
+            tv_sec_lo,
+            tv_nsec,
+        } => {
+            let secs = (tv_sec_hi as u64) << 32 | tv_sec_lo as u64;
+            Some(Duration::new(secs, tv_nsec))
+        }
+        _ => None,
+    }
+}
+
+/**
+Call this from [zwp_input_timestamps_v1::ZwpInputTimestampsV1] dispatch, for the timestamps object
+created with `get_keyboard_timestamps`.
+
+The timestamp is used for the next key event passed to [crate::linux::wl_keyboard_event], in place of
+its millisecond `time`.
+*/
+pub fn zwp_input_timestamps_v1_keyboard_event(event: zwp_input_timestamps_v1::Event) {
+    if let Some(duration) = event_duration(event) {
+        CLOCKS.lock().unwrap().pending_keyboard = Some(duration);
+    }
+}
+
+/**
+Call this from [zwp_input_timestamps_v1::ZwpInputTimestampsV1] dispatch, for the timestamps object
+created with `get_pointer_timestamps`.
+
+The timestamp is used for the next pointer event passed to [crate::linux::motion_event] or
+[crate::linux::button_event], in place of its millisecond `time`.
+*/
+pub fn zwp_input_timestamps_v1_pointer_event(event: zwp_input_timestamps_v1::Event) {
+    if let Some(duration) = event_duration(event) {
+        CLOCKS.lock().unwrap().pending_pointer = Some(duration);
+    }
+}
@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+Keymap handling via libxkbcommon.
+
+The compositor sends its keymap as a file descriptor in `wl_keyboard::Event::Keymap`.  We compile it
+with libxkbcommon, which is loaded at runtime so that the crate still works (with a US-layout
+fallback) on systems without it.
+
+The xkb state is driven entirely by `wl_keyboard::Event::Modifiers`, as the protocol recommends,
+rather than by feeding it individual key presses.
+
+Dead keys and compose sequences are handled with the compose table for the user's locale.
+*/
+use std::ffi::{CStr, CString, c_char};
+use std::fs::File;
+use std::os::fd::OwnedFd;
+use std::ptr::NonNull;
+use xkbcommon_dl::{
+    XkbCommon, XkbCommonCompose, xkb_compose_compile_flags, xkb_compose_feed_result,
+    xkb_compose_state, xkb_compose_state_flags, xkb_compose_status, xkb_compose_table, xkb_context,
+    xkb_context_flags, xkb_keymap, xkb_keymap_compile_flags, xkb_keymap_format, xkb_state,
+};
+
+/// `wl_keyboard::KeymapFormat::XkbV1`
+pub(crate) const KEYMAP_FORMAT_XKB_V1: u32 = 1;
+
+/// Offset between evdev codes, as sent by Wayland, and xkb keycodes.
+const EVDEV_OFFSET: u32 = 8;
+
+/// The result of looking up a key in the keymap.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ResolvedKey {
+    /// The X11 keysym, as defined in `xkbcommon-keysyms.h`.
+    pub(crate) keysym: u32,
+    /// The text the key produces in the current state, which may be empty.
+    pub(crate) text: String,
+    /// The name of the keysym, such as `"a"` or `"Shift_L"`.
+    pub(crate) name: String,
+}
+
+/// A compiled keymap and the xkb state that goes with it.
+pub(crate) struct Keymap {
+    xkb: &'static XkbCommon,
+    context: NonNull<xkb_context>,
+    keymap: NonNull<xkb_keymap>,
+    state: NonNull<xkb_state>,
+    compose: Option<Compose>,
+}
+
+/// The compose table for the user's locale, and the progress of the current sequence.
+struct Compose {
+    xkb: &'static XkbCommonCompose,
+    table: NonNull<xkb_compose_table>,
+    state: NonNull<xkb_compose_state>,
+}
+
+impl Compose {
+    fn new(context: NonNull<xkb_context>) -> Option<Compose> {
+        let xkb = xkbcommon_dl::xkbcommon_compose_option()?;
+        //same precedence as setlocale(LC_CTYPE, "")
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_else(|| "C".to_string());
+        let locale = CString::new(locale).ok()?;
+        unsafe {
+            let table = NonNull::new((xkb.xkb_compose_table_new_from_locale)(
+                context.as_ptr(),
+                locale.as_ptr(),
+                xkb_compose_compile_flags::XKB_COMPOSE_COMPILE_NO_FLAGS,
+            ))?;
+            let Some(state) = NonNull::new((xkb.xkb_compose_state_new)(
+                table.as_ptr(),
+                xkb_compose_state_flags::XKB_COMPOSE_STATE_NO_FLAGS,
+            )) else {
+                (xkb.xkb_compose_table_unref)(table.as_ptr());
+                return None;
+            };
+            Some(Compose { xkb, table, state })
+        }
+    }
+
+    /// Feeds a keysym into the current sequence.
+    ///
+    /// Returns `None` if the keysym is not part of a sequence, or `Some` with the text the sequence
+    /// committed (which is empty while the sequence is in progress or was cancelled).
+    fn feed(&mut self, keysym: u32) -> Option<String> {
+        unsafe {
+            let result = (self.xkb.xkb_compose_state_feed)(self.state.as_ptr(), keysym);
+            if result == xkb_compose_feed_result::XKB_COMPOSE_FEED_IGNORED {
+                return None;
+            }
+            match (self.xkb.xkb_compose_state_get_status)(self.state.as_ptr()) {
+                xkb_compose_status::XKB_COMPOSE_NOTHING => None,
+                xkb_compose_status::XKB_COMPOSE_COMPOSING => Some(String::new()),
+                xkb_compose_status::XKB_COMPOSE_COMPOSED => {
+                    let text = read_string(|buf, len| {
+                        (self.xkb.xkb_compose_state_get_utf8)(self.state.as_ptr(), buf, len)
+                    });
+                    (self.xkb.xkb_compose_state_reset)(self.state.as_ptr());
+                    Some(text)
+                }
+                xkb_compose_status::XKB_COMPOSE_CANCELLED => {
+                    (self.xkb.xkb_compose_state_reset)(self.state.as_ptr());
+                    Some(String::new())
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Compose {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xkb.xkb_compose_state_unref)(self.state.as_ptr());
+            (self.xkb.xkb_compose_table_unref)(self.table.as_ptr());
+        }
+    }
+}
+
+//the xkb objects are only accessed through &mut self or while otherwise synchronized
+unsafe impl Send for Keymap {}
+
+impl Keymap {
+    /// Compiles a keymap from its text form.
+    ///
+    /// Returns `None` if libxkbcommon is unavailable or the keymap does not compile.
+    pub(crate) fn new(text: &[u8]) -> Option<Keymap> {
+        let xkb = xkbcommon_dl::xkbcommon_option()?;
+        //the buffer is NUL-terminated and may be padded; xkb wants only the text
+        let len = text.iter().position(|b| *b == 0).unwrap_or(text.len());
+        unsafe {
+            let context = NonNull::new((xkb.xkb_context_new)(
+                xkb_context_flags::XKB_CONTEXT_NO_FLAGS,
+            ))?;
+            let Some(keymap) = NonNull::new((xkb.xkb_keymap_new_from_buffer)(
+                context.as_ptr(),
+                text.as_ptr() as *const c_char,
+                len,
+                xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            )) else {
+                (xkb.xkb_context_unref)(context.as_ptr());
+                return None;
+            };
+            let Some(state) = NonNull::new((xkb.xkb_state_new)(keymap.as_ptr())) else {
+                (xkb.xkb_keymap_unref)(keymap.as_ptr());
+                (xkb.xkb_context_unref)(context.as_ptr());
+                return None;
+            };
+            Some(Keymap {
+                xkb,
+                context,
+                keymap,
+                state,
+                compose: Compose::new(context),
+            })
+        }
+    }
+
+    /// Compiles the keymap sent in `wl_keyboard::Event::Keymap`.
+    pub(crate) fn from_fd(fd: OwnedFd, size: u32) -> Option<Keymap> {
+        let file = File::from(fd);
+        //since wl_keyboard version 7 the fd must be mapped privately
+        let map = unsafe {
+            memmap2::MmapOptions::new()
+                .len(size as usize)
+                .map_copy_read_only(&file)
+        };
+        match map {
+            Ok(map) => Keymap::new(&map),
+            Err(e) => {
+                logwise::warn_sync!("Can't map keymap: {e}", e = logwise::privacy::LogIt(e));
+                None
+            }
+        }
+    }
+
+    /// Applies the values from `wl_keyboard::Event::Modifiers`.
+    pub(crate) fn update_mask(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        unsafe {
+            (self.xkb.xkb_state_update_mask)(
+                self.state.as_ptr(),
+                depressed,
+                latched,
+                locked,
+                0,
+                0,
+                group,
+            );
+        }
+    }
+
+    /// Looks up an evdev code in the current state.
+    ///
+    /// Returns `None` if the key produces no keysym.
+    pub(crate) fn resolve(&mut self, evdev: u32) -> Option<ResolvedKey> {
+        let keycode = evdev + EVDEV_OFFSET;
+        unsafe {
+            let keysym = (self.xkb.xkb_state_key_get_one_sym)(self.state.as_ptr(), keycode);
+            if keysym == 0 {
+                return None;
+            }
+            let text = read_string(|buf, len| {
+                (self.xkb.xkb_state_key_get_utf8)(self.state.as_ptr(), keycode, buf, len)
+            });
+            let name = read_string(|buf, len| (self.xkb.xkb_keysym_get_name)(keysym, buf, len));
+            Some(ResolvedKey { keysym, text, name })
+        }
+    }
+
+    /// Returns the text typed by pressing a key, after applying dead keys and compose sequences.
+    ///
+    /// Call this exactly once per press, with the result of [`Keymap::resolve`].
+    pub(crate) fn type_key(&mut self, resolved: &ResolvedKey) -> String {
+        match self.compose.as_mut().and_then(|c| c.feed(resolved.keysym)) {
+            Some(composed) => composed,
+            None => resolved.text.clone(),
+        }
+    }
+
+    /// Returns `true` if the keymap says the key should auto-repeat.
+    pub(crate) fn key_repeats(&self, evdev: u32) -> bool {
+        unsafe {
+            (self.xkb.xkb_keymap_key_repeats)(self.keymap.as_ptr(), evdev + EVDEV_OFFSET) != 0
+        }
+    }
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        //release the compose objects before the context they were created from
+        self.compose = None;
+        unsafe {
+            (self.xkb.xkb_state_unref)(self.state.as_ptr());
+            (self.xkb.xkb_keymap_unref)(self.keymap.as_ptr());
+            (self.xkb.xkb_context_unref)(self.context.as_ptr());
+        }
+    }
+}
+
+/// Calls an xkb function that writes a NUL-terminated string, in the snprintf style.
+fn read_string<F: Fn(*mut c_char, usize) -> i32>(f: F) -> String {
+    let mut buf = [0 as c_char; 64];
+    let len = f(buf.as_mut_ptr(), buf.len());
+    if len <= 0 {
+        return String::new();
+    }
+    if len as usize >= buf.len() {
+        let mut big = vec![0 as c_char; len as usize + 1];
+        f(big.as_mut_ptr(), big.len());
+        return unsafe { CStr::from_ptr(big.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+    }
+    unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Keymap;
+
+    //an AZERTY-style fragment: the key in the position of QWERTY 'A' types 'q'
+    const KEYMAP: &str = r#"xkb_keymap {
+        xkb_keycodes "test" {
+            minimum = 8;
+            maximum = 255;
+            <AC01> = 38;
+            <LFSH> = 50;
+        };
+        xkb_types "test" {
+            type "ONE_LEVEL" {
+                modifiers = none;
+                level_name[Level1] = "Any";
+            };
+            type "ALPHABETIC" {
+                modifiers = Shift+Lock;
+                map[Shift] = Level2;
+                map[Lock] = Level2;
+                level_name[Level1] = "Base";
+                level_name[Level2] = "Caps";
+            };
+        };
+        xkb_compat "test" {
+        };
+        xkb_symbols "test" {
+            key <AC01> { [ q, Q ] };
+            key <LFSH> { [ Shift_L ] };
+            modifier_map Shift { <LFSH> };
+        };
+    };
+    "#;
+
+    #[test]
+    fn test_resolve() {
+        if xkbcommon_dl::xkbcommon_option().is_none() {
+            //libxkbcommon is loaded at runtime and this system doesn't have it
+            return;
+        }
+        let mut keymap = Keymap::new(KEYMAP.as_bytes()).expect("keymap should compile");
+        const KEY_A: u32 = 30;
+        let resolved = keymap.resolve(KEY_A).unwrap();
+        assert_eq!(resolved.keysym, 0x71);
+        assert_eq!(resolved.text, "q");
+        assert_eq!(resolved.name, "q");
+
+        keymap.update_mask(1, 0, 0, 0);
+        assert_eq!(keymap.resolve(KEY_A).unwrap().text, "Q");
+        assert!(keymap.key_repeats(KEY_A));
+
+        const KEY_LEFTSHIFT: u32 = 42;
+        let shift = keymap.resolve(KEY_LEFTSHIFT).unwrap();
+        assert_eq!(shift.name, "Shift_L");
+        assert_eq!(shift.text, "");
+    }
+}
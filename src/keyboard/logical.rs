@@ -0,0 +1,640 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::keyboard::key::KeyboardKey;
+use crate::keyboard::modifiers::Modifiers;
+use std::fmt::{Display, Formatter};
+
+/**
+The meaning of a key, as opposed to its position on the keyboard.
+
+Variants are modeled on the [W3C UI Events `key` values](https://www.w3.org/TR/uievents-key/).
+Keys that type a character resolve to [`LogicalKey::Character`]; everything else resolves to a
+named key.  [`Display`] formats the W3C string, so `LogicalKey::ArrowUp` displays as `"ArrowUp"` and
+`LogicalKey::Character('a')` as `"a"`.
+
+Obtain one with [`KeyboardKey::logical`].
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum LogicalKey {
+    /// The key types this character.  The space bar is `Character(' ')`, as in the W3C spec.
+    Character(char),
+    /// The key has no known meaning.
+    Unidentified,
+
+    Alt,
+    AltGraph,
+    CapsLock,
+    Control,
+    Fn,
+    Meta,
+    NumLock,
+    ScrollLock,
+    Shift,
+
+    Enter,
+    Tab,
+
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    End,
+    Home,
+    PageDown,
+    PageUp,
+
+    Backspace,
+    Clear,
+    Copy,
+    Cut,
+    Delete,
+    Insert,
+    Paste,
+    Redo,
+    Undo,
+
+    Again,
+    ContextMenu,
+    Escape,
+    Find,
+    Help,
+    Pause,
+    Props,
+    Select,
+    Open,
+
+    Eject,
+    Power,
+    PrintScreen,
+    WakeUp,
+
+    Convert,
+    NonConvert,
+    Eisu,
+    KanaMode,
+
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+
+    MediaPlayPause,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+    AudioVolumeDown,
+    AudioVolumeUp,
+    AudioVolumeMute,
+
+    LaunchApplication1,
+    LaunchApplication2,
+    LaunchMail,
+    LaunchMediaPlayer,
+
+    BrowserBack,
+    BrowserFavorites,
+    BrowserForward,
+    BrowserHome,
+    BrowserRefresh,
+    BrowserSearch,
+    BrowserStop,
+}
+
+impl LogicalKey {
+    /**
+    Returns the W3C name of a named key, or `None` for [`LogicalKey::Character`].
+    */
+    pub fn name(self) -> Option<&'static str> {
+        Some(match self {
+            LogicalKey::Character(_) => return None,
+            LogicalKey::Unidentified => "Unidentified",
+            LogicalKey::Alt => "Alt",
+            LogicalKey::AltGraph => "AltGraph",
+            LogicalKey::CapsLock => "CapsLock",
+            LogicalKey::Control => "Control",
+            LogicalKey::Fn => "Fn",
+            LogicalKey::Meta => "Meta",
+            LogicalKey::NumLock => "NumLock",
+            LogicalKey::ScrollLock => "ScrollLock",
+            LogicalKey::Shift => "Shift",
+            LogicalKey::Enter => "Enter",
+            LogicalKey::Tab => "Tab",
+            LogicalKey::ArrowDown => "ArrowDown",
+            LogicalKey::ArrowLeft => "ArrowLeft",
+            LogicalKey::ArrowRight => "ArrowRight",
+            LogicalKey::ArrowUp => "ArrowUp",
+            LogicalKey::End => "End",
+            LogicalKey::Home => "Home",
+            LogicalKey::PageDown => "PageDown",
+            LogicalKey::PageUp => "PageUp",
+            LogicalKey::Backspace => "Backspace",
+            LogicalKey::Clear => "Clear",
+            LogicalKey::Copy => "Copy",
+            LogicalKey::Cut => "Cut",
+            LogicalKey::Delete => "Delete",
+            LogicalKey::Insert => "Insert",
+            LogicalKey::Paste => "Paste",
+            LogicalKey::Redo => "Redo",
+            LogicalKey::Undo => "Undo",
+            LogicalKey::Again => "Again",
+            LogicalKey::ContextMenu => "ContextMenu",
+            LogicalKey::Escape => "Escape",
+            LogicalKey::Find => "Find",
+            LogicalKey::Help => "Help",
+            LogicalKey::Pause => "Pause",
+            LogicalKey::Props => "Props",
+            LogicalKey::Select => "Select",
+            LogicalKey::Open => "Open",
+            LogicalKey::Eject => "Eject",
+            LogicalKey::Power => "Power",
+            LogicalKey::PrintScreen => "PrintScreen",
+            LogicalKey::WakeUp => "WakeUp",
+            LogicalKey::Convert => "Convert",
+            LogicalKey::NonConvert => "NonConvert",
+            LogicalKey::Eisu => "Eisu",
+            LogicalKey::KanaMode => "KanaMode",
+            LogicalKey::F1 => "F1",
+            LogicalKey::F2 => "F2",
+            LogicalKey::F3 => "F3",
+            LogicalKey::F4 => "F4",
+            LogicalKey::F5 => "F5",
+            LogicalKey::F6 => "F6",
+            LogicalKey::F7 => "F7",
+            LogicalKey::F8 => "F8",
+            LogicalKey::F9 => "F9",
+            LogicalKey::F10 => "F10",
+            LogicalKey::F11 => "F11",
+            LogicalKey::F12 => "F12",
+            LogicalKey::F13 => "F13",
+            LogicalKey::F14 => "F14",
+            LogicalKey::F15 => "F15",
+            LogicalKey::F16 => "F16",
+            LogicalKey::F17 => "F17",
+            LogicalKey::F18 => "F18",
+            LogicalKey::F19 => "F19",
+            LogicalKey::F20 => "F20",
+            LogicalKey::F21 => "F21",
+            LogicalKey::F22 => "F22",
+            LogicalKey::F23 => "F23",
+            LogicalKey::F24 => "F24",
+            LogicalKey::MediaPlayPause => "MediaPlayPause",
+            LogicalKey::MediaStop => "MediaStop",
+            LogicalKey::MediaTrackNext => "MediaTrackNext",
+            LogicalKey::MediaTrackPrevious => "MediaTrackPrevious",
+            LogicalKey::AudioVolumeDown => "AudioVolumeDown",
+            LogicalKey::AudioVolumeUp => "AudioVolumeUp",
+            LogicalKey::AudioVolumeMute => "AudioVolumeMute",
+            LogicalKey::LaunchApplication1 => "LaunchApplication1",
+            LogicalKey::LaunchApplication2 => "LaunchApplication2",
+            LogicalKey::LaunchMail => "LaunchMail",
+            LogicalKey::LaunchMediaPlayer => "LaunchMediaPlayer",
+            LogicalKey::BrowserBack => "BrowserBack",
+            LogicalKey::BrowserFavorites => "BrowserFavorites",
+            LogicalKey::BrowserForward => "BrowserForward",
+            LogicalKey::BrowserHome => "BrowserHome",
+            LogicalKey::BrowserRefresh => "BrowserRefresh",
+            LogicalKey::BrowserSearch => "BrowserSearch",
+            LogicalKey::BrowserStop => "BrowserStop",
+        })
+    }
+}
+
+impl Display for LogicalKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogicalKey::Character(c) => write!(f, "{c}"),
+            other => f.write_str(other.name().unwrap()),
+        }
+    }
+}
+
+/**
+A keyboard layout, used to decide which character a key types.
+
+The library does not query the layout the OS is using; pass the one your application expects, or
+use [`Keyboard::drain_text_input`](crate::keyboard::Keyboard::drain_text_input) when you need the
+characters the user actually typed.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Layout {
+    /// The US QWERTY layout.
+    #[default]
+    UsQwerty,
+    /// The US Dvorak layout.
+    Dvorak,
+}
+
+impl KeyboardKey {
+    /**
+    Resolves the meaning of this key under the specified modifiers and layout.
+
+    * Character keys honor [`Modifiers::SHIFT`], and letters also honor [`Modifiers::CAPS_LOCK`].
+      Other modifiers don't change the character, as with the W3C `key` value for a control
+      shortcut.
+    * Keypad digits honor [`Modifiers::NUM_LOCK`]: with it off, [`KeyboardKey::Keypad7`] is
+      [`LogicalKey::Home`], [`KeyboardKey::Keypad8`] is [`LogicalKey::ArrowUp`], and so on.
+    * Left and right modifier keys resolve to the same key.
+
+    # Example
+
+    ```
+    use app_input::keyboard::key::KeyboardKey;
+    use app_input::keyboard::logical::{Layout, LogicalKey};
+    use app_input::keyboard::modifiers::Modifiers;
+
+    let home = KeyboardKey::Keypad7.logical(Modifiers::empty(), Layout::UsQwerty);
+    assert_eq!(home, KeyboardKey::Home.logical(Modifiers::empty(), Layout::UsQwerty));
+    assert_eq!(home, LogicalKey::Home);
+
+    let seven = KeyboardKey::Keypad7.logical(Modifiers::NUM_LOCK, Layout::UsQwerty);
+    assert_eq!(seven, LogicalKey::Character('7'));
+    ```
+    */
+    pub fn logical(self, modifiers: Modifiers, layout: Layout) -> LogicalKey {
+        if let Some(c) = base_char(self, layout) {
+            let shift = modifiers.contains(Modifiers::SHIFT);
+            let upper = if c.is_ascii_alphabetic() {
+                shift != modifiers.contains(Modifiers::CAPS_LOCK)
+            } else {
+                shift
+            };
+            return LogicalKey::Character(if upper { shifted(c) } else { c });
+        }
+        if let Some(c) = keypad_char(self) {
+            return LogicalKey::Character(c);
+        }
+        if modifiers.contains(Modifiers::NUM_LOCK) {
+            if let Some(c) = keypad_digit(self) {
+                return LogicalKey::Character(c);
+            }
+        }
+        match self {
+            KeyboardKey::Keypad0 => LogicalKey::Insert,
+            KeyboardKey::Keypad1 => LogicalKey::End,
+            KeyboardKey::Keypad2 => LogicalKey::ArrowDown,
+            KeyboardKey::Keypad3 => LogicalKey::PageDown,
+            KeyboardKey::Keypad4 => LogicalKey::ArrowLeft,
+            KeyboardKey::Keypad5 => LogicalKey::Clear,
+            KeyboardKey::Keypad6 => LogicalKey::ArrowRight,
+            KeyboardKey::Keypad7 => LogicalKey::Home,
+            KeyboardKey::Keypad8 => LogicalKey::ArrowUp,
+            KeyboardKey::Keypad9 => LogicalKey::PageUp,
+            KeyboardKey::KeypadDecimal => LogicalKey::Delete,
+            KeyboardKey::KeypadClear => LogicalKey::Clear,
+            KeyboardKey::KeypadEnter | KeyboardKey::Return => LogicalKey::Enter,
+            KeyboardKey::Tab => LogicalKey::Tab,
+            KeyboardKey::Delete => LogicalKey::Backspace,
+            KeyboardKey::ForwardDelete => LogicalKey::Delete,
+            KeyboardKey::Escape => LogicalKey::Escape,
+            KeyboardKey::Command | KeyboardKey::RightCommand => LogicalKey::Meta,
+            KeyboardKey::Shift | KeyboardKey::RightShift => LogicalKey::Shift,
+            KeyboardKey::Option | KeyboardKey::RightOption => LogicalKey::Alt,
+            KeyboardKey::Control | KeyboardKey::RightControl => LogicalKey::Control,
+            KeyboardKey::CapsLock => LogicalKey::CapsLock,
+            KeyboardKey::NumLock => LogicalKey::NumLock,
+            KeyboardKey::ScrollLock => LogicalKey::ScrollLock,
+            KeyboardKey::Function => LogicalKey::Fn,
+            KeyboardKey::F1 => LogicalKey::F1,
+            KeyboardKey::F2 => LogicalKey::F2,
+            KeyboardKey::F3 => LogicalKey::F3,
+            KeyboardKey::F4 => LogicalKey::F4,
+            KeyboardKey::F5 => LogicalKey::F5,
+            KeyboardKey::F6 => LogicalKey::F6,
+            KeyboardKey::F7 => LogicalKey::F7,
+            KeyboardKey::F8 => LogicalKey::F8,
+            KeyboardKey::F9 => LogicalKey::F9,
+            KeyboardKey::F10 => LogicalKey::F10,
+            KeyboardKey::F11 => LogicalKey::F11,
+            KeyboardKey::F12 => LogicalKey::F12,
+            KeyboardKey::F13 => LogicalKey::F13,
+            KeyboardKey::F14 => LogicalKey::F14,
+            KeyboardKey::F15 => LogicalKey::F15,
+            KeyboardKey::F16 => LogicalKey::F16,
+            KeyboardKey::F17 => LogicalKey::F17,
+            KeyboardKey::F18 => LogicalKey::F18,
+            KeyboardKey::F19 => LogicalKey::F19,
+            KeyboardKey::F20 => LogicalKey::F20,
+            KeyboardKey::F21 => LogicalKey::F21,
+            KeyboardKey::F22 => LogicalKey::F22,
+            KeyboardKey::F23 => LogicalKey::F23,
+            KeyboardKey::F24 => LogicalKey::F24,
+            KeyboardKey::VolumeUp => LogicalKey::AudioVolumeUp,
+            KeyboardKey::VolumeDown => LogicalKey::AudioVolumeDown,
+            KeyboardKey::Mute => LogicalKey::AudioVolumeMute,
+            KeyboardKey::ContextualMenu | KeyboardKey::ContextMenu => LogicalKey::ContextMenu,
+            KeyboardKey::Help => LogicalKey::Help,
+            KeyboardKey::Home => LogicalKey::Home,
+            KeyboardKey::End => LogicalKey::End,
+            KeyboardKey::PageUp => LogicalKey::PageUp,
+            KeyboardKey::PageDown => LogicalKey::PageDown,
+            KeyboardKey::LeftArrow => LogicalKey::ArrowLeft,
+            KeyboardKey::RightArrow => LogicalKey::ArrowRight,
+            KeyboardKey::DownArrow => LogicalKey::ArrowDown,
+            KeyboardKey::UpArrow => LogicalKey::ArrowUp,
+            KeyboardKey::Insert => LogicalKey::Insert,
+            KeyboardKey::JISEisu => LogicalKey::Eisu,
+            KeyboardKey::JISKana => LogicalKey::KanaMode,
+            KeyboardKey::Convert => LogicalKey::Convert,
+            KeyboardKey::NonConvert => LogicalKey::NonConvert,
+            KeyboardKey::Pause => LogicalKey::Pause,
+            KeyboardKey::PrintScreen => LogicalKey::PrintScreen,
+            KeyboardKey::PreviousTrack => LogicalKey::MediaTrackPrevious,
+            KeyboardKey::NextTrack => LogicalKey::MediaTrackNext,
+            KeyboardKey::Play => LogicalKey::MediaPlayPause,
+            KeyboardKey::Stop => LogicalKey::MediaStop,
+            KeyboardKey::LaunchApp1 => LogicalKey::LaunchApplication1,
+            KeyboardKey::LaunchApp2 => LogicalKey::LaunchApplication2,
+            KeyboardKey::LaunchMail => LogicalKey::LaunchMail,
+            KeyboardKey::MediaSelect => LogicalKey::LaunchMediaPlayer,
+            KeyboardKey::BrowserHome => LogicalKey::BrowserHome,
+            KeyboardKey::BrowserSearch => LogicalKey::BrowserSearch,
+            KeyboardKey::BrowserFavorites => LogicalKey::BrowserFavorites,
+            KeyboardKey::BrowserRefresh => LogicalKey::BrowserRefresh,
+            KeyboardKey::BrowserStop => LogicalKey::BrowserStop,
+            KeyboardKey::BrowserForward => LogicalKey::BrowserForward,
+            KeyboardKey::BrowserBack => LogicalKey::BrowserBack,
+            KeyboardKey::Power => LogicalKey::Power,
+            KeyboardKey::Eject => LogicalKey::Eject,
+            KeyboardKey::WakeUp => LogicalKey::WakeUp,
+            KeyboardKey::Again => LogicalKey::Again,
+            KeyboardKey::Props => LogicalKey::Props,
+            KeyboardKey::Undo => LogicalKey::Undo,
+            KeyboardKey::Select => LogicalKey::Select,
+            KeyboardKey::Copy => LogicalKey::Copy,
+            KeyboardKey::Open => LogicalKey::Open,
+            KeyboardKey::Paste => LogicalKey::Paste,
+            KeyboardKey::Find => LogicalKey::Find,
+            KeyboardKey::Cut => LogicalKey::Cut,
+            _ => LogicalKey::Unidentified,
+        }
+    }
+}
+
+/// The character a key in the main block types without modifiers.
+fn base_char(key: KeyboardKey, layout: Layout) -> Option<char> {
+    let qwerty = match key {
+        KeyboardKey::A => 'a',
+        KeyboardKey::B => 'b',
+        KeyboardKey::C => 'c',
+        KeyboardKey::D => 'd',
+        KeyboardKey::E => 'e',
+        KeyboardKey::F => 'f',
+        KeyboardKey::G => 'g',
+        KeyboardKey::H => 'h',
+        KeyboardKey::I => 'i',
+        KeyboardKey::J => 'j',
+        KeyboardKey::K => 'k',
+        KeyboardKey::L => 'l',
+        KeyboardKey::M => 'm',
+        KeyboardKey::N => 'n',
+        KeyboardKey::O => 'o',
+        KeyboardKey::P => 'p',
+        KeyboardKey::Q => 'q',
+        KeyboardKey::R => 'r',
+        KeyboardKey::S => 's',
+        KeyboardKey::T => 't',
+        KeyboardKey::U => 'u',
+        KeyboardKey::V => 'v',
+        KeyboardKey::W => 'w',
+        KeyboardKey::X => 'x',
+        KeyboardKey::Y => 'y',
+        KeyboardKey::Z => 'z',
+        KeyboardKey::Num0 => '0',
+        KeyboardKey::Num1 => '1',
+        KeyboardKey::Num2 => '2',
+        KeyboardKey::Num3 => '3',
+        KeyboardKey::Num4 => '4',
+        KeyboardKey::Num5 => '5',
+        KeyboardKey::Num6 => '6',
+        KeyboardKey::Num7 => '7',
+        KeyboardKey::Num8 => '8',
+        KeyboardKey::Num9 => '9',
+        KeyboardKey::Minus => '-',
+        KeyboardKey::Equal => '=',
+        KeyboardKey::LeftBracket => '[',
+        KeyboardKey::RightBracket => ']',
+        KeyboardKey::Backslash | KeyboardKey::InternationalBackslash => '\\',
+        KeyboardKey::Semicolon => ';',
+        KeyboardKey::Quote => '\'',
+        KeyboardKey::Comma => ',',
+        KeyboardKey::Period => '.',
+        KeyboardKey::Slash => '/',
+        KeyboardKey::Grave => '`',
+        KeyboardKey::Space => ' ',
+        KeyboardKey::ISOSection => '§',
+        KeyboardKey::JISYen => '¥',
+        KeyboardKey::JISUnderscore => '_',
+        _ => return None,
+    };
+    Some(match layout {
+        Layout::UsQwerty => qwerty,
+        Layout::Dvorak => dvorak(qwerty),
+    })
+}
+
+/// Maps the character a QWERTY key types to the one the same position types on Dvorak.
+fn dvorak(qwerty: char) -> char {
+    const QWERTY: &str = "qwertyuiop[]asdfghjkl;'zxcvbnm,./-=";
+    const DVORAK: &str = "',.pyfgcrl/=aoeuidhtns-;qjkxbmwvz[]";
+    match QWERTY.chars().position(|c| c == qwerty) {
+        Some(index) => DVORAK.chars().nth(index).unwrap(),
+        None => qwerty,
+    }
+}
+
+/// The character typed with shift held, on US layouts.
+fn shifted(c: char) -> char {
+    match c {
+        'a'..='z' => c.to_ascii_uppercase(),
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '-' => '_',
+        '=' => '+',
+        '[' => '{',
+        ']' => '}',
+        '\\' => '|',
+        ';' => ':',
+        '\'' => '"',
+        ',' => '<',
+        '.' => '>',
+        '/' => '?',
+        '`' => '~',
+        '§' => '±',
+        '¥' => '|',
+        other => other,
+    }
+}
+
+/// Keypad keys that type the same character regardless of NumLock.
+fn keypad_char(key: KeyboardKey) -> Option<char> {
+    match key {
+        KeyboardKey::KeypadMultiply => Some('*'),
+        KeyboardKey::KeypadPlus => Some('+'),
+        KeyboardKey::KeypadDivide => Some('/'),
+        KeyboardKey::KeypadMinus => Some('-'),
+        KeyboardKey::KeypadEquals => Some('='),
+        KeyboardKey::JISKeypadComma => Some(','),
+        _ => None,
+    }
+}
+
+/// Keypad keys that type a character only when NumLock is on.
+fn keypad_digit(key: KeyboardKey) -> Option<char> {
+    match key {
+        KeyboardKey::Keypad0 => Some('0'),
+        KeyboardKey::Keypad1 => Some('1'),
+        KeyboardKey::Keypad2 => Some('2'),
+        KeyboardKey::Keypad3 => Some('3'),
+        KeyboardKey::Keypad4 => Some('4'),
+        KeyboardKey::Keypad5 => Some('5'),
+        KeyboardKey::Keypad6 => Some('6'),
+        KeyboardKey::Keypad7 => Some('7'),
+        KeyboardKey::Keypad8 => Some('8'),
+        KeyboardKey::Keypad9 => Some('9'),
+        KeyboardKey::KeypadDecimal => Some('.'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Layout, LogicalKey};
+    use crate::keyboard::key::KeyboardKey;
+    use crate::keyboard::modifiers::Modifiers;
+
+    #[test]
+    fn test_keypad_navigation() {
+        let off = Modifiers::empty();
+        for (keypad, nav) in [
+            (KeyboardKey::Keypad7, KeyboardKey::Home),
+            (KeyboardKey::Keypad1, KeyboardKey::End),
+            (KeyboardKey::Keypad8, KeyboardKey::UpArrow),
+            (KeyboardKey::Keypad3, KeyboardKey::PageDown),
+            (KeyboardKey::Keypad0, KeyboardKey::Insert),
+            (KeyboardKey::KeypadDecimal, KeyboardKey::ForwardDelete),
+        ] {
+            assert_eq!(
+                keypad.logical(off, Layout::UsQwerty),
+                nav.logical(off, Layout::UsQwerty)
+            );
+        }
+        assert_eq!(
+            KeyboardKey::Keypad1.logical(Modifiers::NUM_LOCK, Layout::UsQwerty),
+            LogicalKey::Character('1')
+        );
+        assert_eq!(
+            KeyboardKey::KeypadPlus.logical(off, Layout::UsQwerty),
+            LogicalKey::Character('+')
+        );
+        assert_eq!(
+            KeyboardKey::KeypadEnter.logical(off, Layout::UsQwerty),
+            LogicalKey::Enter
+        );
+    }
+
+    #[test]
+    fn test_characters() {
+        let resolve = |key: KeyboardKey, modifiers| key.logical(modifiers, Layout::UsQwerty);
+        assert_eq!(
+            resolve(KeyboardKey::A, Modifiers::empty()),
+            LogicalKey::Character('a')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::A, Modifiers::SHIFT),
+            LogicalKey::Character('A')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::A, Modifiers::CAPS_LOCK),
+            LogicalKey::Character('A')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::A, Modifiers::SHIFT | Modifiers::CAPS_LOCK),
+            LogicalKey::Character('a')
+        );
+        //caps lock only affects letters
+        assert_eq!(
+            resolve(KeyboardKey::Num1, Modifiers::CAPS_LOCK),
+            LogicalKey::Character('1')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::Num1, Modifiers::SHIFT),
+            LogicalKey::Character('!')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::C, Modifiers::CONTROL),
+            LogicalKey::Character('c')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::Space, Modifiers::empty()),
+            LogicalKey::Character(' ')
+        );
+    }
+
+    #[test]
+    fn test_dvorak() {
+        let resolve = |key: KeyboardKey, modifiers| key.logical(modifiers, Layout::Dvorak);
+        assert_eq!(
+            resolve(KeyboardKey::S, Modifiers::empty()),
+            LogicalKey::Character('o')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::Q, Modifiers::SHIFT),
+            LogicalKey::Character('"')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::Minus, Modifiers::empty()),
+            LogicalKey::Character('[')
+        );
+        assert_eq!(
+            resolve(KeyboardKey::Num5, Modifiers::empty()),
+            LogicalKey::Character('5')
+        );
+    }
+
+    #[test]
+    fn test_named() {
+        let resolve = |key: KeyboardKey| key.logical(Modifiers::empty(), Layout::UsQwerty);
+        assert_eq!(resolve(KeyboardKey::Delete), LogicalKey::Backspace);
+        assert_eq!(resolve(KeyboardKey::ForwardDelete), LogicalKey::Delete);
+        assert_eq!(resolve(KeyboardKey::RightShift), LogicalKey::Shift);
+        assert_eq!(resolve(KeyboardKey::Command), LogicalKey::Meta);
+        assert_eq!(resolve(KeyboardKey::Play), LogicalKey::MediaPlayPause);
+        assert_eq!(LogicalKey::ArrowUp.to_string(), "ArrowUp");
+        assert_eq!(LogicalKey::Character('a').to_string(), "a");
+        for key in KeyboardKey::all_keys() {
+            //every key has a meaning
+            assert_ne!(resolve(key), LogicalKey::Unidentified, "{key:?}");
+        }
+    }
+}
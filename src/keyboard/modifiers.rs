@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::keyboard::key::KeyboardKey;
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub};
+
+/**
+A set of modifier keys and lock toggles.
+
+Left and right variants of a modifier are combined: [`Modifiers::SHIFT`] is set if either
+[`KeyboardKey::Shift`] or [`KeyboardKey::RightShift`] is held.
+
+[`Modifiers::CAPS_LOCK`] and [`Modifiers::NUM_LOCK`] report whether the lock is *toggled on*,
+not whether the lock key is held.
+
+# Example
+
+```
+use app_input::keyboard::modifiers::Modifiers;
+
+let modifiers = Modifiers::CONTROL | Modifiers::SHIFT;
+assert!(modifiers.contains(Modifiers::SHIFT));
+assert!(!modifiers.contains(Modifiers::CONTROL | Modifiers::OPTION));
+assert_eq!(modifiers.held(), modifiers);
+```
+*/
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// Either shift key is held.
+    pub const SHIFT: Modifiers = Modifiers(1 << 0);
+    /// Either control key is held.
+    pub const CONTROL: Modifiers = Modifiers(1 << 1);
+    /// Either option (alt) key is held.
+    pub const OPTION: Modifiers = Modifiers(1 << 2);
+    /// Either command (windows, super, meta) key is held.
+    pub const COMMAND: Modifiers = Modifiers(1 << 3);
+    /// The function key is held.
+    pub const FUNCTION: Modifiers = Modifiers(1 << 4);
+    /// Caps lock is toggled on.
+    pub const CAPS_LOCK: Modifiers = Modifiers(1 << 5);
+    /// Num lock is toggled on.
+    pub const NUM_LOCK: Modifiers = Modifiers(1 << 6);
+
+    const NAMES: [(Modifiers, &'static str); 7] = [
+        (Modifiers::SHIFT, "SHIFT"),
+        (Modifiers::CONTROL, "CONTROL"),
+        (Modifiers::OPTION, "OPTION"),
+        (Modifiers::COMMAND, "COMMAND"),
+        (Modifiers::FUNCTION, "FUNCTION"),
+        (Modifiers::CAPS_LOCK, "CAPS_LOCK"),
+        (Modifiers::NUM_LOCK, "NUM_LOCK"),
+    ];
+
+    /// Returns the empty set.
+    pub const fn empty() -> Self {
+        Modifiers(0)
+    }
+
+    /// Returns the set of all modifiers.
+    pub const fn all() -> Self {
+        Modifiers(0x7f)
+    }
+
+    /// Returns the set of modifiers that are held, rather than toggled.
+    pub const fn all_held() -> Self {
+        Modifiers(0x1f)
+    }
+
+    /// Returns the set of lock toggles.
+    pub const fn all_locks() -> Self {
+        Modifiers(0x60)
+    }
+
+    /// Returns the raw bits of this set.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Creates a set from raw bits, discarding any bits that do not correspond to a modifier.
+    pub const fn from_bits_truncate(bits: u8) -> Self {
+        Modifiers(bits & Self::all().0)
+    }
+
+    /// Returns `true` if no modifiers are set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if every modifier in `other` is also in `self`.
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any modifier in `other` is also in `self`.
+    pub const fn intersects(self, other: Modifiers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Adds the modifiers in `other`.
+    pub fn insert(&mut self, other: Modifiers) {
+        self.0 |= other.0;
+    }
+
+    /// Removes the modifiers in `other`.
+    pub fn remove(&mut self, other: Modifiers) {
+        self.0 &= !other.0;
+    }
+
+    /// Adds or removes the modifiers in `other`.
+    pub fn set(&mut self, other: Modifiers, value: bool) {
+        if value {
+            self.insert(other)
+        } else {
+            self.remove(other)
+        }
+    }
+
+    /// Returns only the held modifiers, without lock toggles.
+    pub const fn held(self) -> Self {
+        Modifiers(self.0 & Self::all_held().0)
+    }
+
+    /// Returns only the lock toggles, without held modifiers.
+    pub const fn locks(self) -> Self {
+        Modifiers(self.0 & Self::all_locks().0)
+    }
+
+    /**
+    Returns the held modifier corresponding to a key, or the empty set if the key is not a modifier.
+
+    Lock keys return the empty set, because holding them does not hold a modifier.
+
+    ```
+    use app_input::keyboard::key::KeyboardKey;
+    use app_input::keyboard::modifiers::Modifiers;
+
+    assert_eq!(Modifiers::from_key(KeyboardKey::RightShift), Modifiers::SHIFT);
+    assert_eq!(Modifiers::from_key(KeyboardKey::CapsLock), Modifiers::empty());
+    ```
+    */
+    pub const fn from_key(key: KeyboardKey) -> Self {
+        match key {
+            KeyboardKey::Shift | KeyboardKey::RightShift => Modifiers::SHIFT,
+            KeyboardKey::Control | KeyboardKey::RightControl => Modifiers::CONTROL,
+            KeyboardKey::Option | KeyboardKey::RightOption => Modifiers::OPTION,
+            KeyboardKey::Command | KeyboardKey::RightCommand => Modifiers::COMMAND,
+            KeyboardKey::Function => Modifiers::FUNCTION,
+            _ => Modifiers::empty(),
+        }
+    }
+
+    /// Returns the lock toggled by a key, or the empty set if the key is not a lock key.
+    pub(crate) const fn lock_from_key(key: KeyboardKey) -> Self {
+        match key {
+            KeyboardKey::CapsLock => Modifiers::CAPS_LOCK,
+            KeyboardKey::NumLock => Modifiers::NUM_LOCK,
+            _ => Modifiers::empty(),
+        }
+    }
+}
+
+impl Debug for Modifiers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Modifiers(")?;
+        let mut first = true;
+        for (modifier, name) in Self::NAMES {
+            if self.contains(modifier) {
+                if !first {
+                    write!(f, " | ")?;
+                }
+                write!(f, "{name}")?;
+                first = false;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Modifiers {
+    type Output = Modifiers;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Modifiers(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Modifiers {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Sub for Modifiers {
+    type Output = Modifiers;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Modifiers(self.0 & !rhs.0)
+    }
+}
+
+impl Not for Modifiers {
+    type Output = Modifiers;
+    fn not(self) -> Self::Output {
+        Modifiers(!self.0 & Self::all().0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keyboard::modifiers::Modifiers;
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(
+            format!("{:?}", Modifiers::SHIFT | Modifiers::NUM_LOCK),
+            "Modifiers(SHIFT | NUM_LOCK)"
+        );
+        assert_eq!(format!("{:?}", Modifiers::empty()), "Modifiers()");
+    }
+
+    #[test]
+    fn test_held_locks() {
+        let m = Modifiers::all();
+        assert_eq!(m.held() | m.locks(), m);
+        assert!(
+            !m.held()
+                .intersects(Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK)
+        );
+        assert_eq!(!Modifiers::all_held(), Modifiers::all_locks());
+    }
+}
@@ -0,0 +1,535 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::Window;
+use crate::keyboard::KeyEvent;
+use crate::keyboard::chord::{ChordStyle, KeyChord};
+use crate::keyboard::key::{KeyboardKey, ParseKeyError};
+use crate::keyboard::modifiers::Modifiers;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/**
+One or more chords pressed one after another, such as Emacs' `Ctrl+X Ctrl+S`.
+
+Sequences parse from chords separated by whitespace.  Each chord is parsed as a [`KeyChord`].
+
+```
+use app_input::keyboard::shortcut::KeySequence;
+
+let save: KeySequence = "Ctrl+X Ctrl+S".parse().unwrap();
+assert_eq!(save.chords().len(), 2);
+assert_eq!(save.to_string(), "Ctrl+X Ctrl+S");
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeySequence(Vec<KeyChord>);
+
+impl KeySequence {
+    /// Creates a sequence from its chords, in the order they are pressed.
+    pub fn new(chords: Vec<KeyChord>) -> Self {
+        KeySequence(chords)
+    }
+
+    /// The chords in the order they are pressed.
+    pub fn chords(&self) -> &[KeyChord] {
+        &self.0
+    }
+
+    /// Formats each chord in the specified style, separated by spaces.
+    pub fn format(&self, style: ChordStyle) -> String {
+        self.0
+            .iter()
+            .map(|chord| chord.format(style))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn starts_with(&self, prefix: &[KeyChord]) -> bool {
+        self.0.starts_with(prefix)
+    }
+}
+
+impl From<KeyChord> for KeySequence {
+    fn from(chord: KeyChord) -> Self {
+        KeySequence(vec![chord])
+    }
+}
+
+/// Formats the sequence in [`ChordStyle::native`].
+impl Display for KeySequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(ChordStyle::native()))
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        //whitespace separates chords, except around the `+` inside a chord
+        let mut words: Vec<String> = Vec::new();
+        for token in s.split_whitespace() {
+            match words.last_mut() {
+                Some(last) if last.ends_with('+') || token.starts_with('+') => last.push_str(token),
+                _ => words.push(token.to_string()),
+            }
+        }
+        if words.is_empty() {
+            return Err(ParseKeyError::new(s));
+        }
+        words
+            .iter()
+            .map(|word| word.parse())
+            .collect::<Result<_, _>>()
+            .map(KeySequence)
+    }
+}
+
+/// Where a binding is active.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Scope {
+    /// The binding is active in every window.
+    Global,
+    /// The binding is active only while the window has keyboard focus.
+    ///
+    /// Within its window, a window-scoped binding takes precedence over a global binding.
+    Window(Window),
+}
+
+/// Identifies a binding registered with [`ShortcutMap::register`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BindingId(u64);
+
+/// The reason [`ShortcutMap::register`] rejected a binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShortcutError {
+    /// The sequence has no chords.
+    Empty,
+    /// A binding in the same scope already uses the sequence.
+    Duplicate(BindingId),
+    /// A binding in the same scope is a prefix of the sequence, or the sequence is a prefix of it,
+    /// so the longer of the two could never be completed.
+    Prefix(BindingId),
+}
+
+impl Display for ShortcutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShortcutError::Empty => write!(f, "shortcut has no chords"),
+            ShortcutError::Duplicate(id) => write!(f, "shortcut is already bound by {id:?}"),
+            ShortcutError::Prefix(id) => {
+                write!(f, "shortcut overlaps the prefix of {id:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShortcutError {}
+
+/// A successful registration, returned from [`ShortcutMap::register`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registration {
+    id: BindingId,
+    shadowed: Vec<BindingId>,
+}
+
+impl Registration {
+    /// The new binding.
+    pub fn id(&self) -> BindingId {
+        self.id
+    }
+
+    /**
+    Bindings in another scope that overlap the new binding, by using the same sequence or a prefix
+    of it.
+
+    Within a window, its window-scoped binding takes precedence, so the global binding of each pair
+    cannot be triggered there.  This is often intended, but a rebinding UI may want to warn about it.
+    */
+    pub fn shadowed(&self) -> &[BindingId] {
+        &self.shadowed
+    }
+}
+
+/// The result of feeding a key event to a [`ShortcutMap`].
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShortcutEvent<'a, A> {
+    /// A binding was completed.
+    Triggered {
+        /// The completed binding.
+        id: BindingId,
+        /// The action registered for the binding.
+        action: &'a A,
+    },
+    /// The chords pressed so far are the start of at least one sequence.
+    Pending(&'a KeySequence),
+    /// A pending sequence was abandoned, because the key did not continue any binding or focus moved
+    /// to another window.
+    Cancelled,
+    /// The chord requested by [`ShortcutMap::capture_next_chord`].
+    Captured(KeyChord),
+}
+
+#[derive(Debug)]
+struct Binding<A> {
+    id: BindingId,
+    sequence: KeySequence,
+    scope: Scope,
+    action: A,
+}
+
+/**
+Matches key events against registered chords and sequences.
+
+Each binding maps a [`KeySequence`] to an action of type `A`, which may be an ID of your own or a
+callback such as `Box<dyn Fn()>`.  Feed the map every event from
+[`Keyboard::drain_events`](crate::keyboard::Keyboard::drain_events), in order.
+
+The map tracks held modifiers from the events it is fed, so it should see every event from the
+start.  Modifier keys never complete a chord on their own, and key repeats are ignored.
+
+# Example
+
+```
+use app_input::keyboard::Keyboard;
+use app_input::keyboard::shortcut::{Scope, ShortcutEvent, ShortcutMap};
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Save,
+    Quit,
+}
+
+let mut shortcuts = ShortcutMap::new();
+shortcuts.register("Ctrl+X Ctrl+S".parse().unwrap(), Scope::Global, Action::Save).unwrap();
+shortcuts.register("Ctrl+Q".parse().unwrap(), Scope::Global, Action::Quit).unwrap();
+
+let keyboard = Keyboard::coalesced();
+for event in &keyboard.drain_events() {
+    if let Some(ShortcutEvent::Triggered { action, .. }) = shortcuts.feed(event) {
+        println!("{action:?}");
+    }
+}
+```
+*/
+#[derive(Debug)]
+pub struct ShortcutMap<A> {
+    bindings: Vec<Binding<A>>,
+    next_id: u64,
+    held_modifiers: Vec<KeyboardKey>,
+    pending: KeySequence,
+    pending_window: Option<Window>,
+    capturing: bool,
+}
+
+impl<A> Default for ShortcutMap<A> {
+    fn default() -> Self {
+        ShortcutMap::new()
+    }
+}
+
+impl<A> ShortcutMap<A> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        ShortcutMap {
+            bindings: Vec::new(),
+            next_id: 0,
+            held_modifiers: Vec::new(),
+            pending: KeySequence(Vec::new()),
+            pending_window: None,
+            capturing: false,
+        }
+    }
+
+    /**
+    Binds a sequence to an action.
+
+    # Errors
+
+    Fails if the sequence is empty, or if a binding in the same scope uses the same sequence or
+    overlaps it as a prefix.  Overlap with a binding in another scope is allowed and reported
+    by [`Registration::shadowed`].
+    */
+    pub fn register(
+        &mut self,
+        sequence: KeySequence,
+        scope: Scope,
+        action: A,
+    ) -> Result<Registration, ShortcutError> {
+        if sequence.chords().is_empty() {
+            return Err(ShortcutError::Empty);
+        }
+        let mut shadowed = Vec::new();
+        for binding in &self.bindings {
+            let overlaps = binding.sequence.starts_with(&sequence.0)
+                || sequence.starts_with(&binding.sequence.0);
+            if !overlaps {
+                continue;
+            }
+            if binding.scope == scope {
+                return Err(if binding.sequence == sequence {
+                    ShortcutError::Duplicate(binding.id)
+                } else {
+                    ShortcutError::Prefix(binding.id)
+                });
+            }
+            if binding.scope == Scope::Global || scope == Scope::Global {
+                shadowed.push(binding.id);
+            }
+        }
+        let id = BindingId(self.next_id);
+        self.next_id += 1;
+        self.bindings.push(Binding {
+            id,
+            sequence,
+            scope,
+            action,
+        });
+        Ok(Registration { id, shadowed })
+    }
+
+    /// Removes a binding, returning its action if it was registered.
+    pub fn unregister(&mut self, id: BindingId) -> Option<A> {
+        let index = self.bindings.iter().position(|b| b.id == id)?;
+        Some(self.bindings.remove(index).action)
+    }
+
+    /// Returns the sequence and scope of a binding.
+    pub fn binding(&self, id: BindingId) -> Option<(&KeySequence, Scope)> {
+        self.bindings
+            .iter()
+            .find(|b| b.id == id)
+            .map(|b| (&b.sequence, b.scope))
+    }
+
+    /**
+    Reports the next chord as [`ShortcutEvent::Captured`] instead of matching it.
+
+    Use this in a rebinding UI to read the chord the user wants.  Any pending sequence is abandoned.
+    */
+    pub fn capture_next_chord(&mut self) {
+        self.capturing = true;
+        self.pending.0.clear();
+    }
+
+    /// Stops a capture started by [`ShortcutMap::capture_next_chord`].
+    pub fn cancel_capture(&mut self) {
+        self.capturing = false;
+    }
+
+    /// Returns `true` if the next chord will be captured.
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    /// Returns the modifiers held according to the events fed so far.
+    pub fn modifiers(&self) -> Modifiers {
+        self.held_modifiers
+            .iter()
+            .fold(Modifiers::empty(), |m, key| m | Modifiers::from_key(*key))
+    }
+
+    /// Processes a key event, returning what it did, if anything.
+    pub fn feed(&mut self, event: &KeyEvent) -> Option<ShortcutEvent<'_, A>> {
+        let key = event.key();
+        if !Modifiers::from_key(key).is_empty() {
+            self.held_modifiers.retain(|held| *held != key);
+            if event.down() {
+                self.held_modifiers.push(key);
+            }
+            return None;
+        }
+        if !event.down() || event.repeat() {
+            return None;
+        }
+        let chord = KeyChord::new(self.modifiers(), key);
+        if self.capturing {
+            self.capturing = false;
+            return Some(ShortcutEvent::Captured(chord));
+        }
+
+        let window = event.window();
+        let was_pending = !self.pending.0.is_empty();
+        if was_pending && self.pending_window != window {
+            self.pending.0.clear();
+        }
+        self.pending.0.push(chord);
+        self.pending_window = window;
+
+        let scopes = window
+            .map(Scope::Window)
+            .into_iter()
+            .chain(std::iter::once(Scope::Global));
+        for scope in scopes {
+            let mut in_scope = self.bindings.iter().filter(|b| b.scope == scope);
+            if let Some(binding) = in_scope.clone().find(|b| b.sequence == self.pending) {
+                self.pending.0.clear();
+                return Some(ShortcutEvent::Triggered {
+                    id: binding.id,
+                    action: &binding.action,
+                });
+            }
+            if in_scope.any(|b| b.sequence.starts_with(&self.pending.0)) {
+                return Some(ShortcutEvent::Pending(&self.pending));
+            }
+        }
+        self.pending.0.clear();
+        if was_pending {
+            Some(ShortcutEvent::Cancelled)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KeySequence, Scope, ShortcutError, ShortcutEvent, ShortcutMap};
+    use crate::Window;
+    use crate::keyboard::KeyEvent;
+    use crate::keyboard::chord::KeyChord;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::keyboard::modifiers::Modifiers;
+    use crate::timestamp::Timestamp;
+    use std::ptr::NonNull;
+
+    fn window(n: usize) -> Window {
+        Window(NonNull::new(n as *mut std::ffi::c_void).unwrap())
+    }
+
+    fn event(key: KeyboardKey, down: bool, window: Option<Window>) -> KeyEvent {
+        KeyEvent::new(key, down, false, window, Timestamp::now())
+    }
+
+    /// Feeds a press and release of `chord`, returning a description of the press.
+    fn press<A: Copy>(
+        map: &mut ShortcutMap<A>,
+        chord: &str,
+        window: Option<Window>,
+    ) -> Option<Result<A, &'static str>> {
+        let chord: KeyChord = chord.parse().unwrap();
+        let modifier_keys = [
+            (Modifiers::CONTROL, KeyboardKey::Control),
+            (Modifiers::SHIFT, KeyboardKey::Shift),
+            (Modifiers::OPTION, KeyboardKey::Option),
+            (Modifiers::COMMAND, KeyboardKey::Command),
+        ];
+        for (modifier, key) in modifier_keys {
+            if chord.modifiers().contains(modifier) {
+                map.feed(&event(key, true, window));
+            }
+        }
+        let result = map
+            .feed(&event(chord.key(), true, window))
+            .map(|e| match e {
+                ShortcutEvent::Triggered { action, .. } => Ok(*action),
+                ShortcutEvent::Pending(_) => Err("pending"),
+                ShortcutEvent::Cancelled => Err("cancelled"),
+                ShortcutEvent::Captured(_) => Err("captured"),
+            });
+        map.feed(&event(chord.key(), false, window));
+        for (modifier, key) in modifier_keys {
+            if chord.modifiers().contains(modifier) {
+                map.feed(&event(key, false, window));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let sequence: KeySequence = "Ctrl + X  Ctrl+S".parse().unwrap();
+        assert_eq!(
+            sequence.chords(),
+            &[
+                "Ctrl+X".parse::<KeyChord>().unwrap(),
+                "Ctrl+S".parse().unwrap()
+            ]
+        );
+        assert!("".parse::<KeySequence>().is_err());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut map = ShortcutMap::new();
+        let save = map
+            .register("Ctrl+X Ctrl+S".parse().unwrap(), Scope::Global, 1)
+            .unwrap();
+        assert_eq!(
+            map.register("Ctrl+X Ctrl+S".parse().unwrap(), Scope::Global, 2),
+            Err(ShortcutError::Duplicate(save.id()))
+        );
+        assert_eq!(
+            map.register("Ctrl+X".parse().unwrap(), Scope::Global, 2),
+            Err(ShortcutError::Prefix(save.id()))
+        );
+        assert_eq!(
+            map.register(KeySequence::new(Vec::new()), Scope::Global, 2),
+            Err(ShortcutError::Empty)
+        );
+        let local = map
+            .register("Ctrl+X".parse().unwrap(), Scope::Window(window(1)), 2)
+            .unwrap();
+        assert_eq!(local.shadowed(), &[save.id()]);
+        let other = map
+            .register("Ctrl+X".parse().unwrap(), Scope::Window(window(2)), 3)
+            .unwrap();
+        assert_eq!(other.shadowed(), &[save.id()]);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let mut map = ShortcutMap::new();
+        map.register("Ctrl+X Ctrl+S".parse().unwrap(), Scope::Global, "save")
+            .unwrap();
+        map.register("Ctrl+S".parse().unwrap(), Scope::Global, "search")
+            .unwrap();
+        assert_eq!(press(&mut map, "Ctrl+X", None), Some(Err("pending")));
+        assert_eq!(press(&mut map, "Ctrl+S", None), Some(Ok("save")));
+        assert_eq!(press(&mut map, "Ctrl+S", None), Some(Ok("search")));
+        assert_eq!(press(&mut map, "Ctrl+X", None), Some(Err("pending")));
+        assert_eq!(press(&mut map, "Q", None), Some(Err("cancelled")));
+        assert_eq!(press(&mut map, "Q", None), None);
+        //extra modifiers do not match
+        assert_eq!(press(&mut map, "Ctrl+Shift+S", None), None);
+    }
+
+    #[test]
+    fn test_window_scope() {
+        let mut map = ShortcutMap::new();
+        map.register("Ctrl+W".parse().unwrap(), Scope::Global, "global")
+            .unwrap();
+        map.register("Ctrl+W".parse().unwrap(), Scope::Window(window(1)), "local")
+            .unwrap();
+        assert_eq!(
+            press(&mut map, "Ctrl+W", Some(window(1))),
+            Some(Ok("local"))
+        );
+        assert_eq!(
+            press(&mut map, "Ctrl+W", Some(window(2))),
+            Some(Ok("global"))
+        );
+        assert_eq!(press(&mut map, "Ctrl+W", None), Some(Ok("global")));
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut map = ShortcutMap::new();
+        map.register("Ctrl+S".parse().unwrap(), Scope::Global, ())
+            .unwrap();
+        map.capture_next_chord();
+        map.feed(&event(KeyboardKey::RightControl, true, None));
+        assert!(map.is_capturing());
+        assert_eq!(
+            map.feed(&event(KeyboardKey::S, true, None)),
+            Some(ShortcutEvent::Captured("Ctrl+S".parse().unwrap()))
+        );
+        assert!(!map.is_capturing());
+        assert!(matches!(
+            map.feed(&event(KeyboardKey::S, true, None)),
+            Some(ShortcutEvent::Triggered { .. })
+        ));
+    }
+}
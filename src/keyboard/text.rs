@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::Window;
+use crate::timestamp::Timestamp;
+
+/**
+A run of text typed by the user, as returned from [`Keyboard::drain_text_input`](crate::keyboard::Keyboard::drain_text_input).
+
+The text is already committed: shift state, the keyboard layout, dead keys and compose sequences
+have all been applied.  It never contains control characters.
+
+# Example
+
+```
+use app_input::keyboard::Keyboard;
+
+let keyboard = Keyboard::coalesced();
+for input in keyboard.drain_text_input() {
+    println!("typed {:?} at {:?}", input.text(), input.timestamp());
+}
+```
+*/
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextInput {
+    text: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    window: Option<Window>,
+    timestamp: Timestamp,
+}
+
+impl TextInput {
+    pub(crate) fn new(text: String, window: Option<Window>, timestamp: Timestamp) -> Self {
+        TextInput {
+            text,
+            window,
+            timestamp,
+        }
+    }
+
+    /// Returns the text that was typed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the window the text was delivered to, if known.
+    ///
+    /// See [`Window`] for the platform-specific meaning of this value.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which the text was typed.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Consumes this value, returning the text.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
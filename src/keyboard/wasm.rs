@@ -1,15 +1,24 @@
 // SPDX-License-Identifier: MPL-2.0
 use crate::keyboard::Shared;
-use crate::keyboard::key::KeyboardKey;
+use crate::keyboard::ime::ImeEvent;
+use crate::keyboard::key::{KeyboardKey, RawScancode, ScancodeKind};
+use crate::keyboard::modifiers::Modifiers;
+use crate::timestamp::Timestamp;
 use std::ffi::c_void;
 use std::sync::Arc;
+use std::time::Duration;
 use wasm_bindgen::prelude::*;
-use web_sys::KeyboardEvent;
+use web_sys::{CompositionEvent, KeyboardEvent};
 
 #[derive(Debug)]
 pub(super) struct PlatformCoalescedKeyboard {
     _key_down: JsValue,
     _key_up: JsValue,
+    _blur: JsValue,
+    _focus: JsValue,
+    _composition_start: JsValue,
+    _composition_update: JsValue,
+    _composition_end: JsValue,
 }
 
 unsafe impl Send for PlatformCoalescedKeyboard {}
@@ -17,21 +26,39 @@ unsafe impl Sync for PlatformCoalescedKeyboard {}
 
 pub(crate) const ARBITRARY_WINDOW_PTR: *mut c_void = 0x01 as *mut c_void;
 
+/// Returns the time at which a DOM event occurred.
+pub(crate) fn event_timestamp(event: &web_sys::Event) -> Timestamp {
+    //`timeStamp` is measured from the time origin, which is our epoch
+    Timestamp::from_duration_since_epoch(Duration::from_secs_f64(
+        event.time_stamp().max(0.0) / 1000.0,
+    ))
+}
+
 impl PlatformCoalescedKeyboard {
     pub fn new(shared: &Arc<Shared>) -> Self {
         let weak = Arc::downgrade(shared);
         let weak_up = weak.clone();
+        let weak_blur = weak.clone();
+        let weak_focus = weak.clone();
+        let weak_composition_start = weak.clone();
+        let weak_composition_update = weak.clone();
+        let weak_composition_end = weak.clone();
         let window = web_sys::window().expect("no global window exists");
         let document = window.document().expect("no document on window");
         let keydown_callback = Closure::wrap(Box::new(move |event: KeyboardEvent| {
-            let key = event.key();
-            let code = event.code();
-
             if let Some(shared) = weak.upgrade() {
-                let key = KeyboardKey::from_js_code(&code)
-                    .expect(format!("Unknown key: {}", key).as_str());
+                let key = KeyboardKey::from_event(&event);
 
-                shared.set_key_state(key, true, ARBITRARY_WINDOW_PTR);
+                let timestamp = event_timestamp(&event);
+                if event.repeat() {
+                    shared.repeat_key_at(key, ARBITRARY_WINDOW_PTR, timestamp);
+                } else {
+                    shared.set_key_state_at(key, true, ARBITRARY_WINDOW_PTR, timestamp);
+                }
+                update_locks(&shared, &event);
+                if let Some(text) = key_text(&event) {
+                    shared.push_text(&text, ARBITRARY_WINDOW_PTR);
+                }
             }
         }) as Box<dyn FnMut(KeyboardEvent)>);
         document
@@ -39,25 +66,129 @@ impl PlatformCoalescedKeyboard {
             .expect("Can't add event listener");
 
         let keyup_callback = Closure::wrap(Box::new(move |event: KeyboardEvent| {
-            let key = event.key();
-            let code = event.code();
             if let Some(shared) = weak_up.upgrade() {
-                let key = KeyboardKey::from_js_code(&code)
-                    .expect(format!("Unknown key: {}", key).as_str());
-                shared.set_key_state(key, false, ARBITRARY_WINDOW_PTR);
+                let key = KeyboardKey::from_event(&event);
+                shared.set_key_state_at(key, false, ARBITRARY_WINDOW_PTR, event_timestamp(&event));
+                update_locks(&shared, &event);
             }
         }) as Box<dyn FnMut(KeyboardEvent)>);
         document
             .add_event_listener_with_callback("keyup", keyup_callback.as_ref().unchecked_ref())
             .expect("Can't add event listener");
 
+        //the browser stops delivering key events when the page loses focus, so release held keys
+        let blur_callback = Closure::wrap(Box::new(move || {
+            if let Some(shared) = weak_blur.upgrade() {
+                shared.clear_focus(ARBITRARY_WINDOW_PTR);
+            }
+        }) as Box<dyn FnMut()>);
+        window
+            .add_event_listener_with_callback("blur", blur_callback.as_ref().unchecked_ref())
+            .expect("Can't add event listener");
+
+        let focus_callback = Closure::wrap(Box::new(move || {
+            if let Some(shared) = weak_focus.upgrade() {
+                shared.set_focus(ARBITRARY_WINDOW_PTR);
+            }
+        }) as Box<dyn FnMut()>);
+        window
+            .add_event_listener_with_callback("focus", focus_callback.as_ref().unchecked_ref())
+            .expect("Can't add event listener");
+
+        //a new composition replaces whatever preedit the last one left behind
+        let composition_start_callback = Closure::wrap(Box::new(move |_event: CompositionEvent| {
+            if let Some(shared) = weak_composition_start.upgrade() {
+                shared.push_ime_event(ImeEvent::Preedit {
+                    text: String::new(),
+                    cursor: None,
+                });
+            }
+        })
+            as Box<dyn FnMut(CompositionEvent)>);
+        document
+            .add_event_listener_with_callback(
+                "compositionstart",
+                composition_start_callback.as_ref().unchecked_ref(),
+            )
+            .expect("Can't add event listener");
+
+        let composition_update_callback = Closure::wrap(Box::new(move |event: CompositionEvent| {
+            if let Some(shared) = weak_composition_update.upgrade() {
+                let text = event.data().unwrap_or_default();
+                //the browser doesn't tell us where the cursor is, so put it at the end
+                let cursor = Some((text.len(), text.len()));
+                shared.push_ime_event(ImeEvent::Preedit { text, cursor });
+            }
+        })
+            as Box<dyn FnMut(CompositionEvent)>);
+        document
+            .add_event_listener_with_callback(
+                "compositionupdate",
+                composition_update_callback.as_ref().unchecked_ref(),
+            )
+            .expect("Can't add event listener");
+
+        let composition_end_callback = Closure::wrap(Box::new(move |event: CompositionEvent| {
+            if let Some(shared) = weak_composition_end.upgrade() {
+                shared.push_ime_event(ImeEvent::Commit(event.data().unwrap_or_default()));
+                shared.push_ime_event(ImeEvent::Preedit {
+                    text: String::new(),
+                    cursor: None,
+                });
+            }
+        })
+            as Box<dyn FnMut(CompositionEvent)>);
+        document
+            .add_event_listener_with_callback(
+                "compositionend",
+                composition_end_callback.as_ref().unchecked_ref(),
+            )
+            .expect("Can't add event listener");
+
+        if document.has_focus().unwrap_or(false) {
+            shared.set_focus(ARBITRARY_WINDOW_PTR);
+        }
+
         Self {
             _key_down: keydown_callback.into_js_value(),
             _key_up: keyup_callback.into_js_value(),
+            _blur: blur_callback.into_js_value(),
+            _focus: focus_callback.into_js_value(),
+            _composition_start: composition_start_callback.into_js_value(),
+            _composition_update: composition_update_callback.into_js_value(),
+            _composition_end: composition_end_callback.into_js_value(),
         }
     }
 }
 
+/// Returns the text typed by a keydown event, if any.
+///
+/// `KeyboardEvent.key` is either the character produced, or a name such as `"Enter"` or `"Dead"`.
+fn key_text(event: &KeyboardEvent) -> Option<String> {
+    let key = event.key();
+    let mut chars = key.chars();
+    if chars.next().is_none() || chars.next().is_some() {
+        //named keys are longer than one character
+        return None;
+    }
+    //composition is reported separately, and shortcuts are not text.
+    //AltGr is reported as Control+Alt on some platforms, so that combination is text.
+    if event.is_composing() || event.meta_key() || (event.ctrl_key() && !event.alt_key()) {
+        return None;
+    }
+    Some(key)
+}
+
+fn update_locks(shared: &Shared, event: &KeyboardEvent) {
+    shared.set_lock(Modifiers::CAPS_LOCK, event.get_modifier_state("CapsLock"));
+    shared.set_lock(Modifiers::NUM_LOCK, event.get_modifier_state("NumLock"));
+}
+
+//the browser enables the input method for editable elements, and positions it near them
+pub(crate) fn set_ime_allowed(_allowed: bool) {}
+
+pub(crate) fn set_ime_cursor_area(_x: f64, _y: f64, _width: f64, _height: f64) {}
+
 pub fn debug_window_show() {
     //nothing?
 }
@@ -67,172 +198,27 @@ pub fn debug_window_hide() {
 }
 
 impl KeyboardKey {
-    fn from_js_code(js: &str) -> Option<KeyboardKey> {
+    /// Maps the event's `code`, passing through codes with no named key as
+    /// [`KeyboardKey::Unknown`] carrying the legacy `keyCode`.
+    fn from_event(event: &KeyboardEvent) -> KeyboardKey {
+        let code = event.code();
+        KeyboardKey::from_web_code(&code)
+            .or_else(|| KeyboardKey::from_web_code_alias(&code))
+            .unwrap_or(KeyboardKey::Unknown(RawScancode::new(
+                ScancodeKind::WebKeyCode,
+                event.key_code(),
+            )))
+    }
+
+    /// Maps `code` values that older browsers, or some platforms, report for a named key.
+    fn from_web_code_alias(code: &str) -> Option<KeyboardKey> {
         //https://developer.mozilla.org/en-US/docs/Web/API/UI_Events/Keyboard_event_code_values
-        let key = match js {
-            "Unidentified" => return None,
-            "Escape" => KeyboardKey::Escape,
-            "Digit1" => KeyboardKey::Num1,
-            "Digit2" => KeyboardKey::Num2,
-            "Digit3" => KeyboardKey::Num3,
-            "Digit4" => KeyboardKey::Num4,
-            "Digit5" => KeyboardKey::Num5,
-            "Digit6" => KeyboardKey::Num6,
-            "Digit7" => KeyboardKey::Num7,
-            "Digit8" => KeyboardKey::Num8,
-            "Digit9" => KeyboardKey::Num9,
-            "Digit0" => KeyboardKey::Num0,
-            "Minus" => KeyboardKey::Minus,
-            "Equal" => KeyboardKey::Equal,
-            "Backspace" => KeyboardKey::Delete,
-            "Tab" => KeyboardKey::Tab,
-            "KeyQ" => KeyboardKey::Q,
-            "KeyW" => KeyboardKey::W,
-            "KeyE" => KeyboardKey::E,
-            "KeyR" => KeyboardKey::R,
-            "KeyT" => KeyboardKey::T,
-            "KeyY" => KeyboardKey::Y,
-            "KeyU" => KeyboardKey::U,
-            "KeyI" => KeyboardKey::I,
-            "KeyO" => KeyboardKey::O,
-            "KeyP" => KeyboardKey::P,
-            "BracketLeft" => KeyboardKey::LeftBracket,
-            "BracketRight" => KeyboardKey::RightBracket,
-            "Enter" => KeyboardKey::Return,
-            "ControlLeft" => KeyboardKey::Control,
-            "KeyA" => KeyboardKey::A,
-            "KeyS" => KeyboardKey::S,
-            "KeyD" => KeyboardKey::D,
-            "KeyF" => KeyboardKey::F,
-            "KeyG" => KeyboardKey::G,
-            "KeyH" => KeyboardKey::H,
-            "KeyJ" => KeyboardKey::J,
-            "KeyK" => KeyboardKey::K,
-            "KeyL" => KeyboardKey::L,
-            "Semicolon" => KeyboardKey::Semicolon,
-            "Quote" => KeyboardKey::Quote,
-            "Backquote" => KeyboardKey::Grave,
-            "ShiftLeft" => KeyboardKey::Shift,
-            "Backslash" => KeyboardKey::Backslash,
-            "KeyZ" => KeyboardKey::Z,
-            "KeyX" => KeyboardKey::X,
-            "KeyC" => KeyboardKey::C,
-            "KeyV" => KeyboardKey::V,
-            "KeyB" => KeyboardKey::B,
-            "KeyN" => KeyboardKey::N,
-            "KeyM" => KeyboardKey::M,
-            "Comma" => KeyboardKey::Comma,
-            "Period" => KeyboardKey::Period,
-            "Slash" => KeyboardKey::Slash,
-            "ShiftRight" => KeyboardKey::RightShift,
-            "NumpadMultiply" => KeyboardKey::KeypadMultiply,
-            "AltLeft" => KeyboardKey::Option,
-            "Space" => KeyboardKey::Space,
-            "CapsLock" => KeyboardKey::CapsLock,
-            "F1" => KeyboardKey::F1,
-            "F2" => KeyboardKey::F2,
-            "F3" => KeyboardKey::F3,
-            "F4" => KeyboardKey::F4,
-            "F5" => KeyboardKey::F5,
-            "F6" => KeyboardKey::F6,
-            "F7" => KeyboardKey::F7,
-            "F8" => KeyboardKey::F8,
-            "F9" => KeyboardKey::F9,
-            "F10" => KeyboardKey::F10,
-            "Pause" => KeyboardKey::Pause,
-            "ScrollLock" => KeyboardKey::ScrollLock,
-            "Numpad7" => KeyboardKey::Keypad7,
-            "Numpad8" => KeyboardKey::Keypad8,
-            "Numpad9" => KeyboardKey::Keypad9,
-            "NumpadSubtract" => KeyboardKey::KeypadMinus,
-            "Numpad4" => KeyboardKey::Keypad4,
-            "Numpad5" => KeyboardKey::Keypad5,
-            "Numpad6" => KeyboardKey::Keypad6,
-            "NumpadAdd" => KeyboardKey::KeypadPlus,
-            "Numpad1" => KeyboardKey::Keypad1,
-            "Numpad2" => KeyboardKey::Keypad2,
-            "Numpad3" => KeyboardKey::Keypad3,
-            "Numpad0" => KeyboardKey::Keypad0,
-            "NumpadDecimal" => KeyboardKey::KeypadDecimal,
-            "PrintScreen" => KeyboardKey::PrintScreen,
-            "IntlBackslash" => KeyboardKey::InternationalBackslash,
-            "F11" => KeyboardKey::F11,
-            "F12" => KeyboardKey::F12,
-            "NumpadEqual" => KeyboardKey::KeypadEquals,
-            "F13" => KeyboardKey::F13,
-            "F14" => KeyboardKey::F14,
-            "F15" => KeyboardKey::F15,
-            "F16" => KeyboardKey::F16,
-            "F17" => KeyboardKey::F17,
-            "F18" => KeyboardKey::F18,
-            "F19" => KeyboardKey::F19,
-            "F20" => KeyboardKey::F20,
-            "F21" => KeyboardKey::F21,
-            "F22" => KeyboardKey::F22,
-            "F23" => KeyboardKey::F23,
-            "KanaMode" => KeyboardKey::JISKana,
-            "Lang2" => KeyboardKey::JISEisu,
-            "Lang1" => KeyboardKey::JISKana,
-            "NumpadEnter" => KeyboardKey::KeypadEnter,
-            "ControlRight" => KeyboardKey::RightControl,
-            "AudioVolumeMute" => KeyboardKey::Mute,
-            "AudioVolumeDown" => KeyboardKey::VolumeDown,
-            "AudioVolumeUp" => KeyboardKey::VolumeUp,
-            "NumpadComma" => KeyboardKey::JISKeypadComma,
-            "NumpadDivide" => KeyboardKey::KeypadDivide,
-            "IntlRo" => KeyboardKey::JISUnderscore,
-            "F24" => KeyboardKey::F24,
-            "Convert" => KeyboardKey::Convert,
-            "NonConvert" => KeyboardKey::NonConvert,
-            "IntlYen" => KeyboardKey::JISYen,
-            "MediaTrackPrevious" => KeyboardKey::PreviousTrack,
-            "MediaTrackNext" => KeyboardKey::NextTrack,
-            "LaunchApp2" => KeyboardKey::LaunchApp2,
-            "MediaPlayPause" => KeyboardKey::Play,
-            "MediaStop" => KeyboardKey::Stop,
-            "VolumeDown" => KeyboardKey::VolumeDown,
-            "VolumeUp" => KeyboardKey::VolumeUp,
-            "BrowserHome" => KeyboardKey::BrowserHome,
-            "AltRight" => KeyboardKey::RightOption,
-            "NumLock" => KeyboardKey::NumLock,
-            "Home" => KeyboardKey::Home,
-            "ArrowUp" => KeyboardKey::UpArrow,
-            "PageUp" => KeyboardKey::PageUp,
-            "ArrowLeft" => KeyboardKey::LeftArrow,
-            "ArrowRight" => KeyboardKey::RightArrow,
-            "End" => KeyboardKey::End,
-            "ArrowDown" => KeyboardKey::DownArrow,
-            "PageDown" => KeyboardKey::PageDown,
-            "Insert" => KeyboardKey::Insert,
-            "Delete" => KeyboardKey::Delete,
-            "MetaLeft" => KeyboardKey::Command,
-            "MetaRight" => KeyboardKey::RightCommand,
-            "ContextMenu" => KeyboardKey::ContextMenu,
-            "Power" => KeyboardKey::Power,
-            "Eject" => KeyboardKey::Eject,
-            "BrowserSearch" => KeyboardKey::BrowserSearch,
-            "BrowserFavorites" => KeyboardKey::BrowserFavorites,
-            "BrowserRefresh" => KeyboardKey::BrowserRefresh,
-            "BrowserStop" => KeyboardKey::BrowserStop,
-            "BrowserForward" => KeyboardKey::BrowserForward,
-            "BrowserBack" => KeyboardKey::BrowserBack,
-            "LaunchApp1" => KeyboardKey::LaunchApp1,
-            "LaunchMail" => KeyboardKey::LaunchMail,
-            "MediaSelect" => KeyboardKey::MediaSelect,
-            "Help" => KeyboardKey::Help,
-            "Again" => KeyboardKey::Again,
-            "Props" => KeyboardKey::Props,
-            "Undo" => KeyboardKey::Undo,
-            "Select" => KeyboardKey::Select,
-            "Copy" => KeyboardKey::Copy,
-            "Open" => KeyboardKey::Open,
-            "Paste" => KeyboardKey::Paste,
-            "Find" => KeyboardKey::Find,
-            "Cut" => KeyboardKey::Cut,
-            "WakeUp" => KeyboardKey::WakeUp,
-            "Fn" => KeyboardKey::Function,
-            _ => return None,
-        };
-        Some(key)
+        match code {
+            "VolumeUp" => Some(KeyboardKey::VolumeUp),
+            "VolumeDown" => Some(KeyboardKey::VolumeDown),
+            "VolumeMute" => Some(KeyboardKey::Mute),
+            "Lang1" => Some(KeyboardKey::JISKana),
+            _ => None,
+        }
     }
 }
@@ -18,7 +18,8 @@ app_input is a cross-platform library for receiving keyboard and mouse events.
 * Keyboard events:
    * Report raw up/down events whenever possible
         * We map hardware keys rather than unicode characters
-        * If you are trying to implement text input, you have much work to do, including but not limited to the shift key
+        * For text input, use [`keyboard::Keyboard::drain_text_input`], which reports the typed [`keyboard::text::TextInput`]
+          as the user's layout and modifiers produce it, and [`keyboard::Keyboard::drain_ime_events`] for IME composition
    * Keycodes are translated into a platform-independent enum that works everywhere
    * On Linux, key events are broadcasted over ATSPI.  Due to some [questionable decisions in the Linux ecosystem](https://github.com/AccessKit/accesskit/discussions/503#discussioncomment-11862133)
      this is required for screenreaders to work but nobody does it.  We do!
@@ -41,11 +42,47 @@ app_input is a cross-platform library for receiving keyboard and mouse events.
 This library is also available as an npm package for JavaScript/TypeScript projects targeting WebAssembly.
 The package provides type definitions and can be used in web applications.
 
+# Cargo features
+
+* `serde`: implements `Serialize` and `Deserialize` for keys and input events.  Keys serialize as
+  strings in their `Display` form (e.g. `"KeypadEnter"`) and deserialize with `FromStr`, so aliases
+  such as `"Esc"` are accepted.  Since keys are strings in every format, including ones that aren't
+  self-describing such as bincode, recorded data stays valid if variants are reordered.
+  Mouse buttons are plain `u8` values and serialize as numbers.  Window handles are not serialized
+  and deserialize as `None`.
+* `stream`: implements `futures_core::Stream` for [`stream::EventStream`].
+
 */
+///Named actions bound to keys and mouse buttons.
+pub mod action;
+///Analog values derived from digital keys and the scroll wheel.
+pub mod axis;
+///Timed sequences of presses, such as fighting-game motion inputs.
+pub mod combo;
+///One ordered sequence of events from every device.
+pub mod context;
+///Per-frame snapshots of keyboard and mouse state.
+pub mod frame;
+///Taps, holds and multi-taps on keys and mouse buttons.
+pub mod interaction;
 ///Provides information about keyboard events.
 pub mod keyboard;
 ///Provides information about mouse events.
 pub mod mouse;
+///Reacting to input the moment it arrives.
+pub mod observer;
+///Recording input and playing it back.
+pub mod record;
+///Scripted input for tests.
+pub mod script;
+///Awaiting input instead of polling for it.
+pub mod stream;
+///Timestamps for input events.
+pub mod timestamp;
+///Keyboards and mice driven by code instead of hardware, for tests.
+pub mod virtual_device;
+
+mod queue;
 
 /// Shows a debug window for testing keyboard input (macOS only).
 ///
@@ -75,15 +112,25 @@ Provides information about the window an event was delivered to.
 * on wasm32, we attach to the global DOM window, and we choose an opaque value arbitrarily for this type.
 * on Linux, we return the wayland surface ID.  No memory management is performed, so values may refer to previous surfaces, etc.
 */
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Window(pub std::ptr::NonNull<std::ffi::c_void>);
 //we don't do anything with it so it's fine to send
 unsafe impl Send for Window {}
 
 #[cfg(target_os = "linux")]
 pub mod linux {
-    pub use crate::keyboard::linux::wl_keyboard_event;
-    pub use crate::mouse::linux::{button_event, motion_event, xdg_toplevel_configure_event};
+    pub use crate::keyboard::linux::ime::zwp_text_input_v3_event;
+    pub use crate::keyboard::linux::time::{
+        zwp_input_timestamps_v1_keyboard_event, zwp_input_timestamps_v1_pointer_event,
+    };
+    pub use crate::keyboard::linux::{
+        wl_keyboard_enter_event, wl_keyboard_event, wl_keyboard_keymap_event,
+        wl_keyboard_leave_event, wl_keyboard_modifiers_event, wl_keyboard_repeat_info_event,
+    };
+    pub use crate::mouse::linux::{
+        button_event, motion_event, pointer_enter_event, pointer_leave_event,
+        xdg_toplevel_configure_event,
+    };
 }
 
 #[cfg(target_os = "windows")]
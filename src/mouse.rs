@@ -25,9 +25,16 @@ pub(crate) use windows as sys;
 pub(crate) use linux as sys;
 
 use crate::Window;
+use crate::context::{InputEvent, next_sequence};
+use crate::observer::{Observers, Subscription};
+use crate::queue::EventQueue;
+use crate::stream::{EventStream, Subscribers};
+use crate::timestamp::Timestamp;
+use crate::virtual_device::VirtualMouse;
 use atomic_float::AtomicF64;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 
 /// Mouse button constant for the left mouse button.
 ///
@@ -82,21 +89,26 @@ pub const MOUSE_BUTTON_MIDDLE: u8 = 2;
 /// }
 /// ```
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MouseWindowLocation {
     pos_x: f64,
     pos_y: f64,
     window_width: f64,
     window_height: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
     window: Option<Window>,
+    timestamp: Timestamp,
+    sequence: u64,
 }
 
 impl MouseWindowLocation {
-    fn new(
+    pub(crate) fn new(
         pos_x: f64,
         pos_y: f64,
         window_width: f64,
         window_height: f64,
         window: Option<Window>,
+        timestamp: Timestamp,
     ) -> Self {
         MouseWindowLocation {
             pos_x,
@@ -104,6 +116,8 @@ impl MouseWindowLocation {
             window_width,
             window_height,
             window,
+            timestamp,
+            sequence: next_sequence(),
         }
     }
 
@@ -174,13 +188,229 @@ impl MouseWindowLocation {
     pub fn window_height(&self) -> f64 {
         self.window_height
     }
+
+    /// Returns the window the mouse is in, if known.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which the mouse moved to this location.
+    ///
+    /// Where the platform stamps events, this is the platform's time; see [`Timestamp`].
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the event's position in the order of all input events.  See [`InputEvent::sequence`].
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/**
+A mouse button being pressed or released.
+
+Obtain these from [`Mouse::drain_button_events`].
+*/
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ButtonEvent {
+    button: u8,
+    down: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    window: Option<Window>,
+    timestamp: Timestamp,
+    sequence: u64,
 }
 
+impl ButtonEvent {
+    pub(crate) fn new(
+        button: u8,
+        down: bool,
+        window: Option<Window>,
+        timestamp: Timestamp,
+    ) -> Self {
+        ButtonEvent {
+            button,
+            down,
+            window,
+            timestamp,
+            sequence: next_sequence(),
+        }
+    }
+
+    /// Returns the button that changed state.  See [`Mouse::button_state`] for the meaning of the value.
+    pub fn button(&self) -> u8 {
+        self.button
+    }
+
+    /// Returns `true` if the button was pressed, or `false` if it was released.
+    pub fn down(&self) -> bool {
+        self.down
+    }
+
+    /// Returns the window that received the event, if known.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which the event occurred.
+    ///
+    /// Where the platform stamps events, this is the platform's time; see [`Timestamp`].
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the event's position in the order of all input events.  See [`InputEvent::sequence`].
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/**
+A movement of the scroll wheel or trackpad.
+
+Obtain these from [`Mouse::events`].  The deltas are in the same units as
+[`Mouse::load_clear_scroll_delta`].
+*/
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScrollEvent {
+    delta_x: f64,
+    delta_y: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    window: Option<Window>,
+    timestamp: Timestamp,
+    sequence: u64,
+}
+
+impl ScrollEvent {
+    pub(crate) fn new(
+        delta_x: f64,
+        delta_y: f64,
+        window: Option<Window>,
+        timestamp: Timestamp,
+    ) -> Self {
+        ScrollEvent {
+            delta_x,
+            delta_y,
+            window,
+            timestamp,
+            sequence: next_sequence(),
+        }
+    }
+
+    /// Returns the horizontal delta.
+    pub fn delta_x(&self) -> f64 {
+        self.delta_x
+    }
+
+    /// Returns the vertical delta.
+    pub fn delta_y(&self) -> f64 {
+        self.delta_y
+    }
+
+    /// Returns the window that received the event, if known.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which the event occurred.
+    ///
+    /// Where the platform stamps events, this is the platform's time; see [`Timestamp`].
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the event's position in the order of all input events.  See [`InputEvent::sequence`].
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/**
+The pointer entering or leaving a window, as delivered by [`InputContext`](crate::context::InputContext).
+
+# Platform specifics
+
+* **Linux**: Reported from `wl_pointer` enter and leave events.
+* **Windows**, **macOS** and **WASM**: Not yet reported.
+*/
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrossingEvent {
+    #[cfg_attr(feature = "serde", serde(skip))]
+    window: Option<Window>,
+    timestamp: Timestamp,
+    sequence: u64,
+}
+
+impl CrossingEvent {
+    pub(crate) fn new(window: Option<Window>, timestamp: Timestamp) -> Self {
+        CrossingEvent {
+            window,
+            timestamp,
+            sequence: next_sequence(),
+        }
+    }
+
+    /// Returns the window the pointer entered or left, if known.
+    pub fn window(&self) -> Option<Window> {
+        self.window
+    }
+
+    /// Returns the time at which the pointer crossed the window's edge.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the event's position in the order of all input events.  See [`InputEvent::sequence`].
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/// An event delivered by [`Mouse::events`].
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MouseEvent {
+    /// A button was pressed or released.
+    Button(ButtonEvent),
+    /// The scroll wheel moved.
+    Scroll(ScrollEvent),
+}
+
+impl MouseEvent {
+    /// Returns the time at which the event occurred.
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            MouseEvent::Button(event) => event.timestamp(),
+            MouseEvent::Scroll(event) => event.timestamp(),
+        }
+    }
+}
+
+/// One entry for every possible button number.
+const BUTTON_COUNT: usize = u8::MAX as usize + 1;
+
 #[derive(Debug)]
-struct Shared {
+pub(crate) struct Shared {
     window: std::sync::Mutex<Option<MouseWindowLocation>>,
-
-    buttons: [AtomicBool; 255],
+    /// Button events received since the last call to [`Mouse::drain_button_events`].
+    events: Mutex<EventQueue<ButtonEvent>>,
+    /// Streams returned from [`Mouse::events`].
+    streams: Subscribers<MouseEvent>,
+    /// Closures registered with [`Mouse::on_event`].
+    observers: Observers<MouseEvent>,
+    /// Streams of [`InputContext`](crate::context::InputContext)s that include this mouse.
+    inputs: Subscribers<InputEvent>,
+
+    buttons: [AtomicBool; BUTTON_COUNT],
+    /// Number of up-to-down transitions of each button.
+    press_counts: [AtomicU32; BUTTON_COUNT],
+    /// Number of down-to-up transitions of each button.
+    release_counts: [AtomicU32; BUTTON_COUNT],
     scroll_delta_x: AtomicF64,
     scroll_delta_y: AtomicF64,
     last_window: AtomicPtr<c_void>,
@@ -189,19 +419,26 @@ impl Shared {
     fn new() -> Self {
         Shared {
             window: std::sync::Mutex::new(None),
-            buttons: [const { AtomicBool::new(false) }; 255],
+            events: Mutex::new(EventQueue::new(Mouse::DEFAULT_EVENT_CAPACITY)),
+            streams: Subscribers::new(),
+            observers: Observers::new(),
+            inputs: Subscribers::new(),
+            buttons: [const { AtomicBool::new(false) }; BUTTON_COUNT],
+            press_counts: [const { AtomicU32::new(0) }; BUTTON_COUNT],
+            release_counts: [const { AtomicU32::new(0) }; BUTTON_COUNT],
             scroll_delta_x: AtomicF64::new(0.0),
             scroll_delta_y: AtomicF64::new(0.0),
             last_window: AtomicPtr::new(std::ptr::null_mut()),
         }
     }
 
-    fn set_window_location(&self, location: MouseWindowLocation) {
+    pub(crate) fn set_window_location(&self, location: MouseWindowLocation) {
         logwise::debuginternal_sync!(
             "Set mouse window location {location}",
             location = logwise::privacy::LogIt(&location)
         );
         *self.window.lock().unwrap() = Some(location);
+        self.inputs.send(&InputEvent::Motion(location));
         self.last_window.store(
             location
                 .window
@@ -210,14 +447,61 @@ impl Shared {
             Ordering::Relaxed,
         )
     }
-    fn set_key_state(&self, key: u8, down: bool, window: *mut c_void) {
+    #[cfg_attr(not(any(test, target_os = "macos")), allow(dead_code))] //other platforms know the event time
+    pub(crate) fn set_key_state(&self, key: u8, down: bool, window: *mut c_void) {
+        self.set_key_state_at(key, down, window, Timestamp::now());
+    }
+
+    /// Like [`Shared::set_key_state`], for an event that occurred at `timestamp`.
+    pub(crate) fn set_key_state_at(
+        &self,
+        key: u8,
+        down: bool,
+        window: *mut c_void,
+        timestamp: Timestamp,
+    ) {
         logwise::debuginternal_sync!("Set mouse key {key} state {down}", key = key, down = down);
-        self.buttons[key as usize].store(down, std::sync::atomic::Ordering::Relaxed);
+        let was = self.buttons[key as usize].swap(down, std::sync::atomic::Ordering::Relaxed);
+        if down && !was {
+            self.press_counts[key as usize].fetch_add(1, Ordering::Relaxed);
+        } else if !down && was {
+            self.release_counts[key as usize].fetch_add(1, Ordering::Relaxed);
+        }
         self.last_window
             .store(window, std::sync::atomic::Ordering::Relaxed);
+        let event = ButtonEvent::new(key, down, NonNull::new(window).map(Window), timestamp);
+        self.events.lock().unwrap().push(event);
+        self.notify(MouseEvent::Button(event));
     }
 
-    fn add_scroll_delta(&self, delta_x: f64, delta_y: f64, window: *mut c_void) {
+    /// Releases every held button, as when the pointer leaves a window or the window loses focus.
+    pub(crate) fn release_all_buttons(&self, window: *mut c_void) {
+        self.release_all_buttons_at(window, Timestamp::now());
+    }
+
+    /// Like [`Shared::release_all_buttons`], for a release that occurred at `timestamp`.
+    pub(crate) fn release_all_buttons_at(&self, window: *mut c_void, timestamp: Timestamp) {
+        logwise::debuginternal_sync!("Releasing all mouse buttons");
+        for button in 0..self.buttons.len() {
+            if self.buttons[button].load(Ordering::Relaxed) {
+                self.set_key_state_at(button as u8, false, window, timestamp);
+            }
+        }
+    }
+
+    #[cfg_attr(not(any(test, target_os = "macos")), allow(dead_code))] //other platforms know the event time
+    pub(crate) fn add_scroll_delta(&self, delta_x: f64, delta_y: f64, window: *mut c_void) {
+        self.add_scroll_delta_at(delta_x, delta_y, window, Timestamp::now());
+    }
+
+    /// Like [`Shared::add_scroll_delta`], for an event that occurred at `timestamp`.
+    pub(crate) fn add_scroll_delta_at(
+        &self,
+        delta_x: f64,
+        delta_y: f64,
+        window: *mut c_void,
+        timestamp: Timestamp,
+    ) {
         logwise::debuginternal_sync!(
             "Add mouse scroll delta {delta_x},{delta_y}",
             delta_x = delta_x,
@@ -229,6 +513,45 @@ impl Shared {
             .fetch_add(delta_y, std::sync::atomic::Ordering::Relaxed);
         self.last_window
             .store(window, std::sync::atomic::Ordering::Relaxed);
+        self.notify(MouseEvent::Scroll(ScrollEvent::new(
+            delta_x,
+            delta_y,
+            NonNull::new(window).map(Window),
+            timestamp,
+        )));
+    }
+
+    fn notify(&self, event: MouseEvent) {
+        self.streams.send(&event);
+        self.observers.notify(&event);
+        self.inputs.send(&match event {
+            MouseEvent::Button(event) => InputEvent::Button(event),
+            MouseEvent::Scroll(event) => InputEvent::Scroll(event),
+        });
+    }
+
+    /// Records that the pointer entered a window.
+    pub(crate) fn pointer_entered(&self, window: *mut c_void, timestamp: Timestamp) {
+        logwise::debuginternal_sync!(
+            "Pointer entered {window}",
+            window = logwise::privacy::LogIt(window)
+        );
+        self.inputs.send(&InputEvent::Enter(CrossingEvent::new(
+            NonNull::new(window).map(Window),
+            timestamp,
+        )));
+    }
+
+    /// Records that the pointer left a window.
+    pub(crate) fn pointer_left(&self, window: *mut c_void, timestamp: Timestamp) {
+        logwise::debuginternal_sync!(
+            "Pointer left {window}",
+            window = logwise::privacy::LogIt(window)
+        );
+        self.inputs.send(&InputEvent::Leave(CrossingEvent::new(
+            NonNull::new(window).map(Window),
+            timestamp,
+        )));
     }
 }
 
@@ -267,10 +590,14 @@ impl Shared {
 #[derive(Debug)]
 pub struct Mouse {
     shared: Arc<Shared>,
-    _sys: sys::PlatformCoalescedMouse,
+    /// `None` for a [virtual device](Mouse::virtual_device).
+    _sys: Option<sys::PlatformCoalescedMouse>,
 }
 
 impl Mouse {
+    /// The number of button events buffered between calls to [`Mouse::drain_button_events`] by default.
+    pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
     /// Creates a new `Mouse` instance that coalesces input from all mice on the system.
     ///
     /// This is the primary way to create a `Mouse` instance. The returned object
@@ -289,10 +616,33 @@ impl Mouse {
         let coalesced = sys::PlatformCoalescedMouse::new(&shared);
         Mouse {
             shared,
-            _sys: coalesced,
+            _sys: Some(coalesced),
         }
     }
 
+    /// Creates a mouse that receives no platform input, only the input injected through the
+    /// returned [`VirtualMouse`].
+    ///
+    /// Use this to test input-dependent code without a window system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::mouse::{MOUSE_BUTTON_LEFT, Mouse};
+    ///
+    /// let device = Mouse::virtual_device();
+    /// device.move_to(10.0, 20.0, None);
+    /// device.press(MOUSE_BUTTON_LEFT);
+    /// assert!(device.mouse().button_state(MOUSE_BUTTON_LEFT));
+    /// assert_eq!(device.mouse().window_pos().unwrap().pos_x(), 10.0);
+    /// ```
+    pub fn virtual_device() -> VirtualMouse {
+        VirtualMouse::new(Mouse {
+            shared: Arc::new(Shared::new()),
+            _sys: None,
+        })
+    }
+
     #[allow(rustdoc::broken_intra_doc_links)] //references to the platform-specific code
     /**
         Returns the [MouseWindowLocation]
@@ -341,6 +691,120 @@ impl Mouse {
         self.shared.buttons[button as usize].load(Ordering::Relaxed)
     }
 
+    /// Returns the number of times `button` has been pressed and released, respectively.
+    ///
+    /// The counts wrap on overflow, so compare them for equality only.
+    pub(crate) fn transition_counts(&self, button: u8) -> (u32, u32) {
+        (
+            self.shared.press_counts[button as usize].load(Ordering::Relaxed),
+            self.shared.release_counts[button as usize].load(Ordering::Relaxed),
+        )
+    }
+
+    pub(crate) fn shared(&self) -> &Arc<Shared> {
+        &self.shared
+    }
+
+    /// Delivers this mouse's events to an [`InputContext`](crate::context::InputContext)'s stream.
+    pub(crate) fn attach_input_stream(&self, stream: &EventStream<InputEvent>) {
+        self.shared.inputs.attach(stream);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn shared_for_test(&self) -> &Shared {
+        &self.shared
+    }
+
+    /// Returns the button events received since the previous call, in the order they occurred.
+    ///
+    /// Unlike [`Mouse::button_state`], this observes every transition, so a click that was pressed and
+    /// released between two calls is reported as two events.
+    ///
+    /// Events are buffered up to a bounded capacity ([`Mouse::DEFAULT_EVENT_CAPACITY`] unless changed
+    /// with [`Mouse::set_event_capacity`]).  If more events arrive before the next drain, the oldest
+    /// events are discarded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::mouse::{Mouse, MOUSE_BUTTON_LEFT};
+    ///
+    /// let mouse = Mouse::coalesced();
+    ///
+    /// // Once per frame:
+    /// for event in mouse.drain_button_events() {
+    ///     if event.button() == MOUSE_BUTTON_LEFT && event.down() {
+    ///         // Handle a click, no matter how short
+    ///     }
+    /// }
+    /// ```
+    pub fn drain_button_events(&self) -> Vec<ButtonEvent> {
+        self.shared.events.lock().unwrap().drain().0
+    }
+
+    /// Calls `callback` with every button and scroll event, as soon as it is received.
+    ///
+    /// The closure runs synchronously on the thread that delivers input, such as the thread running
+    /// your event loop.  It should return quickly, and must not create a new `Mouse`, which would
+    /// deadlock.  Querying this `Mouse` and registering or dropping subscriptions are fine.
+    ///
+    /// The closure is unregistered when the returned [`Subscription`] is dropped.  See the
+    /// [`observer`](crate::observer) module.
+    pub fn on_event(&self, callback: impl Fn(&MouseEvent) + Send + Sync + 'static) -> Subscription {
+        self.shared.observers.subscribe(callback)
+    }
+
+    /// Returns a stream of the button and scroll events received from now on.
+    ///
+    /// See the [`stream`](crate::stream) module.  Each stream buffers up to the event capacity; see
+    /// [`Mouse::set_event_capacity`].
+    pub fn events(&self) -> EventStream<MouseEvent> {
+        let capacity = self.shared.events.lock().unwrap().capacity();
+        self.shared.streams.subscribe(capacity)
+    }
+
+    /// Waits for `button` to be pressed and then released, and returns the release.
+    ///
+    /// Only presses after this call count, even if the future is first polled later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::mouse::{Mouse, MOUSE_BUTTON_LEFT};
+    ///
+    /// async fn click_to_continue(mouse: &Mouse) {
+    ///     mouse.next_click(MOUSE_BUTTON_LEFT).await;
+    /// }
+    /// ```
+    pub fn next_click(&self, button: u8) -> impl Future<Output = ButtonEvent> + Send + 'static {
+        let mut events = self.events();
+        async move {
+            let mut pressed = false;
+            loop {
+                if let MouseEvent::Button(event) = events.next().await {
+                    if event.button() != button {
+                        continue;
+                    }
+                    if event.down() {
+                        pressed = true;
+                    } else if pressed {
+                        return event;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets the maximum number of events buffered between calls to [`Mouse::drain_button_events`].
+    ///
+    /// The same capacity applies separately to streams created afterwards by [`Mouse::events`].
+    ///
+    /// If the buffer currently holds more events than `capacity`, the oldest are discarded.
+    /// A capacity of zero disables buffering.
+    pub fn set_event_capacity(&self, capacity: usize) {
+        self.shared.events.lock().unwrap().set_capacity(capacity);
+    }
+
     /// Returns the accumulated scroll delta and resets it to zero.
     ///
     /// This method is useful for implementing scroll handling in your application.
@@ -420,4 +884,45 @@ mod test {
         assert_sync::<Mouse>();
         assert_unpin::<Mouse>();
     }
+
+    #[test]
+    fn test_drain_button_events() {
+        use crate::mouse::{MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT};
+        let mouse = Mouse::coalesced();
+        let shared = mouse.shared_for_test();
+        shared.set_key_state(MOUSE_BUTTON_LEFT, true, std::ptr::null_mut());
+        shared.set_key_state(MOUSE_BUTTON_LEFT, false, std::ptr::null_mut());
+        shared.set_key_state(MOUSE_BUTTON_RIGHT, true, std::ptr::null_mut());
+        let events = mouse.drain_button_events();
+        let summary: Vec<_> = events.iter().map(|e| (e.button(), e.down())).collect();
+        assert_eq!(
+            summary,
+            [
+                (MOUSE_BUTTON_LEFT, true),
+                (MOUSE_BUTTON_LEFT, false),
+                (MOUSE_BUTTON_RIGHT, true)
+            ]
+        );
+        assert!(events[0].timestamp() <= events[2].timestamp());
+        assert!(mouse.drain_button_events().is_empty());
+
+        mouse.set_event_capacity(1);
+        shared.set_key_state(MOUSE_BUTTON_RIGHT, false, std::ptr::null_mut());
+        shared.set_key_state(MOUSE_BUTTON_LEFT, true, std::ptr::null_mut());
+        assert_eq!(mouse.drain_button_events().len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_location() {
+        use crate::mouse::MouseWindowLocation;
+        use crate::timestamp::Timestamp;
+        let location = MouseWindowLocation::new(1.0, 2.0, 3.0, 4.0, None, Timestamp::now());
+        let json = serde_json::to_string(&location).unwrap();
+        let decoded: MouseWindowLocation = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.pos_x(), 1.0);
+        assert_eq!(decoded.window_height(), 4.0);
+        assert!(decoded.window.is_none());
+        assert_eq!(decoded.timestamp(), location.timestamp());
+    }
 }
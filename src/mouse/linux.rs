@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 use crate::Window;
+use crate::keyboard::linux::time::pointer_time;
 use crate::mouse::{MouseWindowLocation, Shared};
+use crate::timestamp::Timestamp;
 use std::ffi::c_void;
 use std::ptr::NonNull;
 use std::sync::{Arc, Mutex, OnceLock, Weak};
@@ -30,7 +32,7 @@ impl MouseState {
             }
         })
     }
-    fn send_events_if_needed(&mut self) {
+    fn send_events_if_needed(&mut self, timestamp: Timestamp) {
         if let (
             Some(recent_window_width),
             Some(recent_window_height),
@@ -52,6 +54,7 @@ impl MouseState {
                 recent_window_width as f64,
                 recent_window_height as f64,
                 window,
+                timestamp,
             );
             self.apply_all(|shared| {
                 shared.set_window_location(pos);
@@ -65,11 +68,12 @@ Call this to handle [wayland_client::protocol::wl_pointer::Event::Motion].
 
 Call this from your wayland dispatch queue.
 */
-pub fn motion_event(_time: u32, surface_x: f64, surface_y: f64) {
+pub fn motion_event(time: u32, surface_x: f64, surface_y: f64) {
+    let timestamp = pointer_time(time);
     let mut lock = MOUSE_STATE.get_or_init(Mutex::default).lock().unwrap();
     lock.recent_x_pos = Some(surface_x);
     lock.recent_y_pos = Some(surface_y);
-    lock.send_events_if_needed();
+    lock.send_events_if_needed(timestamp);
 }
 
 /**
@@ -81,7 +85,7 @@ pub fn xdg_toplevel_configure_event(width: i32, height: i32) {
     let mut lock = MOUSE_STATE.get_or_init(Mutex::default).lock().unwrap();
     lock.recent_window_width = Some(width);
     lock.recent_window_height = Some(height);
-    lock.send_events_if_needed();
+    lock.send_events_if_needed(Timestamp::now());
 }
 
 /**
@@ -89,7 +93,8 @@ Call this to handle wayland_client::protocol::wl_pointer::Event::Button.
 
 Call this from your wayland dispatch queue.
 */
-pub fn button_event(_time: u32, button: u32, state: u32, window: ObjectId) {
+pub fn button_event(time: u32, button: u32, state: u32, window: ObjectId) {
+    let timestamp = pointer_time(time);
     let down = state != 0;
     //see https://github.com/torvalds/linux/blob/master/include/uapi/linux/input-event-codes.h
     let btn_code = match button {
@@ -113,12 +118,54 @@ pub fn button_event(_time: u32, button: u32, state: u32, window: ObjectId) {
         .lock()
         .unwrap()
         .apply_all(|shared| {
-            shared.set_key_state(btn_code, down, window.protocol_id() as *mut c_void);
+            shared.set_key_state_at(
+                btn_code,
+                down,
+                window.protocol_id() as *mut c_void,
+                timestamp,
+            );
         });
     crate::keyboard::linux::ax::ax_mouse();
 }
 
-pub fn axis_event(_time: u32, axis: u32, value: f64, window: ObjectId) {
+/**
+Call this to handle [wayland_client::protocol::wl_pointer::Event::Enter].
+
+Call this from your wayland dispatch queue.
+*/
+pub fn pointer_enter_event(_serial: u32, surface: ObjectId, surface_x: f64, surface_y: f64) {
+    let mut lock = MOUSE_STATE.get_or_init(Mutex::default).lock().unwrap();
+    let timestamp = Timestamp::now();
+    lock.apply_all(|shared| {
+        shared.pointer_entered(surface.protocol_id() as *mut c_void, timestamp);
+    });
+    lock.recent_window = Some(surface);
+    lock.recent_x_pos = Some(surface_x);
+    lock.recent_y_pos = Some(surface_y);
+    lock.send_events_if_needed(Timestamp::now());
+}
+
+/**
+Call this to handle [wayland_client::protocol::wl_pointer::Event::Leave].
+
+Every held button is released, since the compositor will not tell us about releases outside our surfaces.
+
+Call this from your wayland dispatch queue.
+*/
+pub fn pointer_leave_event(_serial: u32, surface: ObjectId) {
+    MOUSE_STATE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap()
+        .apply_all(|shared| {
+            let window = surface.protocol_id() as *mut c_void;
+            shared.pointer_left(window, Timestamp::now());
+            shared.release_all_buttons(window);
+        });
+}
+
+pub fn axis_event(time: u32, axis: u32, value: f64, window: ObjectId) {
+    let timestamp = pointer_time(time);
     if axis == 0 {
         //vertical
         MOUSE_STATE
@@ -126,7 +173,12 @@ pub fn axis_event(_time: u32, axis: u32, value: f64, window: ObjectId) {
             .lock()
             .unwrap()
             .apply_all(|shared| {
-                shared.add_scroll_delta(0.0, value, window.protocol_id() as *mut c_void);
+                shared.add_scroll_delta_at(
+                    0.0,
+                    value,
+                    window.protocol_id() as *mut c_void,
+                    timestamp,
+                );
             })
     } else {
         //horizontal
@@ -135,7 +187,12 @@ pub fn axis_event(_time: u32, axis: u32, value: f64, window: ObjectId) {
             .lock()
             .unwrap()
             .apply_all(|shared| {
-                shared.add_scroll_delta(value, 0.0, window.protocol_id() as *mut c_void);
+                shared.add_scroll_delta_at(
+                    value,
+                    0.0,
+                    window.protocol_id() as *mut c_void,
+                    timestamp,
+                );
             })
     }
 }
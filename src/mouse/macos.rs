@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MPL-2.0
 use crate::Window;
 use crate::mouse::{MouseWindowLocation, Shared};
+use crate::timestamp::Timestamp;
 use std::ffi::c_void;
 use std::ptr::NonNull;
 use std::sync::{Arc, Weak};
@@ -38,6 +39,7 @@ extern "C" fn raw_input_mouse_move(
                 window_width,
                 window_height,
                 window,
+                Timestamp::now(),
             );
             shared.set_window_location(loc);
         }
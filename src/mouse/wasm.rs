@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 use crate::Window;
-use crate::keyboard::wasm::ARBITRARY_WINDOW_PTR;
+use crate::keyboard::wasm::{ARBITRARY_WINDOW_PTR, event_timestamp};
 use crate::mouse::MouseWindowLocation;
 use std::ptr::NonNull;
 use std::sync::Arc;
@@ -22,6 +22,7 @@ pub(super) struct PlatformCoalescedMouse {
     _mousedown_listener: JsValue,
     _mouseup_listener: JsValue,
     _wheel_listener: JsValue,
+    _blur_listener: JsValue,
 }
 
 unsafe impl Send for PlatformCoalescedMouse {}
@@ -36,6 +37,7 @@ impl PlatformCoalescedMouse {
         let weak_down = weak.clone();
         let weak_up = weak.clone();
         let weak_wheel = weak.clone();
+        let weak_blur = weak.clone();
 
         // Mouse move callback
         let mousemove_callback = Closure::wrap(Box::new(move |event: MouseEvent| {
@@ -59,6 +61,7 @@ impl PlatformCoalescedMouse {
                     width,
                     height,
                     window,
+                    event_timestamp(&event),
                 ));
             }
         }) as Box<dyn FnMut(MouseEvent)>);
@@ -72,10 +75,11 @@ impl PlatformCoalescedMouse {
 
         let mousedown_callback = Closure::wrap(Box::new(move |event: MouseEvent| {
             if let Some(shared) = weak_down.upgrade() {
-                shared.set_key_state(
+                shared.set_key_state_at(
                     js_button_to_rust(event.button()),
                     true,
                     ARBITRARY_WINDOW_PTR,
+                    event_timestamp(&event),
                 );
             }
         }) as Box<dyn FnMut(MouseEvent)>);
@@ -88,10 +92,11 @@ impl PlatformCoalescedMouse {
 
         let mouseup_callback = Closure::wrap(Box::new(move |event: MouseEvent| {
             if let Some(shared) = weak_up.upgrade() {
-                shared.set_key_state(
+                shared.set_key_state_at(
                     js_button_to_rust(event.button()),
                     false,
                     ARBITRARY_WINDOW_PTR,
+                    event_timestamp(&event),
                 );
             }
         }) as Box<dyn FnMut(MouseEvent)>);
@@ -110,18 +115,35 @@ impl PlatformCoalescedMouse {
             };
 
             if let Some(shared) = weak_wheel.upgrade() {
-                shared.add_scroll_delta(x as f64, y as f64, ARBITRARY_WINDOW_PTR);
+                shared.add_scroll_delta_at(
+                    x as f64,
+                    y as f64,
+                    ARBITRARY_WINDOW_PTR,
+                    event_timestamp(&event),
+                );
             }
         }) as Box<dyn FnMut(WheelEvent)>);
         document
             .add_event_listener_with_callback("wheel", wheel_callback.as_ref().unchecked_ref())
             .expect("Can't add event listener");
 
+        //a button released outside the page is never reported, so release held buttons on blur
+        let blur_callback = Closure::wrap(Box::new(move || {
+            if let Some(shared) = weak_blur.upgrade() {
+                shared.release_all_buttons(ARBITRARY_WINDOW_PTR);
+            }
+        }) as Box<dyn FnMut()>);
+        web_sys::window()
+            .expect("no global window exists")
+            .add_event_listener_with_callback("blur", blur_callback.as_ref().unchecked_ref())
+            .expect("Can't add event listener");
+
         Self {
             _mouse_listener: mousemove_callback.into_js_value(),
             _mousedown_listener: mousedown_callback.into_js_value(),
             _mouseup_listener: mouseup_callback.into_js_value(),
             _wheel_listener: wheel_callback.into_js_value(),
+            _blur_listener: blur_callback.into_js_value(),
         }
     }
 }
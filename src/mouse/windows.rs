@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MPL-2.0
 use crate::Window;
+use crate::keyboard::windows::message_time;
 use crate::mouse::{MouseWindowLocation, Shared};
 use std::mem::MaybeUninit;
 use std::ptr::NonNull;
@@ -7,9 +8,9 @@ use std::sync::{Arc, Mutex, OnceLock, Weak};
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
 use windows::Win32::Graphics::Gdi::ClientToScreen;
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetClientRect, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEHWHEEL,
-    WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_XBUTTONDOWN, WM_XBUTTONUP,
-    XBUTTON1, XBUTTON2,
+    GetClientRect, WM_KILLFOCUS, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP,
+    WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_XBUTTONDOWN,
+    WM_XBUTTONUP, XBUTTON1, XBUTTON2,
 };
 
 fn get_x_lparam(lparam: LPARAM) -> i16 {
@@ -93,6 +94,7 @@ pub(crate) fn window_proc(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM
                 rect.right as f64,
                 rect.bottom as f64,
                 window,
+                message_time(),
             );
 
             apply_all(|shared| {
@@ -101,38 +103,44 @@ pub(crate) fn window_proc(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM
             LRESULT(0)
         }
         msg if msg == WM_LBUTTONDOWN => {
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(0, true, hwnd.0);
+                shared.set_key_state_at(0, true, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_LBUTTONUP => {
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(0, false, hwnd.0);
+                shared.set_key_state_at(0, false, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_RBUTTONDOWN => {
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(1, true, hwnd.0);
+                shared.set_key_state_at(1, true, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_RBUTTONUP => {
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(1, false, hwnd.0);
+                shared.set_key_state_at(1, false, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_MBUTTONDOWN => {
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(2, true, hwnd.0);
+                shared.set_key_state_at(2, true, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_MBUTTONUP => {
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(2, false, hwnd.0);
+                shared.set_key_state_at(2, false, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
@@ -145,8 +153,9 @@ pub(crate) fn window_proc(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM
                     unimplemented!("Unknown xbutton {:?}", xbutton)
                 }
             };
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(key, true, hwnd.0);
+                shared.set_key_state_at(key, true, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
@@ -159,27 +168,37 @@ pub(crate) fn window_proc(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM
                     unimplemented!("Unknown xbutton {:?}", xbutton)
                 }
             };
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.set_key_state(key, false, hwnd.0);
+                shared.set_key_state_at(key, false, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_MOUSEWHEEL => {
             //todo: should this be scaled in some way?
             let delta = get_wheel_delta_wparam(w_param);
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.add_scroll_delta(0.0, delta as f64, hwnd.0);
+                shared.add_scroll_delta_at(0.0, delta as f64, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
         msg if msg == WM_MOUSEHWHEEL => {
             //todo: should this be scaled in some way?
             let delta = get_wheel_delta_wparam(w_param);
+            let timestamp = message_time();
             apply_all(|shared| {
-                shared.add_scroll_delta(delta as f64, 0.0, hwnd.0);
+                shared.add_scroll_delta_at(delta as f64, 0.0, hwnd.0, timestamp);
             });
             LRESULT(0)
         }
+        msg if msg == WM_KILLFOCUS => {
+            apply_all(|shared| {
+                shared.release_all_buttons(hwnd.0);
+            });
+            //let the keyboard and the application see this message too
+            LRESULT(1)
+        }
 
         _ => LRESULT(1),
     }
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Reacting to input the moment it arrives.
+//!
+//! [`Keyboard::on_event`](crate::keyboard::Keyboard::on_event) and
+//! [`Mouse::on_event`](crate::mouse::Mouse::on_event) register a closure that is called for every
+//! event, on the thread that delivers input to the crate, before the call that delivered it returns.
+//! They return a [`Subscription`](crate::observer::Subscription); the closure is unregistered when
+//! the subscription is dropped.
+//!
+//! On Linux under Wayland, the crate synthesizes key repeats on a background thread, so closures
+//! and stream wakers are called on that thread for repeats.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//!
+//! let keyboard = Keyboard::coalesced();
+//! let subscription = keyboard.on_event(|event| {
+//!     if event.key() == KeyboardKey::Escape && event.down() {
+//!         // cancel the drag immediately
+//!     }
+//! });
+//! // Later, stop observing:
+//! drop(subscription);
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+type Callback<E> = Arc<dyn Fn(&E) + Send + Sync>;
+
+struct List<E> {
+    next_id: u64,
+    callbacks: Vec<(u64, Callback<E>)>,
+}
+
+/// The closures registered on a device.
+pub(crate) struct Observers<E> {
+    list: Arc<Mutex<List<E>>>,
+}
+
+impl<E> std::fmt::Debug for Observers<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Observers")
+            .field("len", &self.list.lock().unwrap().callbacks.len())
+            .finish()
+    }
+}
+
+impl<E: 'static> Observers<E> {
+    pub(crate) fn new() -> Self {
+        Observers {
+            list: Arc::new(Mutex::new(List {
+                next_id: 0,
+                callbacks: Vec::new(),
+            })),
+        }
+    }
+
+    pub(crate) fn subscribe(&self, callback: impl Fn(&E) + Send + Sync + 'static) -> Subscription {
+        let id = {
+            let mut list = self.list.lock().unwrap();
+            let id = list.next_id;
+            list.next_id += 1;
+            list.callbacks.push((id, Arc::new(callback)));
+            id
+        };
+        let list = Arc::downgrade(&self.list);
+        Subscription {
+            unsubscribe: Some(Box::new(move || {
+                if let Some(list) = list.upgrade() {
+                    list.lock().unwrap().callbacks.retain(|(i, _)| *i != id);
+                }
+            })),
+        }
+    }
+
+    /// Calls every registered closure with `event`.
+    pub(crate) fn notify(&self, event: &E) {
+        //call outside the lock, so that closures may subscribe and unsubscribe
+        let callbacks: Vec<Callback<E>> = self
+            .list
+            .lock()
+            .unwrap()
+            .callbacks
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+}
+
+/**
+Keeps a closure registered with `on_event`.  Dropping the subscription unregisters the closure.
+
+A closure that is running when its subscription is dropped finishes, but is not called again.
+*/
+#[must_use = "the closure is unregistered when the subscription is dropped"]
+pub struct Subscription {
+    unsubscribe: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription").finish_non_exhaustive()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{MOUSE_BUTTON_LEFT, Mouse, MouseEvent};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_on_event_until_dropped() {
+        let keyboard = Keyboard::coalesced();
+        let shared = keyboard.shared_for_test();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let subscription = keyboard.on_event({
+            let seen = seen.clone();
+            move |event| seen.lock().unwrap().push((event.key(), event.down()))
+        });
+        shared.set_key_state(KeyboardKey::F, true, std::ptr::null_mut());
+        shared.set_key_state(KeyboardKey::F, false, std::ptr::null_mut());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [(KeyboardKey::F, true), (KeyboardKey::F, false)]
+        );
+        drop(subscription);
+        shared.set_key_state(KeyboardKey::G, true, std::ptr::null_mut());
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_mouse_on_event_reentrant() {
+        let mouse = Arc::new(Mouse::coalesced());
+        let count = Arc::new(AtomicUsize::new(0));
+        let inner = Arc::new(Mutex::new(None));
+        let _subscription = mouse.on_event({
+            let (mouse, count, inner) = (mouse.clone(), count.clone(), inner.clone());
+            move |event| {
+                if let MouseEvent::Button(event) = event {
+                    if event.down() {
+                        //closures may query the device and register more closures
+                        assert!(mouse.button_state(event.button()));
+                        let count = count.clone();
+                        *inner.lock().unwrap() = Some(mouse.on_event(move |_| {
+                            count.fetch_add(1, Ordering::Relaxed);
+                        }));
+                    }
+                }
+            }
+        });
+        let shared = mouse.shared_for_test();
+        shared.set_key_state(MOUSE_BUTTON_LEFT, true, std::ptr::null_mut());
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+        shared.add_scroll_delta(0.0, 1.0, std::ptr::null_mut());
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        inner.lock().unwrap().take();
+        shared.add_scroll_delta(0.0, 1.0, std::ptr::null_mut());
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: MPL-2.0
+use std::collections::VecDeque;
+
+/// Bounded buffer of events awaiting a drain, such as
+/// [`Keyboard::drain_events`](crate::keyboard::Keyboard::drain_events).
+#[derive(Debug)]
+pub(crate) struct EventQueue<T> {
+    events: VecDeque<T>,
+    capacity: usize,
+    overflowed: bool,
+}
+
+impl<T> EventQueue<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        EventQueue {
+            events: VecDeque::new(),
+            capacity,
+            overflowed: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, event: T) {
+        if self.capacity == 0 {
+            self.overflowed = true;
+            return;
+        }
+        while self.events.len() >= self.capacity {
+            self.events.pop_front();
+            self.overflowed = true;
+        }
+        self.events.push_back(event);
+    }
+
+    /// Removes and returns the oldest buffered event.
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        self.events.pop_front()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the buffered events, and whether any were discarded since the previous drain.
+    pub(crate) fn drain(&mut self) -> (Vec<T>, bool) {
+        let overflowed = std::mem::replace(&mut self.overflowed, false);
+        (self.events.drain(..).collect(), overflowed)
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.events.len() > capacity {
+            self.events.pop_front();
+            self.overflowed = true;
+        }
+    }
+}
@@ -0,0 +1,1066 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Recording input and playing it back.
+//!
+//! A [`Recorder`](crate::record::Recorder) captures every event delivered to a keyboard and mouse,
+//! including pointer motion, window sizes and focus changes, into a
+//! [`Recording`](crate::record::Recording).  Recordings can be saved to a file and loaded again, and
+//! a [`Player`](crate::record::Player) delivers a recording to a keyboard and mouse as if the
+//! platform had reported it, at the original speed or faster or slower.
+//!
+//! Attach a recording to a bug report to reproduce the input exactly, or replay one in a test to
+//! exercise input-dependent code without a display.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::keyboard::Keyboard;
+//! use app_input::mouse::Mouse;
+//! use app_input::record::{Player, Recorder, Recording, RecordingFormat};
+//! use std::time::Duration;
+//!
+//! let keyboard = Keyboard::coalesced();
+//! let mouse = Mouse::coalesced();
+//! let recorder = Recorder::new(&keyboard, &mouse);
+//! // ... run the application ...
+//! let recording = recorder.finish();
+//! let text = recording.to_json_lines();
+//!
+//! let replayed = Recording::from_json_lines(&text).unwrap();
+//! let mut player = Player::new(&replayed, &keyboard, &mouse);
+//! // Deliver everything that happened in the first second:
+//! player.advance_to(Duration::from_secs(1));
+//! ```
+//!
+//! # File formats
+//!
+//! Recordings are stored as JSON Lines, which is easy to read and edit, or in a compact binary
+//! format.  [`Recording::load`](crate::record::Recording::load) detects which.  Both store the
+//! events in order, each with its time in nanoseconds, so the absolute time of the recording is
+//! not preserved.  Windows are stored as the integer value of their [`Window`](crate::Window)
+//! handle.
+//!
+//! Both formats carry a version number, currently 1.  Readers reject versions they don't know.
+//!
+//! ## JSON Lines
+//!
+//! The first line is a header:
+//!
+//! ```text
+//! {"format":"app_input-recording","version":1}
+//! ```
+//!
+//! Every following line is one event, an object with these fields:
+//!
+//! | Field | Events | Value |
+//! |-------|--------|-------|
+//! | `t` | all | Nanoseconds since the first event |
+//! | `type` | all | `"key"`, `"button"`, `"motion"`, `"scroll"`, `"enter"`, `"leave"` or `"focus"` |
+//! | `window` | all | The window handle; omitted if unknown |
+//! | `key` | key | The key's name, as formatted by [`KeyboardKey`](crate::keyboard::key::KeyboardKey)'s `Display` |
+//! | `down` | key, button | `true` for a press, `false` for a release |
+//! | `repeat` | key | `true` for an auto-repeat |
+//! | `button` | button | The button number, as in [`Mouse::button_state`](crate::mouse::Mouse::button_state) |
+//! | `x`, `y`, `width`, `height` | motion | The pointer position and window size, in points |
+//! | `dx`, `dy` | scroll | The scroll delta |
+//! | `focused` | focus | `true` if the window gained focus |
+//!
+//! Unknown fields are ignored, and blank lines are skipped.
+//!
+//! ## Binary
+//!
+//! The file starts with the four bytes `AIRC` and the version as a little-endian `u32`.  Each event
+//! follows as:
+//!
+//! 1. A type byte: 0 key, 1 button, 2 motion, 3 scroll, 4 enter, 5 leave, 6 focus.
+//! 2. The nanoseconds since the first event, as an unsigned LEB128 integer.
+//! 3. The window handle as an unsigned LEB128 integer, or 0 if unknown.
+//! 4. The fields for the type:
+//!    * key: a flags byte (bit 0 down, bit 1 repeat), a length byte, and that many bytes of the
+//!      UTF-8 key name
+//!    * button: the button byte and a down byte (0 or 1)
+//!    * motion: `x`, `y`, `width` and `height` as little-endian `f64`s
+//!    * scroll: `dx` and `dy` as little-endian `f64`s
+//!    * focus: a focused byte (0 or 1)
+//!    * enter and leave: nothing
+
+use crate::Window;
+use crate::context::InputEvent;
+use crate::keyboard::key::KeyboardKey;
+use crate::keyboard::{self, FocusEvent, KeyEvent, Keyboard};
+use crate::mouse::{self, ButtonEvent, CrossingEvent, Mouse, MouseWindowLocation, ScrollEvent};
+use crate::stream::EventStream;
+use crate::timestamp::Timestamp;
+use std::ffi::c_void;
+use std::fmt::{Display, Formatter, Write as _};
+use std::path::Path;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::time::Duration;
+
+const JSON_FORMAT: &str = "app_input-recording";
+const BINARY_MAGIC: &[u8; 4] = b"AIRC";
+
+/// The error returned when a recording cannot be loaded.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RecordingError {
+    /// The recording could not be read.
+    Io(std::io::Error),
+    /// The recording was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// Part of the recording could not be parsed.
+    Malformed {
+        /// The 1-based line number for JSON Lines, or the byte offset for the binary format.
+        position: usize,
+        /// A description of the problem.
+        message: String,
+    },
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(e) => write!(f, "can't read recording: {e}"),
+            RecordingError::UnsupportedVersion(version) => {
+                write!(f, "unsupported recording version {version}")
+            }
+            RecordingError::Malformed { position, message } => {
+                write!(f, "at {position}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RecordingError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(e: std::io::Error) -> Self {
+        RecordingError::Io(e)
+    }
+}
+
+/// A file format for [`Recording::save`].  See the [module documentation](crate::record).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RecordingFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// The compact binary format.
+    Binary,
+}
+
+/// A sequence of input events, with their times.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    events: Vec<InputEvent>,
+}
+
+impl Recording {
+    /// The version of the file formats written by this crate.
+    pub const VERSION: u32 = 1;
+
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /// Appends an event.  Events should be appended in the order they occurred.
+    pub fn push(&mut self, event: InputEvent) {
+        self.events.push(event);
+    }
+
+    /// Returns the events in the order they occurred.
+    pub fn events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Returns the time from the first event to the last.
+    pub fn duration(&self) -> Duration {
+        match (self.events.first(), self.events.last()) {
+            (Some(first), Some(last)) => last.timestamp() - first.timestamp(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns the time of each event relative to the first.
+    fn offsets(&self) -> impl Iterator<Item = (Duration, &InputEvent)> {
+        let first = self.events.first().map(InputEvent::timestamp);
+        self.events
+            .iter()
+            .map(move |event| (event.timestamp() - first.unwrap(), event))
+    }
+
+    /// Formats the recording as JSON Lines.
+    pub fn to_json_lines(&self) -> String {
+        let mut out = format!(
+            "{{\"format\":\"{JSON_FORMAT}\",\"version\":{}}}\n",
+            Recording::VERSION
+        );
+        for (offset, event) in self.offsets() {
+            write_json_event(&mut out, offset, event);
+        }
+        out
+    }
+
+    /// Parses a recording from JSON Lines.
+    pub fn from_json_lines(text: &str) -> Result<Self, RecordingError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let malformed = |position, message| RecordingError::Malformed { position, message };
+        let (position, header) = lines
+            .next()
+            .ok_or_else(|| malformed(1, "missing header".to_string()))?;
+        let header = Fields::parse(header).map_err(|m| malformed(position, m))?;
+        if header.str("format").ok() != Some(JSON_FORMAT) {
+            return Err(malformed(position, "not an input recording".to_string()));
+        }
+        let version = header.u64("version").map_err(|m| malformed(position, m))?;
+        let version = u32::try_from(version)
+            .map_err(|_| malformed(position, format!("invalid version {version}")))?;
+        if version != Recording::VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+        let mut recording = Recording::new();
+        for (position, line) in lines {
+            let event = Fields::parse(line)
+                .and_then(|fields| read_json_event(&fields))
+                .map_err(|m| malformed(position, m))?;
+            recording.push(event);
+        }
+        Ok(recording)
+    }
+
+    /// Encodes the recording in the binary format.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = BINARY_MAGIC.to_vec();
+        out.extend_from_slice(&Recording::VERSION.to_le_bytes());
+        for (offset, event) in self.offsets() {
+            write_binary_event(&mut out, offset, event);
+        }
+        out
+    }
+
+    /// Decodes a recording in the binary format.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, RecordingError> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(4).ok() != Some(BINARY_MAGIC.as_slice()) {
+            return Err(reader.malformed("not an input recording"));
+        }
+        let version = u32::from_le_bytes(reader.array()?);
+        if version != Recording::VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+        let mut recording = Recording::new();
+        while reader.position < bytes.len() {
+            let event = read_binary_event(&mut reader)?;
+            recording.push(event);
+        }
+        Ok(recording)
+    }
+
+    /// Writes the recording to a file.
+    pub fn save(&self, path: impl AsRef<Path>, format: RecordingFormat) -> std::io::Result<()> {
+        match format {
+            RecordingFormat::JsonLines => std::fs::write(path, self.to_json_lines()),
+            RecordingFormat::Binary => std::fs::write(path, self.to_binary()),
+        }
+    }
+
+    /// Reads a recording from a file in either format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let bytes = std::fs::read(path)?;
+        if bytes.starts_with(BINARY_MAGIC) {
+            return Recording::from_binary(&bytes);
+        }
+        let text = String::from_utf8(bytes).map_err(|e| RecordingError::Malformed {
+            position: 1,
+            message: e.to_string(),
+        })?;
+        Recording::from_json_lines(&text)
+    }
+}
+
+fn window_id(window: Option<Window>) -> u64 {
+    window.map_or(0, |window| window.0.as_ptr() as usize as u64)
+}
+
+fn window_from_id(id: u64) -> Option<Window> {
+    NonNull::new(id as usize as *mut c_void).map(Window)
+}
+
+fn write_json_event(out: &mut String, offset: Duration, event: &InputEvent) {
+    let kind = match event {
+        InputEvent::Key(_) => "key",
+        InputEvent::Button(_) => "button",
+        InputEvent::Motion(_) => "motion",
+        InputEvent::Scroll(_) => "scroll",
+        InputEvent::Enter(_) => "enter",
+        InputEvent::Leave(_) => "leave",
+        InputEvent::Focus(_) => "focus",
+    };
+    write!(out, "{{\"t\":{},\"type\":\"{kind}\"", offset.as_nanos()).unwrap();
+    if let Some(window) = event.window() {
+        write!(out, ",\"window\":{}", window_id(Some(window))).unwrap();
+    }
+    match event {
+        InputEvent::Key(e) => {
+            out.push_str(",\"key\":");
+            write_json_string(out, &e.key().to_string());
+            write!(out, ",\"down\":{},\"repeat\":{}", e.down(), e.repeat()).unwrap();
+        }
+        InputEvent::Button(e) => {
+            write!(out, ",\"button\":{},\"down\":{}", e.button(), e.down()).unwrap()
+        }
+        InputEvent::Motion(e) => write!(
+            out,
+            ",\"x\":{},\"y\":{},\"width\":{},\"height\":{}",
+            e.pos_x(),
+            e.pos_y(),
+            e.window_width(),
+            e.window_height()
+        )
+        .unwrap(),
+        InputEvent::Scroll(e) => {
+            write!(out, ",\"dx\":{},\"dy\":{}", e.delta_x(), e.delta_y()).unwrap()
+        }
+        InputEvent::Enter(_) | InputEvent::Leave(_) => {}
+        InputEvent::Focus(e) => write!(out, ",\"focused\":{}", e.focused()).unwrap(),
+    }
+    out.push_str("}\n");
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn read_json_event(fields: &Fields) -> Result<InputEvent, String> {
+    let timestamp = Timestamp::from_duration_since_epoch(Duration::from_nanos(fields.u64("t")?));
+    let window = match fields.get("window") {
+        Some(_) => window_from_id(fields.u64("window")?),
+        None => None,
+    };
+    let event = match fields.str("type")? {
+        "key" => {
+            let key: KeyboardKey = fields.str("key")?.parse().map_err(|e| format!("{e}"))?;
+            InputEvent::Key(KeyEvent::new(
+                key,
+                fields.bool("down")?,
+                fields.bool("repeat")?,
+                window,
+                timestamp,
+            ))
+        }
+        "button" => {
+            let button = u8::try_from(fields.u64("button")?)
+                .map_err(|_| "button out of range".to_string())?;
+            InputEvent::Button(ButtonEvent::new(
+                button,
+                fields.bool("down")?,
+                window,
+                timestamp,
+            ))
+        }
+        "motion" => InputEvent::Motion(MouseWindowLocation::new(
+            fields.f64("x")?,
+            fields.f64("y")?,
+            fields.f64("width")?,
+            fields.f64("height")?,
+            window,
+            timestamp,
+        )),
+        "scroll" => InputEvent::Scroll(ScrollEvent::new(
+            fields.f64("dx")?,
+            fields.f64("dy")?,
+            window,
+            timestamp,
+        )),
+        "enter" => InputEvent::Enter(CrossingEvent::new(window, timestamp)),
+        "leave" => InputEvent::Leave(CrossingEvent::new(window, timestamp)),
+        "focus" => InputEvent::Focus(FocusEvent::new(fields.bool("focused")?, window, timestamp)),
+        other => return Err(format!("unknown event type {other:?}")),
+    };
+    Ok(event)
+}
+
+/// A JSON value, as far as recordings need them.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    /// The number as written, so that integers keep their full precision.
+    Number(String),
+    Bool(bool),
+    Null,
+}
+
+/// The fields of a flat JSON object.
+#[derive(Debug)]
+struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: line.chars().peekable(),
+        };
+        let fields = parser.object()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(Fields(fields)),
+            Some(c) => Err(format!("unexpected {c:?} after object")),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    fn require(&self, name: &str) -> Result<&Value, String> {
+        self.get(name)
+            .ok_or_else(|| format!("missing field {name:?}"))
+    }
+
+    fn str(&self, name: &str) -> Result<&str, String> {
+        match self.require(name)? {
+            Value::String(s) => Ok(s),
+            _ => Err(format!("field {name:?} is not a string")),
+        }
+    }
+
+    fn bool(&self, name: &str) -> Result<bool, String> {
+        match self.require(name)? {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(format!("field {name:?} is not a boolean")),
+        }
+    }
+
+    fn u64(&self, name: &str) -> Result<u64, String> {
+        match self.require(name)? {
+            Value::Number(n) => n
+                .parse()
+                .map_err(|_| format!("field {name:?} is not an unsigned integer")),
+            _ => Err(format!("field {name:?} is not a number")),
+        }
+    }
+
+    fn f64(&self, name: &str) -> Result<f64, String> {
+        match self.require(name)? {
+            Value::Number(n) => n
+                .parse()
+                .map_err(|_| format!("field {name:?} is not a number")),
+            _ => Err(format!("field {name:?} is not a number")),
+        }
+    }
+}
+
+/// A parser for the flat JSON objects in a recording.  Nested arrays and objects are not supported.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected {expected:?}, found {c:?}")),
+            None => Err(format!("expected {expected:?}, found end of line")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Vec<(String, Value)>, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.chars.next_if_eq(&'}').is_some() {
+            return Ok(fields);
+        }
+        loop {
+            self.expect('"')?;
+            let name = self.string()?;
+            self.expect(':')?;
+            fields.push((name, self.value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => {}
+                Some('}') => return Ok(fields),
+                Some(c) => return Err(format!("expected ',' or '}}', found {c:?}")),
+                None => return Err("unterminated object".to_string()),
+            }
+        }
+    }
+
+    /// Parses the rest of a string whose opening quote has been consumed.
+    fn string(&mut self) -> Result<String, String> {
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.chars.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex: String = self.chars.by_ref().take(4).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| format!("invalid escape \\u{hex}"))?
+                        }
+                        other => return Err(format!("invalid escape {other:?}")),
+                    };
+                    s.push(c);
+                }
+                Some(c) => s.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('"') => {
+                self.chars.next();
+                Ok(Value::String(self.string()?))
+            }
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+                {
+                    number.push(c);
+                }
+                Ok(Value::Number(number))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" => Ok(Value::Null),
+                    _ => Err(format!("unexpected {word:?}")),
+                }
+            }
+            Some(c) => Err(format!("unexpected {c:?}")),
+            None => Err("expected a value, found end of line".to_string()),
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_binary_event(out: &mut Vec<u8>, offset: Duration, event: &InputEvent) {
+    let tag = match event {
+        InputEvent::Key(_) => 0,
+        InputEvent::Button(_) => 1,
+        InputEvent::Motion(_) => 2,
+        InputEvent::Scroll(_) => 3,
+        InputEvent::Enter(_) => 4,
+        InputEvent::Leave(_) => 5,
+        InputEvent::Focus(_) => 6,
+    };
+    out.push(tag);
+    write_varint(out, offset.as_nanos() as u64);
+    write_varint(out, window_id(event.window()));
+    match event {
+        InputEvent::Key(e) => {
+            out.push(e.down() as u8 | (e.repeat() as u8) << 1);
+            let name = e.key().to_string();
+            out.push(name.len() as u8);
+            out.extend_from_slice(name.as_bytes());
+        }
+        InputEvent::Button(e) => out.extend_from_slice(&[e.button(), e.down() as u8]),
+        InputEvent::Motion(e) => {
+            for value in [e.pos_x(), e.pos_y(), e.window_width(), e.window_height()] {
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        InputEvent::Scroll(e) => {
+            out.extend_from_slice(&e.delta_x().to_le_bytes());
+            out.extend_from_slice(&e.delta_y().to_le_bytes());
+        }
+        InputEvent::Enter(_) | InputEvent::Leave(_) => {}
+        InputEvent::Focus(e) => out.push(e.focused() as u8),
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn malformed(&self, message: &str) -> RecordingError {
+        RecordingError::Malformed {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], RecordingError> {
+        let end = self.position + len;
+        let slice = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| self.malformed("unexpected end of recording"))?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], RecordingError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn byte(&mut self) -> Result<u8, RecordingError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, RecordingError> {
+        Ok(self.byte()? != 0)
+    }
+
+    fn f64(&mut self) -> Result<f64, RecordingError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn varint(&mut self) -> Result<u64, RecordingError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.malformed("integer too long"))
+    }
+}
+
+fn read_binary_event(reader: &mut Reader<'_>) -> Result<InputEvent, RecordingError> {
+    let tag = reader.byte()?;
+    let offset = Duration::from_nanos(reader.varint()?);
+    let timestamp = Timestamp::from_duration_since_epoch(offset);
+    let window = window_from_id(reader.varint()?);
+    let event = match tag {
+        0 => {
+            let flags = reader.byte()?;
+            let len = reader.byte()? as usize;
+            let name = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| reader.malformed("key name is not UTF-8"))?;
+            let key: KeyboardKey = name.parse().map_err(|_| reader.malformed("unknown key"))?;
+            InputEvent::Key(KeyEvent::new(
+                key,
+                flags & 1 != 0,
+                flags & 2 != 0,
+                window,
+                timestamp,
+            ))
+        }
+        1 => {
+            let button = reader.byte()?;
+            InputEvent::Button(ButtonEvent::new(button, reader.bool()?, window, timestamp))
+        }
+        2 => InputEvent::Motion(MouseWindowLocation::new(
+            reader.f64()?,
+            reader.f64()?,
+            reader.f64()?,
+            reader.f64()?,
+            window,
+            timestamp,
+        )),
+        3 => InputEvent::Scroll(ScrollEvent::new(
+            reader.f64()?,
+            reader.f64()?,
+            window,
+            timestamp,
+        )),
+        4 => InputEvent::Enter(CrossingEvent::new(window, timestamp)),
+        5 => InputEvent::Leave(CrossingEvent::new(window, timestamp)),
+        6 => InputEvent::Focus(FocusEvent::new(reader.bool()?, window, timestamp)),
+        _ => return Err(reader.malformed("unknown event type")),
+    };
+    Ok(event)
+}
+
+/**
+Captures the events delivered to a keyboard and mouse.
+
+Events are buffered without limit from the moment the recorder is created until
+[`Recorder::finish`].
+*/
+#[derive(Debug)]
+pub struct Recorder {
+    events: EventStream<InputEvent>,
+}
+
+impl Recorder {
+    /// Starts recording the events delivered to `keyboard` and `mouse`.
+    pub fn new(keyboard: &Keyboard, mouse: &Mouse) -> Self {
+        let events = EventStream::with_capacity(usize::MAX);
+        keyboard.attach_input_stream(&events);
+        mouse.attach_input_stream(&events);
+        Recorder { events }
+    }
+
+    /// Stops recording and returns the events captured.
+    pub fn finish(mut self) -> Recording {
+        let mut events: Vec<InputEvent> = std::iter::from_fn(|| self.events.try_next()).collect();
+        events.sort_by_key(InputEvent::sequence);
+        Recording { events }
+    }
+}
+
+/**
+Delivers a [`Recording`] to a keyboard and mouse.
+
+Events are delivered the same way the platform delivers them, so the devices' state, buffers,
+streams and callbacks all observe them.  Each event is stamped with the time the replay started
+plus the event's time in the recording, divided by the speed.
+
+Replay either in real time with [`Player::play`], or deterministically by telling the player how
+much time has passed with [`Player::advance_to`].
+*/
+#[derive(Debug)]
+pub struct Player {
+    events: Vec<InputEvent>,
+    next: usize,
+    speed: f64,
+    start: Option<Timestamp>,
+    keyboard: Arc<keyboard::Shared>,
+    mouse: Arc<mouse::Shared>,
+}
+
+impl Player {
+    /// Creates a player that delivers `recording` to `keyboard` and `mouse`.
+    pub fn new(recording: &Recording, keyboard: &Keyboard, mouse: &Mouse) -> Self {
+        Player {
+            events: recording.events.clone(),
+            next: 0,
+            speed: 1.0,
+            start: None,
+            keyboard: keyboard.shared().clone(),
+            mouse: mouse.shared().clone(),
+        }
+    }
+
+    /// Returns a copy of this player that replays `speed` times as fast as the recording.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not a positive, finite number.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        assert!(
+            speed > 0.0 && speed.is_finite(),
+            "speed must be positive and finite"
+        );
+        self.speed = speed;
+        self
+    }
+
+    /// Returns a copy of this player whose replay starts at `start`.
+    ///
+    /// By default, the replay starts when the first event is delivered.  Use this with a
+    /// [`ManualClock`](crate::timestamp::ManualClock) to control event timestamps exactly.
+    pub fn with_start(mut self, start: Timestamp) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// Returns `true` if every event has been delivered.
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    /// Returns the time after the start of the replay at which the next event is due.
+    pub fn next_due(&self) -> Option<Duration> {
+        let first = self.events.first()?.timestamp();
+        let event = self.events.get(self.next)?;
+        Some((event.timestamp() - first).div_f64(self.speed))
+    }
+
+    /// Delivers every event due within `elapsed` of the start of the replay, returning how many were delivered.
+    pub fn advance_to(&mut self, elapsed: Duration) -> usize {
+        let start = *self.start.get_or_insert_with(Timestamp::now);
+        let mut delivered = 0;
+        while let Some(due) = self.next_due().filter(|due| *due <= elapsed) {
+            self.deliver(self.events[self.next], start + due);
+            self.next += 1;
+            delivered += 1;
+        }
+        delivered
+    }
+
+    /// Delivers the remaining events in real time, blocking until the last has been delivered.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn play(&mut self) {
+        let start = *self.start.get_or_insert_with(Timestamp::now);
+        while let Some(due) = self.next_due() {
+            let now = Timestamp::now();
+            if start + due > now {
+                std::thread::sleep(start + due - now);
+            }
+            self.advance_to(due);
+        }
+    }
+
+    fn deliver(&self, event: InputEvent, timestamp: Timestamp) {
+        let window = event
+            .window()
+            .map_or(std::ptr::null_mut(), |w| w.0.as_ptr());
+        match event {
+            InputEvent::Key(e) if e.repeat() => {
+                self.keyboard.repeat_key_at(e.key(), window, timestamp)
+            }
+            //losing focus already released the keys, which the recording also contains
+            InputEvent::Key(e) if !e.down() && !self.keyboard.is_pressed(e.key()) => {}
+            InputEvent::Key(e) => {
+                self.keyboard
+                    .set_key_state_at(e.key(), e.down(), window, timestamp)
+            }
+            InputEvent::Button(e) => {
+                self.mouse
+                    .set_key_state_at(e.button(), e.down(), window, timestamp)
+            }
+            InputEvent::Motion(e) => self.mouse.set_window_location(MouseWindowLocation::new(
+                e.pos_x(),
+                e.pos_y(),
+                e.window_width(),
+                e.window_height(),
+                e.window(),
+                timestamp,
+            )),
+            InputEvent::Scroll(e) => {
+                self.mouse
+                    .add_scroll_delta_at(e.delta_x(), e.delta_y(), window, timestamp)
+            }
+            InputEvent::Enter(_) => self.mouse.pointer_entered(window, timestamp),
+            InputEvent::Leave(_) => self.mouse.pointer_left(window, timestamp),
+            InputEvent::Focus(e) if e.focused() => self.keyboard.set_focus_at(window, timestamp),
+            InputEvent::Focus(_) => {
+                self.keyboard.clear_focus_at(window, timestamp);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::context::InputEvent;
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::{KeyboardKey, RawScancode, ScancodeKind};
+    use crate::mouse::{MOUSE_BUTTON_LEFT, Mouse};
+    use crate::record::{Player, Recorder, Recording, RecordingError};
+    use crate::timestamp::Timestamp;
+    use std::time::Duration;
+
+    fn sample() -> Recording {
+        let keyboard = Keyboard::coalesced();
+        let mouse = Mouse::coalesced();
+        let recorder = Recorder::new(&keyboard, &mouse);
+        let keys = keyboard.shared_for_test();
+        let buttons = mouse.shared_for_test();
+        let window = 0x30 as *mut std::ffi::c_void;
+        let at = |ms| Timestamp::from_duration_since_epoch(Duration::from_millis(ms));
+        let unknown = KeyboardKey::Unknown(RawScancode::new(ScancodeKind::Evdev, 0x2ff));
+        keys.set_focus_at(window, at(1000));
+        buttons.pointer_entered(window, at(1000));
+        keys.set_key_state_at(KeyboardKey::Shift, true, window, at(1000));
+        keys.repeat_key_at(KeyboardKey::Shift, window, at(1500));
+        keys.set_key_state_at(unknown, true, window, at(1510));
+        buttons.set_window_location(crate::mouse::MouseWindowLocation::new(
+            10.5,
+            20.25,
+            800.0,
+            600.0,
+            None,
+            at(1600),
+        ));
+        buttons.set_key_state_at(MOUSE_BUTTON_LEFT, true, window, at(1700));
+        buttons.add_scroll_delta_at(0.0, -3.5, window, at(1800));
+        buttons.pointer_left(window, at(2000));
+        recorder.finish()
+    }
+
+    fn summary(recording: &Recording) -> Vec<String> {
+        let first = recording.events()[0].timestamp();
+        recording
+            .events()
+            .iter()
+            .map(|event| {
+                let t = (event.timestamp() - first).as_millis();
+                let window = event.window().map(|w| w.0.as_ptr() as usize);
+                let detail = match event {
+                    InputEvent::Key(e) => format!("{} {} {}", e.key(), e.down(), e.repeat()),
+                    InputEvent::Button(e) => format!("{} {}", e.button(), e.down()),
+                    InputEvent::Motion(e) => format!(
+                        "{} {} {} {}",
+                        e.pos_x(),
+                        e.pos_y(),
+                        e.window_width(),
+                        e.window_height()
+                    ),
+                    InputEvent::Scroll(e) => format!("{} {}", e.delta_x(), e.delta_y()),
+                    InputEvent::Focus(e) => format!("focus {}", e.focused()),
+                    InputEvent::Enter(_) => "enter".to_string(),
+                    InputEvent::Leave(_) => "leave".to_string(),
+                };
+                format!("{t} {window:?} {detail}")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let recording = sample();
+        assert_eq!(recording.events().len(), 9);
+        let expected = summary(&recording);
+        let json = Recording::from_json_lines(&recording.to_json_lines()).unwrap();
+        assert_eq!(summary(&json), expected);
+        let binary = Recording::from_binary(&recording.to_binary()).unwrap();
+        assert_eq!(summary(&binary), expected);
+        assert!(recording.to_binary().len() < recording.to_json_lines().len() / 2);
+
+        let path = std::env::temp_dir().join(format!("app_input_{}.rec", std::process::id()));
+        for format in [
+            super::RecordingFormat::JsonLines,
+            super::RecordingFormat::Binary,
+        ] {
+            recording.save(&path, format).unwrap();
+            assert_eq!(summary(&Recording::load(&path).unwrap()), expected);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_out_of_order_round_trip() {
+        //the pointer is stamped on receipt, motion with the earlier platform time
+        let mouse = Mouse::coalesced();
+        let recorder = Recorder::new(&Keyboard::coalesced(), &mouse);
+        let buttons = mouse.shared_for_test();
+        let at = |ms| Timestamp::from_duration_since_epoch(Duration::from_millis(ms));
+        buttons.pointer_entered(std::ptr::null_mut(), at(1000));
+        buttons.set_key_state_at(MOUSE_BUTTON_LEFT, true, std::ptr::null_mut(), at(1020));
+        buttons.add_scroll_delta_at(0.0, 1.0, std::ptr::null_mut(), at(1010));
+        buttons.set_key_state_at(MOUSE_BUTTON_LEFT, false, std::ptr::null_mut(), at(1030));
+        let recording = recorder.finish();
+        let expected = summary(&recording);
+        assert_eq!(expected[2], "10 None 0 1");
+        let binary = Recording::from_binary(&recording.to_binary()).unwrap();
+        assert_eq!(summary(&binary), expected);
+        let json = Recording::from_json_lines(&recording.to_json_lines()).unwrap();
+        assert_eq!(summary(&json), expected);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let header = "{\"format\":\"app_input-recording\",\"version\":1}\n";
+        let bad_key = format!(
+            "{header}{{\"t\":0,\"type\":\"key\",\"key\":\"Nope\",\"down\":true,\"repeat\":false}}\n"
+        );
+        match Recording::from_json_lines(&bad_key) {
+            Err(RecordingError::Malformed { position: 2, .. }) => {}
+            other => panic!("unexpected {other:?}"),
+        }
+        let future = "{\"format\":\"app_input-recording\",\"version\":2}\n";
+        assert!(matches!(
+            Recording::from_json_lines(future),
+            Err(RecordingError::UnsupportedVersion(2))
+        ));
+        let wrapped = "{\"format\":\"app_input-recording\",\"version\":4294967297}\n";
+        assert!(matches!(
+            Recording::from_json_lines(wrapped),
+            Err(RecordingError::Malformed { position: 1, .. })
+        ));
+        let mut truncated = sample().to_binary();
+        truncated.pop();
+        assert!(matches!(
+            Recording::from_binary(&truncated),
+            Err(RecordingError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_player() {
+        let recording = sample();
+        let keyboard = Keyboard::coalesced();
+        let mouse = Mouse::coalesced();
+        let start = Timestamp::from_duration_since_epoch(Duration::from_secs(100));
+        let mut player = Player::new(&recording, &keyboard, &mouse)
+            .with_speed(2.0)
+            .with_start(start);
+        //focus, enter and the shift press are all at the start
+        assert_eq!(player.advance_to(Duration::ZERO), 3);
+        assert!(keyboard.is_pressed(KeyboardKey::Shift));
+        assert_eq!(keyboard.focused_window().unwrap().0.as_ptr() as usize, 0x30);
+        //at double speed, the repeat 500ms in is due at 250ms
+        assert_eq!(player.advance_to(Duration::from_millis(249)), 0);
+        assert_eq!(player.advance_to(Duration::from_millis(250)), 1);
+        assert_eq!(player.next_due(), Some(Duration::from_millis(255)));
+        player.advance_to(Duration::from_secs(10));
+        assert!(player.is_finished());
+        assert!(mouse.button_state(MOUSE_BUTTON_LEFT));
+        assert_eq!(mouse.window_pos().unwrap().pos_x(), 10.5);
+
+        let times: Vec<_> = keyboard
+            .drain_events()
+            .iter()
+            .map(|e| e.timestamp() - start)
+            .collect();
+        assert_eq!(times, [0, 250, 255].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn test_play_last_button() {
+        let text = "{\"format\":\"app_input-recording\",\"version\":1}\n\
+            {\"t\":0,\"type\":\"button\",\"button\":255,\"down\":true}\n";
+        let recording = Recording::from_json_lines(text).unwrap();
+        let binary = Recording::from_binary(&recording.to_binary()).unwrap();
+        for recording in [recording, binary] {
+            let mouse = Mouse::coalesced();
+            Player::new(&recording, &Keyboard::coalesced(), &mouse).advance_to(Duration::ZERO);
+            assert!(mouse.button_state(u8::MAX));
+        }
+    }
+}
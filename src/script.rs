@@ -0,0 +1,583 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Scripted input for tests.
+//!
+//! A [`Script`](crate::script::Script) is a list of input steps, written in a small text language
+//! or built in code.  A [`Simulation`](crate::script::Simulation) runs scripts against a
+//! [virtual keyboard and mouse](crate::virtual_device) whose time is a
+//! [`ManualClock`](crate::timestamp::ManualClock), so time-dependent behavior such as holds,
+//! double-clicks and key repeat is tested deterministically, without a window system.
+//!
+//! # Language
+//!
+//! Steps are separated by `;` or newlines.  `#` starts a comment that runs to the end of the line.
+//!
+//! | Step | Meaning |
+//! |------|---------|
+//! | `press <key>` | Press a key |
+//! | `release <key>` | Release a key |
+//! | `tap <key>` | Press and release a key |
+//! | `wait <duration>` | Advance the clock, for example `150ms` or `2s` |
+//! | `move <x>,<y>` | Move the pointer |
+//! | `down <button>` | Press a mouse button |
+//! | `up <button>` | Release a mouse button |
+//! | `click <button>` | Press and release a mouse button |
+//! | `scroll <dx>,<dy>` | Scroll |
+//!
+//! Keys are named as parsed by [`KeyboardKey`](crate::keyboard::key::KeyboardKey)'s `FromStr`, so
+//! `Ctrl`, `S` and `Enter` all work.  Buttons are `left`, `right`, `middle` or a button number.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::interaction::{Interaction, Recognizer};
+//! use app_input::keyboard::key::KeyboardKey;
+//! use app_input::script::Simulation;
+//!
+//! let mut sim = Simulation::new();
+//! let mut hold = Recognizer::with_clock(KeyboardKey::E, Interaction::hold(), sim.clock().clone());
+//!
+//! sim.run(&"press Ctrl; tap S; press E; wait 600ms".parse().unwrap());
+//! sim.assert_pressed(KeyboardKey::Control);
+//! sim.assert_released(KeyboardKey::S);
+//!
+//! for event in &sim.keyboard().drain_events() {
+//!     hold.feed_key_event(event);
+//! }
+//! assert!(hold.poll());
+//! ```
+
+use crate::keyboard::Keyboard;
+use crate::keyboard::key::KeyboardKey;
+use crate::keyboard::modifiers::Modifiers;
+use crate::mouse::{MOUSE_BUTTON_LEFT, MOUSE_BUTTON_MIDDLE, MOUSE_BUTTON_RIGHT, Mouse};
+use crate::timestamp::{Clock, ManualClock, Timestamp};
+use crate::virtual_device::{VirtualKeyboard, VirtualMouse};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// One step of a [`Script`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Step {
+    /// Press a key.
+    Press(KeyboardKey),
+    /// Release a key.
+    Release(KeyboardKey),
+    /// Press and release a key.
+    Tap(KeyboardKey),
+    /// Advance the clock.
+    Wait(Duration),
+    /// Move the pointer.
+    Move {
+        /// The horizontal position.
+        x: f64,
+        /// The vertical position.
+        y: f64,
+    },
+    /// Press a mouse button.
+    ButtonDown(u8),
+    /// Release a mouse button.
+    ButtonUp(u8),
+    /// Press and release a mouse button.
+    Click(u8),
+    /// Scroll.
+    Scroll {
+        /// The horizontal delta.
+        delta_x: f64,
+        /// The vertical delta.
+        delta_y: f64,
+    },
+}
+
+/// The error returned when a script cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    line: usize,
+    message: String,
+}
+
+impl ScriptError {
+    /// The 1-based line number of the step that could not be parsed.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// A description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/**
+A list of input steps for a [`Simulation`].
+
+Build one with the step methods, or parse one from the language described in the
+[module documentation](crate::script).
+
+```
+use app_input::keyboard::key::KeyboardKey;
+use app_input::mouse::MOUSE_BUTTON_LEFT;
+use app_input::script::Script;
+use std::time::Duration;
+
+let built = Script::new()
+    .press(KeyboardKey::Control)
+    .tap(KeyboardKey::S)
+    .wait(Duration::from_millis(150))
+    .move_to(10.0, 20.0)
+    .click(MOUSE_BUTTON_LEFT)
+    .scroll(0.0, -3.0);
+let parsed: Script = "press Ctrl; tap S; wait 150ms; move 10,20; click left; scroll 0,-3"
+    .parse()
+    .unwrap();
+assert_eq!(built, parsed);
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Script::default()
+    }
+
+    /// Returns the steps in order.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Appends a step.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Appends a key press.
+    pub fn press(self, key: KeyboardKey) -> Self {
+        self.step(Step::Press(key))
+    }
+
+    /// Appends a key release.
+    pub fn release(self, key: KeyboardKey) -> Self {
+        self.step(Step::Release(key))
+    }
+
+    /// Appends a key press and release.
+    pub fn tap(self, key: KeyboardKey) -> Self {
+        self.step(Step::Tap(key))
+    }
+
+    /// Appends a wait.
+    pub fn wait(self, duration: Duration) -> Self {
+        self.step(Step::Wait(duration))
+    }
+
+    /// Appends a pointer movement.
+    pub fn move_to(self, x: f64, y: f64) -> Self {
+        self.step(Step::Move { x, y })
+    }
+
+    /// Appends a button press.
+    pub fn button_down(self, button: u8) -> Self {
+        self.step(Step::ButtonDown(button))
+    }
+
+    /// Appends a button release.
+    pub fn button_up(self, button: u8) -> Self {
+        self.step(Step::ButtonUp(button))
+    }
+
+    /// Appends a button press and release.
+    pub fn click(self, button: u8) -> Self {
+        self.step(Step::Click(button))
+    }
+
+    /// Appends a scroll.
+    pub fn scroll(self, delta_x: f64, delta_y: f64) -> Self {
+        self.step(Step::Scroll { delta_x, delta_y })
+    }
+}
+
+fn parse_duration(s: &str) -> Option<Duration> {
+    if let Some(ms) = s.strip_suffix("ms") {
+        ms.trim().parse().ok().map(Duration::from_millis)
+    } else if let Some(secs) = s.strip_suffix('s') {
+        secs.trim()
+            .parse()
+            .ok()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+    } else {
+        None
+    }
+}
+
+fn parse_button(s: &str) -> Option<u8> {
+    match s.to_ascii_lowercase().as_str() {
+        "left" => Some(MOUSE_BUTTON_LEFT),
+        "right" => Some(MOUSE_BUTTON_RIGHT),
+        "middle" => Some(MOUSE_BUTTON_MIDDLE),
+        other => other.parse().ok(),
+    }
+}
+
+fn parse_pair(s: &str) -> Option<(f64, f64)> {
+    let (a, b) = s.split_once(',')?;
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+fn parse_step(statement: &str) -> Result<Step, String> {
+    let (command, argument) = statement
+        .split_once(char::is_whitespace)
+        .map_or((statement, ""), |(c, a)| (c, a.trim()));
+    let key = || -> Result<KeyboardKey, String> { argument.parse().map_err(|e| format!("{e}")) };
+    let button = || parse_button(argument).ok_or_else(|| format!("invalid button {argument:?}"));
+    let pair = || parse_pair(argument).ok_or_else(|| format!("expected `x,y`, found {argument:?}"));
+    let step = match command.to_ascii_lowercase().as_str() {
+        "press" => Step::Press(key()?),
+        "release" => Step::Release(key()?),
+        "tap" => Step::Tap(key()?),
+        "wait" => Step::Wait(
+            parse_duration(argument).ok_or_else(|| format!("invalid duration {argument:?}"))?,
+        ),
+        "move" => {
+            let (x, y) = pair()?;
+            Step::Move { x, y }
+        }
+        "down" => Step::ButtonDown(button()?),
+        "up" => Step::ButtonUp(button()?),
+        "click" => Step::Click(button()?),
+        "scroll" => {
+            let (delta_x, delta_y) = pair()?;
+            Step::Scroll { delta_x, delta_y }
+        }
+        _ => return Err(format!("unknown step {command:?}")),
+    };
+    Ok(step)
+}
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut script = Script::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split_once('#').map_or(line, |(code, _)| code);
+            for statement in line.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+                let step = parse_step(statement).map_err(|message| ScriptError {
+                    line: index + 1,
+                    message,
+                })?;
+                script.steps.push(step);
+            }
+        }
+        Ok(script)
+    }
+}
+
+/**
+Runs [`Script`]s against a virtual keyboard and mouse with a simulated clock.
+
+The clock starts at the epoch and only moves during waits.  Pass [`Simulation::clock`] to
+time-dependent code under test, such as a
+[`Recognizer`](crate::interaction::Recognizer::with_clock), so that it sees the same time as the
+events.
+
+Platforms repeat the most recently pressed key while it is held, except for modifiers and locks.
+To simulate that, enable
+[`Simulation::with_key_repeat`]; waits then deliver repeats at the times they would occur.
+*/
+#[derive(Debug)]
+pub struct Simulation {
+    keyboard: VirtualKeyboard,
+    mouse: VirtualMouse,
+    clock: ManualClock,
+    /// The delay before the first repeat, and the interval between repeats.
+    key_repeat: Option<(Duration, Duration)>,
+    /// The key being repeated, and when it next repeats.
+    repeating: Option<(KeyboardKey, Timestamp)>,
+}
+
+impl Simulation {
+    /// Creates a simulation with a new virtual keyboard and mouse, and key repeat disabled.
+    pub fn new() -> Self {
+        let clock = ManualClock::new();
+        Simulation {
+            keyboard: Keyboard::virtual_device().with_clock(clock.clone()),
+            mouse: Mouse::virtual_device().with_clock(clock.clone()),
+            clock,
+            key_repeat: None,
+            repeating: None,
+        }
+    }
+
+    /// Returns a copy of this simulation that repeats held keys after `delay`, then every `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn with_key_repeat(mut self, delay: Duration, interval: Duration) -> Self {
+        assert!(!interval.is_zero(), "repeat interval must be nonzero");
+        self.key_repeat = Some((delay, interval));
+        self
+    }
+
+    /// Returns the simulated clock.
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    /// Returns the simulated keyboard.
+    pub fn keyboard(&self) -> &Keyboard {
+        self.keyboard.keyboard()
+    }
+
+    /// Returns the simulated mouse.
+    pub fn mouse(&self) -> &Mouse {
+        self.mouse.mouse()
+    }
+
+    /// Returns the virtual keyboard, for input the script language doesn't cover, such as focus.
+    pub fn virtual_keyboard(&self) -> &VirtualKeyboard {
+        &self.keyboard
+    }
+
+    /// Returns the virtual mouse, for input the script language doesn't cover, such as windows.
+    pub fn virtual_mouse(&self) -> &VirtualMouse {
+        &self.mouse
+    }
+
+    /// Runs every step of `script`.
+    pub fn run(&mut self, script: &Script) {
+        for step in script.steps() {
+            self.step(*step);
+        }
+    }
+
+    /// Runs one step.
+    pub fn step(&mut self, step: Step) {
+        match step {
+            Step::Press(key) => self.press(key),
+            Step::Release(key) => self.release(key),
+            Step::Tap(key) => {
+                self.press(key);
+                self.release(key);
+            }
+            Step::Wait(duration) => self.wait(duration),
+            Step::Move { x, y } => self.mouse.move_to(x, y, None),
+            Step::ButtonDown(button) => self.mouse.press(button),
+            Step::ButtonUp(button) => self.mouse.release(button),
+            Step::Click(button) => self.mouse.click(button),
+            Step::Scroll { delta_x, delta_y } => self.mouse.scroll(delta_x, delta_y),
+        }
+    }
+
+    fn press(&mut self, key: KeyboardKey) {
+        self.keyboard.press(key);
+        //modifiers and locks don't repeat, and don't interrupt the key that does
+        if Modifiers::from_key(key).is_empty() && Modifiers::lock_from_key(key).is_empty() {
+            self.repeating = self
+                .key_repeat
+                .map(|(delay, _)| (key, self.clock.now() + delay));
+        }
+    }
+
+    fn release(&mut self, key: KeyboardKey) {
+        self.keyboard.release(key);
+        if self
+            .repeating
+            .is_some_and(|(repeating, _)| repeating == key)
+        {
+            self.repeating = None;
+        }
+    }
+
+    /// Advances the clock by `duration`, delivering any key repeats that fall due.
+    pub fn wait(&mut self, duration: Duration) {
+        let end = self.clock.now() + duration;
+        while let Some((key, due)) = self.repeating.filter(|(_, due)| *due <= end) {
+            self.clock.set(due);
+            self.keyboard.repeat(key);
+            let (_, interval) = self.key_repeat.unwrap();
+            self.repeating = Some((key, due + interval));
+        }
+        self.clock.set(end);
+    }
+
+    /// Panics unless `key` is pressed.
+    #[track_caller]
+    pub fn assert_pressed(&self, key: KeyboardKey) {
+        assert!(
+            self.keyboard().is_pressed(key),
+            "expected {key} to be pressed"
+        );
+    }
+
+    /// Panics if `key` is pressed.
+    #[track_caller]
+    pub fn assert_released(&self, key: KeyboardKey) {
+        assert!(
+            !self.keyboard().is_pressed(key),
+            "expected {key} to be released"
+        );
+    }
+
+    /// Panics unless `button` is pressed.
+    #[track_caller]
+    pub fn assert_button_pressed(&self, button: u8) {
+        assert!(
+            self.mouse().button_state(button),
+            "expected button {button} to be pressed"
+        );
+    }
+
+    /// Panics if `button` is pressed.
+    #[track_caller]
+    pub fn assert_button_released(&self, button: u8) {
+        assert!(
+            !self.mouse().button_state(button),
+            "expected button {button} to be released"
+        );
+    }
+
+    /// Panics unless the pointer is at `x`, `y`.
+    #[track_caller]
+    pub fn assert_pointer_at(&self, x: f64, y: f64) {
+        let position = self.mouse().window_pos().map(|p| (p.pos_x(), p.pos_y()));
+        assert_eq!(position, Some((x, y)), "unexpected pointer position");
+    }
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::action::Input;
+    use crate::interaction::{Interaction, Recognizer};
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT};
+    use crate::script::{Script, Simulation, Step};
+    use crate::timestamp::Clock;
+    use std::time::Duration;
+
+    #[test]
+    fn test_run_script() {
+        let script: Script = "
+            press Ctrl; tap S   # save
+            wait 150ms
+            move 10,20; click left; down right
+            scroll 0,-3
+        "
+        .parse()
+        .unwrap();
+        assert_eq!(script.steps().len(), 7);
+        assert_eq!(script.steps()[2], Step::Wait(Duration::from_millis(150)));
+
+        let mut sim = Simulation::new();
+        let mut double_click = Recognizer::with_clock(
+            Input::MouseButton(MOUSE_BUTTON_LEFT),
+            Interaction::double_tap(),
+            sim.clock().clone(),
+        );
+        sim.run(&script);
+        sim.assert_pressed(KeyboardKey::Control);
+        sim.assert_released(KeyboardKey::S);
+        sim.assert_button_pressed(MOUSE_BUTTON_RIGHT);
+        sim.assert_button_released(MOUSE_BUTTON_LEFT);
+        sim.assert_pointer_at(10.0, 20.0);
+        assert_eq!(
+            sim.clock().now().duration_since_epoch(),
+            Duration::from_millis(150)
+        );
+
+        sim.run(&"wait 100ms; click left".parse().unwrap());
+        let recognized = sim
+            .mouse()
+            .drain_button_events()
+            .iter()
+            .filter(|e| e.button() == MOUSE_BUTTON_LEFT)
+            .any(|e| double_click.feed_button_event(e));
+        assert!(recognized);
+    }
+
+    #[test]
+    fn test_key_repeat() {
+        let mut sim = Simulation::new()
+            .with_key_repeat(Duration::from_millis(500), Duration::from_millis(50));
+        sim.run(
+            &Script::new()
+                .press(KeyboardKey::A)
+                .wait(Duration::from_millis(620)),
+        );
+        sim.run(&"release A; wait 1s".parse().unwrap());
+        let repeats: Vec<_> = sim
+            .keyboard()
+            .drain_events()
+            .iter()
+            .filter(|e| e.repeat())
+            .map(|e| e.timestamp().duration_since_epoch().as_millis())
+            .collect();
+        assert_eq!(repeats, [500, 550, 600]);
+
+        //pressing a modifier doesn't repeat, or stop the held key repeating
+        sim.run(
+            &"press S; wait 500ms; press Ctrl; press CapsLock; wait 100ms"
+                .parse()
+                .unwrap(),
+        );
+        let repeats: Vec<_> = sim
+            .keyboard()
+            .drain_events()
+            .iter()
+            .filter(|e| e.repeat())
+            .map(|e| e.key())
+            .collect();
+        assert_eq!(repeats, [KeyboardKey::S; 3]);
+    }
+
+    #[test]
+    fn test_tap_stops_repeat() {
+        //a tap takes over the repeat from the held key, and releasing it stops the repeat
+        let mut sim = Simulation::new()
+            .with_key_repeat(Duration::from_millis(500), Duration::from_millis(50));
+        sim.run(&"press A; wait 100ms; tap B; wait 1s".parse().unwrap());
+        sim.assert_pressed(KeyboardKey::A);
+        assert!(!sim.keyboard().drain_events().iter().any(|e| e.repeat()));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let error = "tap A\nwait soon".parse::<Script>().unwrap_err();
+        assert_eq!(error.line(), 2);
+        assert!("hover 1,2".parse::<Script>().is_err());
+        assert!("tap Hyper".parse::<Script>().is_err());
+        assert!("move 1".parse::<Script>().is_err());
+        assert!("click sideways".parse::<Script>().is_err());
+        assert!("click 256".parse::<Script>().is_err());
+    }
+
+    #[test]
+    fn test_last_button() {
+        let mut sim = Simulation::new();
+        sim.run(&"down 255".parse().unwrap());
+        sim.assert_button_pressed(u8::MAX);
+        sim.run(&"click 255".parse().unwrap());
+        sim.assert_button_released(u8::MAX);
+    }
+}
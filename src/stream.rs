@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Awaiting input instead of polling for it.
+//!
+//! [`Keyboard::events`](crate::keyboard::Keyboard::events) and
+//! [`Mouse::events`](crate::mouse::Mouse::events) return an
+//! [`EventStream`](crate::stream::EventStream), which buffers every event received after it was
+//! created and hands them out in order from [`EventStream::next`](crate::stream::EventStream::next).
+//! Each stream has its own buffer, so streams do not compete with each other or with
+//! [`Keyboard::drain_events`](crate::keyboard::Keyboard::drain_events).
+//!
+//! Streams are woken by the thread that delivers input, so they work with any executor.  For
+//! one-off waits, [`Keyboard::next_press`](crate::keyboard::Keyboard::next_press) and
+//! [`Mouse::next_click`](crate::mouse::Mouse::next_click) are more convenient.
+//!
+//! With the `stream` feature, [`EventStream`](crate::stream::EventStream) also implements
+//! `futures_core::Stream`.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//!
+//! async fn wait_for_escape(keyboard: &Keyboard) {
+//!     let mut events = keyboard.events();
+//!     loop {
+//!         let event = events.next().await;
+//!         if event.key() == KeyboardKey::Escape && event.down() {
+//!             return;
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::queue::EventQueue;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug)]
+struct ChannelState<E> {
+    queue: EventQueue<E>,
+    waker: Option<Waker>,
+}
+
+/// The buffer shared between an [`EventStream`] and the device that feeds it.
+#[derive(Debug)]
+struct Channel<E> {
+    state: Mutex<ChannelState<E>>,
+}
+
+/// The streams subscribed to a device.
+#[derive(Debug)]
+pub(crate) struct Subscribers<E> {
+    channels: Mutex<Vec<Weak<Channel<E>>>>,
+}
+
+impl<E: Clone> Subscribers<E> {
+    pub(crate) fn new() -> Self {
+        Subscribers {
+            channels: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn subscribe(&self, capacity: usize) -> EventStream<E> {
+        let stream = EventStream::with_capacity(capacity);
+        self.attach(&stream);
+        stream
+    }
+
+    /// Delivers events to an existing stream, which may also receive events from other devices.
+    pub(crate) fn attach(&self, stream: &EventStream<E>) {
+        self.channels
+            .lock()
+            .unwrap()
+            .push(Arc::downgrade(&stream.channel));
+    }
+
+    /// Delivers an event to every stream, and drops streams that no longer exist.
+    pub(crate) fn send(&self, event: &E) {
+        let mut wakers = Vec::new();
+        self.channels.lock().unwrap().retain(|channel| {
+            let Some(channel) = channel.upgrade() else {
+                return false;
+            };
+            let mut state = channel.state.lock().unwrap();
+            state.queue.push(event.clone());
+            wakers.extend(state.waker.take());
+            true
+        });
+        //wake outside the locks, in case a waker polls synchronously and subscribes again
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/**
+An asynchronous sequence of input events.
+
+Events are buffered from the moment the stream is created, up to the device's event capacity (see
+[`Keyboard::set_event_capacity`](crate::keyboard::Keyboard::set_event_capacity)).  If more arrive
+before they are consumed, the oldest are discarded.
+
+Input never ends, so the stream never finishes.
+*/
+#[derive(Debug)]
+pub struct EventStream<E> {
+    channel: Arc<Channel<E>>,
+}
+
+impl<E> EventStream<E> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        EventStream {
+            channel: Arc::new(Channel {
+                state: Mutex::new(ChannelState {
+                    queue: EventQueue::new(capacity),
+                    waker: None,
+                }),
+            }),
+        }
+    }
+
+    /// Waits for the next event.
+    #[allow(clippy::should_implement_trait)] //an async counterpart, like `StreamExt::next`
+    pub fn next(&mut self) -> Next<'_, E> {
+        Next { stream: self }
+    }
+
+    /// Returns the next event if one is buffered, without waiting.
+    pub fn try_next(&mut self) -> Option<E> {
+        self.channel.state.lock().unwrap().queue.pop()
+    }
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<E> {
+        let mut state = self.channel.state.lock().unwrap();
+        match state.queue.pop() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The future returned by [`EventStream::next`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct Next<'a, E> {
+    stream: &'a mut EventStream<E>,
+}
+
+impl<E> Future for Next<'_, E> {
+    type Output = E;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.stream.poll_event(cx)
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<E> futures_core::Stream for EventStream<E> {
+    type Item = E;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_event(cx).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT, Mouse, MouseEvent};
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::{Context, Poll, Wake, Waker};
+
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_stream_wakes() {
+        let keyboard = Keyboard::coalesced();
+        let shared = keyboard.shared_for_test();
+        let mut events = keyboard.events();
+        let mut other = keyboard.events();
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+        {
+            let mut next = pin!(events.next());
+            assert!(next.as_mut().poll(&mut cx).is_pending());
+            shared.set_key_state(KeyboardKey::Q, true, std::ptr::null_mut());
+            assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+            let Poll::Ready(event) = next.as_mut().poll(&mut cx) else {
+                panic!("expected an event");
+            };
+            assert_eq!((event.key(), event.down()), (KeyboardKey::Q, true));
+        }
+        assert!(events.try_next().is_none());
+        //each stream has its own buffer
+        assert_eq!(other.try_next().map(|e| e.key()), Some(KeyboardKey::Q));
+    }
+
+    struct SubscribingWaker(Arc<Keyboard>, AtomicUsize);
+
+    impl Wake for SubscribingWaker {
+        fn wake(self: Arc<Self>) {
+            drop(self.0.events());
+            self.1.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_waker_subscribes() {
+        let keyboard = Arc::new(Keyboard::coalesced());
+        let waker = Arc::new(SubscribingWaker(keyboard.clone(), AtomicUsize::new(0)));
+        let task_waker = Waker::from(waker.clone());
+        let mut cx = Context::from_waker(&task_waker);
+        let mut events = keyboard.events();
+        assert!(pin!(events.next()).poll(&mut cx).is_pending());
+        keyboard
+            .shared_for_test()
+            .set_key_state(KeyboardKey::Q, true, std::ptr::null_mut());
+        assert_eq!(waker.1.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_next_press_and_click() {
+        let keyboard = Keyboard::coalesced();
+        let mouse = Mouse::coalesced();
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut press = pin!(keyboard.next_press());
+        let mut click = pin!(mouse.next_click(MOUSE_BUTTON_LEFT));
+        assert!(press.as_mut().poll(&mut cx).is_pending());
+        assert!(click.as_mut().poll(&mut cx).is_pending());
+
+        let keys = keyboard.shared_for_test();
+        keys.set_key_state(KeyboardKey::Z, true, std::ptr::null_mut());
+        keys.repeat_key(KeyboardKey::Z, std::ptr::null_mut());
+        let Poll::Ready(event) = press.as_mut().poll(&mut cx) else {
+            panic!("expected a press");
+        };
+        assert!(!event.repeat());
+
+        let buttons = mouse.shared_for_test();
+        let mut events = mouse.events();
+        buttons.set_key_state(MOUSE_BUTTON_RIGHT, true, std::ptr::null_mut());
+        buttons.set_key_state(MOUSE_BUTTON_LEFT, true, std::ptr::null_mut());
+        assert!(click.as_mut().poll(&mut cx).is_pending());
+        buttons.set_key_state(MOUSE_BUTTON_LEFT, false, std::ptr::null_mut());
+        let Poll::Ready(event) = click.as_mut().poll(&mut cx) else {
+            panic!("expected a click");
+        };
+        assert_eq!((event.button(), event.down()), (MOUSE_BUTTON_LEFT, false));
+
+        buttons.add_scroll_delta(0.0, 3.0, std::ptr::null_mut());
+        let kinds: Vec<_> = std::iter::from_fn(|| events.try_next())
+            .map(|e| matches!(e, MouseEvent::Scroll(_)))
+            .collect();
+        assert_eq!(kinds, [false, false, false, true]);
+    }
+}
@@ -0,0 +1,305 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Timestamps for input events.
+//!
+//! Input events are stamped with a [`Timestamp`](crate::timestamp::Timestamp), which is a monotonic point in time measured
+//! from a process-wide epoch.  Timestamps are comparable with each other and can be subtracted
+//! to find the time between two events.
+//!
+//! Where the platform stamps events itself, those stamps are converted to the same epoch, so an
+//! event's timestamp reflects when the user acted rather than when the application got around to
+//! reading the event.
+
+use std::ops::{Add, Sub};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/**
+A monotonic point in time at which an input event occurred.
+
+# Epoch
+
+Timestamps are measured from an arbitrary process-wide epoch.  The epoch is fixed for the lifetime
+of the process, so timestamps can be compared with each other, but their absolute value carries no meaning.
+
+# Platform specifics
+
+* On wasm32, the epoch is the page's time origin (the same clock as `performance.now()`).
+* On other platforms, the epoch is the first time this crate needed a timestamp.
+
+# Event timestamps
+
+Events carry the platform's own timestamp where one is available, converted to this epoch:
+
+* On Linux, the `time` of each Wayland event, or the high-resolution time from
+  `zwp_input_timestamps_v1` if the application forwards it.
+* On Windows, `GetMessageTime`.
+* On wasm32, the DOM event's `timeStamp`, which already uses this epoch.
+* On macOS, events are stamped when they are received.
+
+Converted timestamps are never later than [`Timestamp::now`] at the time the event is processed.
+*/
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timestamp(Duration);
+
+impl Timestamp {
+    /// Returns the current time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::timestamp::Timestamp;
+    ///
+    /// let earlier = Timestamp::now();
+    /// let later = Timestamp::now();
+    /// assert!(later >= earlier);
+    /// ```
+    pub fn now() -> Self {
+        Timestamp(sys_now())
+    }
+
+    /// Creates a timestamp from the time elapsed since the epoch.
+    ///
+    /// This is mostly useful for tests and for replaying recorded input.
+    pub const fn from_duration_since_epoch(duration: Duration) -> Self {
+        Timestamp(duration)
+    }
+
+    /// Returns the time elapsed between the epoch and this timestamp.
+    pub const fn duration_since_epoch(self) -> Duration {
+        self.0
+    }
+
+    /// Returns the time elapsed from `earlier` to `self`, or zero if `earlier` is later than `self`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app_input::timestamp::Timestamp;
+    /// use std::time::Duration;
+    ///
+    /// let a = Timestamp::from_duration_since_epoch(Duration::from_millis(10));
+    /// let b = Timestamp::from_duration_since_epoch(Duration::from_millis(25));
+    /// assert_eq!(b.saturating_duration_since(a), Duration::from_millis(15));
+    /// assert_eq!(a.saturating_duration_since(b), Duration::ZERO);
+    /// ```
+    pub fn saturating_duration_since(self, earlier: Timestamp) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+    fn add(self, rhs: Duration) -> Self::Output {
+        Timestamp(self.0 + rhs)
+    }
+}
+
+impl Sub<Timestamp> for Timestamp {
+    type Output = Duration;
+    /// Equivalent to [`Timestamp::saturating_duration_since`].
+    fn sub(self, rhs: Timestamp) -> Self::Output {
+        self.saturating_duration_since(rhs)
+    }
+}
+
+/**
+A source of the current time.
+
+Time-dependent logic, such as [`Recognizer`](crate::interaction::Recognizer), reads the time from a
+`Clock` so that tests can substitute a [`ManualClock`].
+*/
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Timestamp;
+}
+
+/// The clock that stamps input events.  See [`Timestamp::now`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+/**
+A clock that only moves when told to, for tests.
+
+Clones share the same time, so a test can keep one clone and hand another to the code under test.
+
+# Example
+
+```
+use app_input::timestamp::{Clock, ManualClock};
+use std::time::Duration;
+
+let clock = ManualClock::new();
+let shared = clock.clone();
+clock.advance(Duration::from_millis(150));
+assert_eq!(shared.now().duration_since_epoch(), Duration::from_millis(150));
+```
+*/
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    /// Creates a clock reading the epoch.
+    pub fn new() -> Self {
+        ManualClock::default()
+    }
+
+    /// Sets the current time.
+    pub fn set(&self, time: Timestamp) {
+        self.0.store(time.0.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Moves the current time forward.
+    pub fn advance(&self, duration: Duration) {
+        self.0
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        Timestamp(Duration::from_nanos(self.0.load(Ordering::Relaxed)))
+    }
+}
+
+/**
+Converts times from a platform clock into [`Timestamp`]s.
+
+Platform clocks have their own epochs (boot time on Windows, unspecified on Wayland), so we
+estimate the offset between the platform clock and ours.  An event is always received after it
+occurred, so the smallest difference seen between our time of receipt and the platform time is
+the best estimate of the offset: it differs from the true offset only by the shortest delivery
+latency.  As a result, converted timestamps are never in the future and keep the platform's
+spacing between events.
+
+If an event appears to have occurred much too long ago, we assume the platform clock was reset
+(for example, because the compositor restarted) and start over.
+*/
+#[derive(Debug)]
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+pub(crate) struct PlatformClock {
+    /// Our time minus platform time, in nanoseconds.
+    offset: Option<i128>,
+    /// The most recent conversion, so that conversions never go backward.
+    last: Option<Timestamp>,
+    /// The most recent millisecond count passed to [`PlatformClock::convert_millis`], and how many
+    /// times the count has wrapped.
+    millis: Option<(u32, u64)>,
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+impl PlatformClock {
+    /// Events that appear older than this cause the offset to be estimated again.
+    const RESYNC_THRESHOLD: Duration = Duration::from_secs(10);
+
+    pub(crate) const fn new() -> Self {
+        PlatformClock {
+            offset: None,
+            last: None,
+            millis: None,
+        }
+    }
+
+    /// Converts a platform time, given as the time since the platform's epoch, for an event received at `now`.
+    pub(crate) fn convert(&mut self, platform: Duration, now: Timestamp) -> Timestamp {
+        let platform = platform.as_nanos() as i128;
+        let now_nanos = now.0.as_nanos() as i128;
+        let observed = now_nanos - platform;
+        let offset = match self.offset {
+            Some(offset) if observed < offset => observed,
+            Some(offset) if observed - offset > Self::RESYNC_THRESHOLD.as_nanos() as i128 => {
+                logwise::debuginternal_sync!("Platform clock jumped; resynchronizing");
+                self.last = None;
+                observed
+            }
+            Some(offset) => offset,
+            None => observed,
+        };
+        self.offset = Some(offset);
+        //our epoch is the earliest time we can represent
+        let converted = Timestamp(Duration::from_nanos((platform + offset).max(0) as u64));
+        let converted = self.last.map_or(converted, |last| converted.max(last));
+        self.last = Some(converted);
+        converted
+    }
+
+    /// Converts a platform time given as a wrapping 32-bit millisecond count.
+    pub(crate) fn convert_millis(&mut self, millis: u32, now: Timestamp) -> Timestamp {
+        let wraps = match self.millis {
+            //a large backward step is a wrap; a small one is an event delivered out of order
+            Some((last, wraps)) if millis < last && last - millis > u32::MAX / 2 => wraps + 1,
+            Some((_, wraps)) => wraps,
+            None => 0,
+        };
+        self.millis = Some((millis, wraps));
+        let extended = (wraps << 32) | millis as u64;
+        self.convert(Duration::from_millis(extended), now)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn sys_now() -> Duration {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn sys_now() -> Duration {
+    //Instant is not available on wasm32-unknown-unknown
+    let millis = web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    Duration::from_secs_f64(millis / 1000.0)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::timestamp::{PlatformClock, Timestamp};
+    use std::time::Duration;
+
+    fn ms(ms: u64) -> Timestamp {
+        Timestamp::from_duration_since_epoch(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn test_platform_clock_offset() {
+        let mut clock = PlatformClock::new();
+        //platform epoch is 5000ms before ours; the first event took 20ms to arrive
+        assert_eq!(clock.convert(Duration::from_millis(5100), ms(120)), ms(120));
+        //a faster delivery improves the estimate
+        assert_eq!(clock.convert(Duration::from_millis(5200), ms(205)), ms(205));
+        //a slow delivery keeps the platform's spacing, and is never later than now
+        assert_eq!(clock.convert(Duration::from_millis(5300), ms(400)), ms(305));
+        //a reset platform clock starts over
+        assert_eq!(
+            clock.convert(Duration::from_millis(10), ms(20_000)),
+            ms(20_000)
+        );
+        assert_eq!(
+            clock.convert(Duration::from_millis(60), ms(20_070)),
+            ms(20_050)
+        );
+    }
+
+    #[test]
+    fn test_platform_clock_millis_wrap() {
+        let mut clock = PlatformClock::new();
+        let before = clock.convert_millis(u32::MAX - 10, ms(1000));
+        let after = clock.convert_millis(20, ms(1040));
+        assert_eq!(after - before, Duration::from_millis(31));
+        //slightly out of order is not a wrap
+        let late = clock.convert_millis(15, ms(1041));
+        assert_eq!(late, after);
+    }
+}
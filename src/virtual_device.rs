@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: MPL-2.0
+
+//! Keyboards and mice driven by code instead of hardware, for tests.
+//!
+//! [`Keyboard::virtual_device`](crate::keyboard::Keyboard::virtual_device) and
+//! [`Mouse::virtual_device`](crate::mouse::Mouse::virtual_device) create devices that are not
+//! connected to the platform.  They need no window system, window or Wayland object.  Input is
+//! injected through the returned [`VirtualKeyboard`](crate::virtual_device::VirtualKeyboard) and
+//! [`VirtualMouse`](crate::virtual_device::VirtualMouse), and is delivered exactly as platform
+//! input is: device state, buffers, streams, callbacks and
+//! [`Recorder`](crate::record::Recorder)s all observe it.
+//!
+//! Events are stamped from a [`Clock`](crate::timestamp::Clock), by default the system clock.
+//! Substitute a [`ManualClock`](crate::timestamp::ManualClock) to control timestamps exactly.
+//!
+//! Virtual keyboards report key presses only.  They do not produce text, IME or modifier state
+//! beyond what follows from the keys held.
+//!
+//! # Example
+//!
+//! ```
+//! use app_input::keyboard::{Keyboard, key::KeyboardKey};
+//! use app_input::mouse::{MOUSE_BUTTON_LEFT, Mouse};
+//! use app_input::timestamp::ManualClock;
+//! use std::time::Duration;
+//!
+//! let clock = ManualClock::new();
+//! let keyboard = Keyboard::virtual_device().with_clock(clock.clone());
+//! let mouse = Mouse::virtual_device().with_clock(clock.clone());
+//!
+//! keyboard.press(KeyboardKey::Shift);
+//! clock.advance(Duration::from_millis(100));
+//! mouse.click(MOUSE_BUTTON_LEFT);
+//!
+//! let held_for = mouse.mouse().drain_button_events()[0].timestamp()
+//!     - keyboard.keyboard().pressed_since(KeyboardKey::Shift).unwrap();
+//! assert_eq!(held_for, Duration::from_millis(100));
+//! ```
+
+use crate::Window;
+use crate::keyboard::Keyboard;
+use crate::keyboard::key::KeyboardKey;
+use crate::mouse::{Mouse, MouseWindowLocation};
+use crate::timestamp::{Clock, SystemClock, Timestamp};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+
+type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+fn window_ptr(window: Option<Window>) -> *mut c_void {
+    window.map_or(std::ptr::null_mut(), |window| window.0.as_ptr())
+}
+
+/**
+Injects input into a keyboard created by
+[`Keyboard::virtual_device`](crate::keyboard::Keyboard::virtual_device).
+
+Key events are delivered to the most recently [focused](VirtualKeyboard::focus) window, or to no
+window.
+*/
+pub struct VirtualKeyboard {
+    keyboard: Keyboard,
+    clock: SharedClock,
+    window: AtomicPtr<c_void>,
+}
+
+impl std::fmt::Debug for VirtualKeyboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VirtualKeyboard")
+            .field("keyboard", &self.keyboard)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VirtualKeyboard {
+    pub(crate) fn new(keyboard: Keyboard) -> Self {
+        VirtualKeyboard {
+            keyboard,
+            clock: Arc::new(SystemClock),
+            window: AtomicPtr::new(std::ptr::null_mut()),
+        }
+    }
+
+    /// Returns a copy of this device that stamps events with `clock`.
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Returns the keyboard that receives the injected input.
+    pub fn keyboard(&self) -> &Keyboard {
+        &self.keyboard
+    }
+
+    /// Returns the current time of the device's clock.
+    pub fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    fn window(&self) -> *mut c_void {
+        self.window.load(Ordering::Relaxed)
+    }
+
+    /// Presses `key`.
+    pub fn press(&self, key: KeyboardKey) {
+        self.keyboard
+            .shared()
+            .set_key_state_at(key, true, self.window(), self.now());
+    }
+
+    /// Releases `key`.
+    pub fn release(&self, key: KeyboardKey) {
+        self.keyboard
+            .shared()
+            .set_key_state_at(key, false, self.window(), self.now());
+    }
+
+    /// Presses and immediately releases `key`.
+    pub fn tap(&self, key: KeyboardKey) {
+        self.press(key);
+        self.release(key);
+    }
+
+    /// Delivers an auto-repeat of `key`, as the platform does while a key is held.
+    pub fn repeat(&self, key: KeyboardKey) {
+        self.keyboard
+            .shared()
+            .repeat_key_at(key, self.window(), self.now());
+    }
+
+    /// Gives keyboard focus to `window`.
+    pub fn focus(&self, window: Option<Window>) {
+        let window = window_ptr(window);
+        self.window.store(window, Ordering::Relaxed);
+        self.keyboard.shared().set_focus_at(window, self.now());
+    }
+
+    /// Takes keyboard focus away from the focused window, which releases every held key.
+    pub fn unfocus(&self) {
+        let window = self.window.swap(std::ptr::null_mut(), Ordering::Relaxed);
+        self.keyboard.shared().clear_focus_at(window, self.now());
+    }
+}
+
+/**
+Injects input into a mouse created by [`Mouse::virtual_device`](crate::mouse::Mouse::virtual_device).
+
+Button and scroll events are delivered to the window the pointer last [moved](VirtualMouse::move_to)
+over.  Window sizes are reported as set by [`VirtualMouse::set_window_size`], and as zero until
+then.
+*/
+pub struct VirtualMouse {
+    mouse: Mouse,
+    clock: SharedClock,
+    window: AtomicPtr<c_void>,
+    window_size: Mutex<(f64, f64)>,
+}
+
+impl std::fmt::Debug for VirtualMouse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VirtualMouse")
+            .field("mouse", &self.mouse)
+            .field("window", &self.window)
+            .field("window_size", &self.window_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl VirtualMouse {
+    pub(crate) fn new(mouse: Mouse) -> Self {
+        VirtualMouse {
+            mouse,
+            clock: Arc::new(SystemClock),
+            window: AtomicPtr::new(std::ptr::null_mut()),
+            window_size: Mutex::new((0.0, 0.0)),
+        }
+    }
+
+    /// Returns a copy of this device that stamps events with `clock`.
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Returns the mouse that receives the injected input.
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// Returns the current time of the device's clock.
+    pub fn now(&self) -> Timestamp {
+        self.clock.now()
+    }
+
+    fn window(&self) -> *mut c_void {
+        self.window.load(Ordering::Relaxed)
+    }
+
+    /// Sets the window size reported with later pointer positions.
+    pub fn set_window_size(&self, width: f64, height: f64) {
+        *self.window_size.lock().unwrap() = (width, height);
+    }
+
+    /**
+    Moves the pointer to `x`, `y` within `window`.
+
+    If `window` differs from the window the pointer was over, the pointer [leaves](VirtualMouse::leave)
+    that window and enters this one first.
+    */
+    pub fn move_to(&self, x: f64, y: f64, window: Option<Window>) {
+        let shared = self.mouse.shared();
+        let now = self.now();
+        let window_ptr = window_ptr(window);
+        let previous = self.window.swap(window_ptr, Ordering::Relaxed);
+        if previous != window_ptr {
+            self.left(previous, now);
+            if !window_ptr.is_null() {
+                shared.pointer_entered(window_ptr, now);
+            }
+        }
+        let (width, height) = *self.window_size.lock().unwrap();
+        shared.set_window_location(MouseWindowLocation::new(x, y, width, height, window, now));
+    }
+
+    /// Moves the pointer out of the window it was over, which releases every held button.
+    pub fn leave(&self) {
+        let previous = self.window.swap(std::ptr::null_mut(), Ordering::Relaxed);
+        self.left(previous, self.now());
+    }
+
+    /// Delivers what the platform does when the pointer leaves `window`.
+    fn left(&self, window: *mut c_void, now: Timestamp) {
+        if !window.is_null() {
+            let shared = self.mouse.shared();
+            shared.pointer_left(window, now);
+            shared.release_all_buttons_at(window, now);
+        }
+    }
+
+    /// Presses `button`.
+    pub fn press(&self, button: u8) {
+        self.mouse
+            .shared()
+            .set_key_state_at(button, true, self.window(), self.now());
+    }
+
+    /// Releases `button`.
+    pub fn release(&self, button: u8) {
+        self.mouse
+            .shared()
+            .set_key_state_at(button, false, self.window(), self.now());
+    }
+
+    /// Presses and immediately releases `button`.
+    pub fn click(&self, button: u8) {
+        self.press(button);
+        self.release(button);
+    }
+
+    /// Scrolls by `delta_x`, `delta_y`.
+    pub fn scroll(&self, delta_x: f64, delta_y: f64) {
+        self.mouse
+            .shared()
+            .add_scroll_delta_at(delta_x, delta_y, self.window(), self.now());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Window;
+    use crate::context::InputEvent;
+    use crate::keyboard::Keyboard;
+    use crate::keyboard::key::KeyboardKey;
+    use crate::mouse::{MOUSE_BUTTON_LEFT, MOUSE_BUTTON_RIGHT, Mouse};
+    use crate::record::Recorder;
+    use crate::timestamp::ManualClock;
+    use std::ptr::NonNull;
+    use std::time::Duration;
+
+    #[test]
+    fn test_virtual_devices() {
+        let clock = ManualClock::new();
+        let keyboard = Keyboard::virtual_device().with_clock(clock.clone());
+        let mouse = Mouse::virtual_device().with_clock(clock.clone());
+        let recorder = Recorder::new(keyboard.keyboard(), mouse.mouse());
+        let window = Window(NonNull::new(0x40 as *mut std::ffi::c_void).unwrap());
+
+        keyboard.focus(Some(window));
+        keyboard.press(KeyboardKey::A);
+        clock.advance(Duration::from_millis(500));
+        keyboard.repeat(KeyboardKey::A);
+        mouse.set_window_size(800.0, 600.0);
+        mouse.move_to(5.0, 6.0, Some(window));
+        mouse.click(MOUSE_BUTTON_RIGHT);
+        mouse.scroll(0.0, -3.0);
+        mouse.press(MOUSE_BUTTON_LEFT);
+        mouse.leave();
+        keyboard.unfocus();
+
+        assert!(!keyboard.keyboard().is_pressed(KeyboardKey::A));
+        assert!(!mouse.mouse().button_state(MOUSE_BUTTON_LEFT));
+        assert_eq!(mouse.mouse().window_pos().unwrap().window_width(), 800.0);
+        let events = recorder.finish();
+        let summary: Vec<_> = events
+            .events()
+            .iter()
+            .map(|event| {
+                let kind = match event {
+                    InputEvent::Key(e) => format!("key {} {}", e.down(), e.repeat()),
+                    InputEvent::Button(e) => format!("button {}", e.down()),
+                    InputEvent::Motion(_) => "motion".to_string(),
+                    InputEvent::Scroll(_) => "scroll".to_string(),
+                    InputEvent::Enter(_) => "enter".to_string(),
+                    InputEvent::Leave(_) => "leave".to_string(),
+                    InputEvent::Focus(e) => format!("focus {}", e.focused()),
+                };
+                (kind, event.timestamp().duration_since_epoch().as_millis())
+            })
+            .collect();
+        let expected = [
+            ("focus true", 0),
+            ("key true false", 0),
+            ("key true true", 500),
+            ("enter", 500),
+            ("motion", 500),
+            ("button true", 500),
+            ("button false", 500),
+            ("scroll", 500),
+            ("button true", 500),
+            ("leave", 500),
+            ("button false", 500),
+            ("focus false", 500),
+            ("key false false", 500),
+        ];
+        assert_eq!(
+            summary,
+            expected.map(|(kind, ms)| (kind.to_string(), ms)).to_vec()
+        );
+        assert!(
+            events
+                .events()
+                .iter()
+                .all(|e| e.window().unwrap().0 == window.0)
+        );
+    }
+
+    #[test]
+    fn test_last_button() {
+        let mouse = Mouse::virtual_device();
+        mouse.press(u8::MAX);
+        assert!(mouse.mouse().button_state(u8::MAX));
+        mouse.release(u8::MAX);
+        mouse.click(u8::MAX);
+        assert!(!mouse.mouse().button_state(u8::MAX));
+        assert_eq!(mouse.mouse().drain_button_events().len(), 4);
+    }
+}